@@ -1,5 +1,3 @@
-PRAGMA foreign_keys = ON;
-
 create table if not exists task
 (
     id integer primary key autoincrement,
@@ -0,0 +1,3 @@
+alter table kb_column add column sort_order integer not null default 0;
+
+update kb_column set sort_order = id;
@@ -0,0 +1,22 @@
+alter table task add column created_at integer not null default 0;
+alter table task add column updated_at integer not null default 0;
+alter table task add column completed_at integer;
+
+update task set created_at = unixepoch(), updated_at = unixepoch();
+update task set completed_at = unixepoch()
+  where column_id in (select id from kb_column where name = 'Done');
+
+create table if not exists task_event
+(
+    id integer primary key autoincrement,
+    task_id integer not null,
+    kind text not null,
+    column_id integer,
+    created_at integer not null
+);
+
+create index if not exists task_event_task_id on task_event(task_id);
+
+-- Give the tasks that already exist a starting point in their history
+insert into task_event(task_id, kind, column_id, created_at)
+  select id, 'created', column_id, created_at from task;
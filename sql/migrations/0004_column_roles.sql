@@ -0,0 +1,10 @@
+alter table kb_column add column role text not null default 'backlog';
+
+-- Best guess for existing boards, based on the names of the default columns
+update kb_column set role =
+  case name
+    when 'InProgress' then 'active'
+    when 'Done' then 'done'
+    when 'Ideas' then 'parked'
+    else 'backlog'
+  end;
@@ -0,0 +1,29 @@
+-- Full text index over the tasks, kept in sync by the triggers below
+create virtual table if not exists task_fts using fts5(
+    title,
+    description,
+    content = 'task',
+    content_rowid = 'id'
+);
+
+create trigger if not exists task_fts_insert after insert on task
+begin
+  insert into task_fts(rowid, title, description)
+    values (new.id, new.title, new.description);
+end;
+
+create trigger if not exists task_fts_delete after delete on task
+begin
+  insert into task_fts(task_fts, rowid, title, description)
+    values ('delete', old.id, old.title, old.description);
+end;
+
+create trigger if not exists task_fts_update after update of title, description on task
+begin
+  insert into task_fts(task_fts, rowid, title, description)
+    values ('delete', old.id, old.title, old.description);
+  insert into task_fts(rowid, title, description)
+    values (new.id, new.title, new.description);
+end;
+
+insert into task_fts(task_fts) values ('rebuild');
@@ -0,0 +1,17 @@
+create table if not exists label
+(
+    id integer primary key autoincrement,
+    name text not null unique collate nocase,
+    color text not null default 'blue'
+);
+
+create table if not exists task_label
+(
+    task_id integer not null,
+    label_id integer not null,
+    primary key (task_id, label_id),
+    foreign key (task_id) references task(id) on delete cascade,
+    foreign key (label_id) references label(id) on delete cascade
+);
+
+create index if not exists task_label_label_id on task_label(label_id);
@@ -0,0 +1,4 @@
+-- From 0 (most urgent) to 3, null when the task has no priority
+alter table task add column priority integer;
+
+alter table kb_column add column sort_by_priority integer not null default 0;
@@ -0,0 +1,2 @@
+-- ISO 8601 date like 2024-05-31, null when the task isn't due
+alter table task add column due_date text;
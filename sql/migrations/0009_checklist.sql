@@ -0,0 +1,11 @@
+create table if not exists checklist_item
+(
+    id integer primary key autoincrement,
+    task_id integer not null,
+    text text not null,
+    done integer not null default 0,
+    sort_order integer not null,
+    foreign key (task_id) references task(id) on delete cascade
+);
+
+create index if not exists checklist_item_task_id on checklist_item(task_id, sort_order);
@@ -0,0 +1,12 @@
+-- blocker_id has to be done before blocked_id can start
+create table if not exists task_link
+(
+    blocker_id integer not null,
+    blocked_id integer not null,
+    primary key (blocker_id, blocked_id),
+    check (blocker_id != blocked_id),
+    foreign key (blocker_id) references task(id) on delete cascade,
+    foreign key (blocked_id) references task(id) on delete cascade
+);
+
+create index if not exists task_link_blocked_id on task_link(blocked_id);
@@ -0,0 +1,20 @@
+create table if not exists board
+(
+    id integer primary key autoincrement,
+    name text not null unique collate nocase,
+    selected_column integer not null default 0
+);
+
+-- Until now the database held a single implicit board
+insert into board(id, name, selected_column)
+  values (1, 'Main',
+          coalesce((select cast(value as integer) from app_state where key = 'selected_column'), 0));
+
+-- SQLite can't add a column with both a foreign key and a default, so
+-- deleting a board takes care of its columns itself
+alter table kb_column add column board_id integer not null default 1;
+
+create index if not exists kb_column_board_id on kb_column(board_id, sort_order);
+
+delete from app_state where key = 'selected_column';
+insert into app_state(key, value) values ('selected_board', '1');
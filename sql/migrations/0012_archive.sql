@@ -0,0 +1,5 @@
+-- Archived tasks keep their column so they can be restored to it, but
+-- aren't shown on the board anymore
+alter table task add column archived_at integer;
+
+create index if not exists task_column_id on task(column_id, archived_at);
@@ -0,0 +1,3 @@
+-- Deleted tasks stay in the trash, off the board, until they're
+-- restored or purged
+alter table task add column deleted_at integer;
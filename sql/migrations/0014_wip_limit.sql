@@ -0,0 +1,2 @@
+-- The most tasks the column should hold, null when it has no limit
+alter table kb_column add column wip_limit integer;
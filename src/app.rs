@@ -1,10 +1,218 @@
-use anyhow::Error;
+use anyhow::{bail, Error};
+use chrono::NaiveDate;
 use int_enum::IntEnum;
 use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
 use std::cmp::min;
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
 use tui_textarea::TextArea;
 
 use crate::db;
+use crate::due::DUE_DATE_FORMAT;
+use crate::editor::{format_task_for_editor, parse_task_from_editor};
+use crate::export::{export_to_string, ExportFormat};
+use crate::keymap::Keymap;
+use crate::layout::{BoardLayout, TaskView};
+use crate::mouse::ClickAreas;
+use crate::stats::FlowMetrics;
+use crate::theme::Theme;
+use crate::undo::{History, Operation};
+
+/// What a [`Column`] means in the workflow, which is what the stats
+/// rely on instead of the column's name or position.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnRole {
+    /// Work that is planned but not started
+    Backlog,
+    /// Work in progress
+    Active,
+    /// Completed work
+    Done,
+    /// Work that is set aside and doesn't count towards the progress
+    Parked,
+}
+
+impl ColumnRole {
+    /// All the roles, in the order they are cycled through.
+    pub const ALL: [ColumnRole; 4] = [
+        ColumnRole::Backlog,
+        ColumnRole::Active,
+        ColumnRole::Done,
+        ColumnRole::Parked,
+    ];
+
+    /// The name used to store the role in the DB.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ColumnRole::Backlog => "backlog",
+            ColumnRole::Active => "active",
+            ColumnRole::Done => "done",
+            ColumnRole::Parked => "parked",
+        }
+    }
+
+    /// The inverse of [`as_str`][`ColumnRole::as_str`]. Returns `None`
+    /// for unknown names.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        ColumnRole::ALL.into_iter().find(|role| role.as_str() == name)
+    }
+
+    /// The role that comes after this one, wrapping around.
+    #[must_use]
+    pub fn next(self) -> Self {
+        let idx = ColumnRole::ALL.iter().position(|r| *r == self).unwrap_or(0);
+        ColumnRole::ALL[(idx + 1) % ColumnRole::ALL.len()]
+    }
+}
+
+/// How urgent a [`Task`] is, from [`Priority::P0`] the most urgent to
+/// [`Priority::P3`] the least.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// Drop everything else
+    P0,
+    /// Next in line
+    P1,
+    /// Normal
+    P2,
+    /// Whenever there's time
+    P3,
+}
+
+impl Priority {
+    /// All the priorities, most urgent first.
+    pub const ALL: [Priority; 4] = [Priority::P0, Priority::P1, Priority::P2, Priority::P3];
+
+    /// The label shown in the UI, like `P1`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Priority::P0 => "P0",
+            Priority::P1 => "P1",
+            Priority::P2 => "P2",
+            Priority::P3 => "P3",
+        }
+    }
+
+    /// The number used to store the priority in the DB, which is also
+    /// the one in its name.
+    #[must_use]
+    pub fn level(self) -> i64 {
+        self as i64
+    }
+
+    /// The inverse of [`level`][`Priority::level`]. Returns `None` for
+    /// unknown levels.
+    #[must_use]
+    pub fn from_level(level: i64) -> Option<Self> {
+        Priority::ALL.into_iter().find(|p| p.level() == level)
+    }
+
+    /// Steps through no priority and then [`Priority::ALL`], wrapping
+    /// around, which is how the task form changes it.
+    #[must_use]
+    pub fn cycle(priority: Option<Self>, forward: bool) -> Option<Self> {
+        let idx = priority.map_or(0, |p| p.level() + 1);
+        let next = if forward { idx + 1 } else { idx - 1 };
+        Priority::from_level(next.rem_euclid(5) - 1)
+    }
+}
+
+/// The colors a [`Label`] can be rendered with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelColor {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl LabelColor {
+    /// All the colors, in the order they are cycled through and given
+    /// to new labels.
+    pub const ALL: [LabelColor; 6] = [
+        LabelColor::Red,
+        LabelColor::Green,
+        LabelColor::Yellow,
+        LabelColor::Blue,
+        LabelColor::Magenta,
+        LabelColor::Cyan,
+    ];
+
+    /// The name used to store the color in the DB.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LabelColor::Red => "red",
+            LabelColor::Green => "green",
+            LabelColor::Yellow => "yellow",
+            LabelColor::Blue => "blue",
+            LabelColor::Magenta => "magenta",
+            LabelColor::Cyan => "cyan",
+        }
+    }
+
+    /// The inverse of [`as_str`][`LabelColor::as_str`]. Returns `None`
+    /// for unknown names.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        LabelColor::ALL
+            .into_iter()
+            .find(|color| color.as_str() == name)
+    }
+
+    /// The color that comes after this one, wrapping around.
+    #[must_use]
+    pub fn next(self) -> Self {
+        let idx = LabelColor::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        LabelColor::ALL[(idx + 1) % LabelColor::ALL.len()]
+    }
+}
+
+/// A label that can be put on any number of [`Task`]s.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Label {
+    /// Id provided by the database
+    #[serde(skip)]
+    pub id: i64,
+    /// Name of the label, unique ignoring case
+    pub name: String,
+    /// Color of the chip drawn for the label
+    pub color: LabelColor,
+}
+
+/// One step of the checklist of a [`Task`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    /// What has to be done
+    pub text: String,
+    /// Whether it's done
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// Splits the comma separated labels typed in the task form, dropping
+/// the empty and duplicate ones.
+#[must_use]
+pub fn parse_labels(text: &str) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for name in text.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        if !labels.iter().any(|l| l.eq_ignore_ascii_case(name)) {
+            labels.push(name.to_string());
+        }
+    }
+    labels
+}
 
 /// Represents a kanban column containing the tasks and other metadata.
 #[derive(Debug)]
@@ -13,15 +221,58 @@ pub struct Column {
     pub id: i64,
     /// The name used for the title in the UI
     pub name: String,
+    /// What the column means in the workflow
+    pub role: ColumnRole,
     /// The currently selected [`Task`], which keeps track of the
     /// user's position in a column when the go from one to another
     pub selected_task_idx: usize,
     /// The collection of [`Task`]
     pub tasks: Vec<Task>,
+    /// If `true` the tasks are sorted by [`Priority`] first and by
+    /// their manual order second, otherwise only by the manual order
+    pub sort_by_priority: bool,
+    /// The most tasks the column should hold, `None` when it has no
+    /// limit. See [`WipLimitMode`]
+    pub wip_limit: Option<usize>,
+}
+
+impl Column {
+    /// Returns `true` if the column holds more tasks than its WIP
+    /// limit.
+    #[must_use]
+    pub fn is_over_wip_limit(&self) -> bool {
+        self.wip_limit.is_some_and(|limit| self.tasks.len() > limit)
+    }
+}
+
+/// What happens when adding or moving a [`Task`] would go over the WIP
+/// limit of a [`Column`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WipLimitMode {
+    /// The task goes in anyway and the user is told about it
+    #[default]
+    Warn,
+    /// The task stays where it is
+    Refuse,
+}
+
+/// A kanban board owning its [`Column`]s. A database can hold several
+/// of them, like a personal board and a team board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    /// Id provided by the database
+    pub id: i64,
+    /// The name shown in the title and in the board picker
+    pub name: String,
+    /// The index of the [`Column`] that was selected when the user
+    /// last left the board
+    pub selected_column_idx: usize,
 }
 
 /// Basic TODO task with a title and a description.
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Task {
     /// Id provided by the database
     pub id: i64,
@@ -29,11 +280,151 @@ pub struct Task {
     pub title: String,
     /// Description of the [`Task`]
     pub description: String,
+    /// Unix timestamp of when the [`Task`] was created
+    pub created_at: i64,
+    /// Unix timestamp of the last time the [`Task`] was edited or moved
+    pub updated_at: i64,
+    /// Unix timestamp of when the [`Task`] was moved to a
+    /// [`ColumnRole::Done`] column, `None` if it's not in one
+    pub completed_at: Option<i64>,
+    /// Names of the [`Label`]s put on the [`Task`], sorted
+    pub labels: Vec<String>,
+    /// How urgent the [`Task`] is, `None` if that wasn't decided
+    pub priority: Option<Priority>,
+    /// The day the [`Task`] should be done by
+    pub due_date: Option<NaiveDate>,
+    /// The steps of the [`Task`], in order
+    pub checklist: Vec<ChecklistItem>,
+    /// IDs of the tasks that have to be done before this one can
+    /// start, sorted
+    pub blocked_by: Vec<i64>,
+    /// IDs of the tasks waiting on this one, sorted
+    pub blocks: Vec<i64>,
+    /// Unix timestamp of when the [`Task`] was archived, `None` while
+    /// it's on the board
+    pub archived_at: Option<i64>,
+}
+
+impl Task {
+    /// How many [`ChecklistItem`]s are done and how many there are.
+    #[must_use]
+    pub fn checklist_progress(&self) -> (usize, usize) {
+        let done = self.checklist.iter().filter(|item| item.done).count();
+        (done, self.checklist.len())
+    }
+}
+
+/// The kind of change recorded in a [`TaskEvent`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskEventKind {
+    /// The [`Task`] was created
+    Created,
+    /// The title and/or description of the [`Task`] changed
+    Edited,
+    /// The [`Task`] entered another [`Column`]
+    Moved,
+    /// The [`Task`] was deleted
+    Deleted,
+    /// A deleted [`Task`] was put back
+    Restored,
+    /// The [`Task`] was taken off the board into the archive
+    Archived,
+    /// An archived [`Task`] was put back on the board
+    Unarchived,
+}
+
+impl TaskEventKind {
+    /// The name used to store the kind in the DB.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskEventKind::Created => "created",
+            TaskEventKind::Edited => "edited",
+            TaskEventKind::Moved => "moved",
+            TaskEventKind::Deleted => "deleted",
+            TaskEventKind::Restored => "restored",
+            TaskEventKind::Archived => "archived",
+            TaskEventKind::Unarchived => "unarchived",
+        }
+    }
+
+    /// The inverse of [`as_str`][`TaskEventKind::as_str`]. Returns
+    /// `None` for unknown names.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "created" => Some(TaskEventKind::Created),
+            "edited" => Some(TaskEventKind::Edited),
+            "moved" => Some(TaskEventKind::Moved),
+            "deleted" => Some(TaskEventKind::Deleted),
+            "restored" => Some(TaskEventKind::Restored),
+            "archived" => Some(TaskEventKind::Archived),
+            "unarchived" => Some(TaskEventKind::Unarchived),
+            _ => None,
+        }
+    }
+}
+
+/// Why a [`Task`] isn't on the board anymore.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum OffBoardKind {
+    /// It was archived
+    #[default]
+    Archived,
+    /// It was deleted and sits in the trash
+    Deleted,
+}
+
+/// A [`Task`] taken off the board, along with the [`Column`] it gets
+/// restored to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OffBoardTask {
+    /// The archived or deleted [`Task`]
+    pub task: Task,
+    /// Id of the [`Column`] it was in
+    pub column_id: i64,
+    /// Name of that [`Column`]
+    pub column_name: String,
+    /// Unix timestamp of when it was archived or deleted
+    pub removed_at: i64,
+}
+
+/// An entry in the activity history of a [`Task`].
+#[derive(Clone, Debug, Serialize)]
+pub struct TaskEvent {
+    /// Id of the [`Task`] the event belongs to
+    pub task_id: i64,
+    /// What happened to the [`Task`]
+    pub kind: TaskEventKind,
+    /// The [`Column`] the [`Task`] was in after the event
+    pub column_id: Option<i64>,
+    /// The name of that [`Column`], `None` if it was deleted since
+    pub column_name: Option<String>,
+    /// Unix timestamp of when it happened
+    pub created_at: i64,
+}
+
+impl TaskEvent {
+    /// Short human readable description of the event, like `moved to Done`.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let column = self.column_name.as_deref().unwrap_or("?");
+        match self.kind {
+            TaskEventKind::Created => format!("created in {column}"),
+            TaskEventKind::Edited => String::from("edited"),
+            TaskEventKind::Moved => format!("moved to {column}"),
+            TaskEventKind::Deleted => String::from("deleted"),
+            TaskEventKind::Restored => format!("restored in {column}"),
+            TaskEventKind::Archived => format!("archived from {column}"),
+            TaskEventKind::Unarchived => format!("unarchived to {column}"),
+        }
+    }
 }
 
 /// The number of `TaskEditFocus` variants, used so we can "wrap around"
 /// with modulo when cycling through tasks with Tab/Backtab.
-pub const EDIT_WINDOW_FOCUS_STATES: i8 = 4;
+pub const EDIT_WINDOW_FOCUS_STATES: i8 = 8;
 
 /// Used to track the focus of the form field in the task edit window.
 #[repr(i8)]
@@ -43,10 +434,18 @@ pub enum TaskEditFocus {
     Title = 0,
     /// Description text input box
     Description = 1,
+    /// Checklist items next to the description
+    Checklist = 2,
+    /// Comma separated labels input line
+    Labels = 3,
+    /// Priority picker
+    Priority = 4,
+    /// Due date input line
+    DueDate = 5,
     /// Confirm changes button
-    ConfirmBtn = 2,
+    ConfirmBtn = 6,
     /// Cancel changes button
-    CancelBtn = 3,
+    CancelBtn = 7,
 }
 
 /// Represents the transient state of a task while it is being editing
@@ -56,6 +455,21 @@ pub struct TaskState<'a> {
     pub title: TextArea<'a>,
     /// The description of the Task
     pub description: TextArea<'a>,
+    /// The labels of the Task, separated by commas
+    pub labels: TextArea<'a>,
+    /// The [`Priority`] of the Task
+    pub priority: Option<Priority>,
+    /// The due date of the Task, as typed by the user
+    pub due_date: TextArea<'a>,
+    /// The checklist of the Task
+    pub checklist: Vec<ChecklistItem>,
+    /// Index of the highlighted item of [`TaskState::checklist`]
+    pub checklist_idx: usize,
+    /// If [`Some(TextArea)`] then a checklist item is being typed
+    pub checklist_input: Option<TextArea<'a>>,
+    /// `true` if [`TaskState::checklist_input`] replaces the text of
+    /// the highlighted item instead of adding a new one
+    pub is_checklist_edit: bool,
     /// Where the current focus of the task edit form is
     pub focus: TaskEditFocus,
     /// Used to decide if the user is editing an existing task or
@@ -68,18 +482,186 @@ impl Default for TaskState<'_> {
         TaskState {
             title: TextArea::default(),
             description: TextArea::default(),
+            labels: TextArea::default(),
+            priority: None,
+            due_date: TextArea::default(),
+            checklist: Vec::new(),
+            checklist_idx: 0,
+            checklist_input: None,
+            is_checklist_edit: false,
             focus: TaskEditFocus::Title,
             is_edit: false,
         }
     }
 }
 
+/// Used to track what the user is doing while managing the columns.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColumnEditMode {
+    /// Selecting and reordering columns
+    Browse,
+    /// Typing the name of a new column
+    Create,
+    /// Typing the new name of the selected column
+    Rename,
+    /// Typing the WIP limit of the selected column
+    WipLimit,
+    /// Waiting for the user to confirm deleting the selected column
+    /// and to choose what happens to its tasks
+    ConfirmDelete,
+}
+
+/// Represents the transient state of the column management mode.
+pub struct ColumnEditState<'a> {
+    /// What the user is currently doing in this mode
+    pub mode: ColumnEditMode,
+    /// The name input used when creating or renaming a column, and
+    /// the limit input when setting its WIP limit
+    pub name: TextArea<'a>,
+}
+
+impl Default for ColumnEditState<'_> {
+    fn default() -> Self {
+        ColumnEditState {
+            mode: ColumnEditMode::Browse,
+            name: TextArea::default(),
+        }
+    }
+}
+
+/// The modes of the board picker popup.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BoardPickerMode {
+    /// Choosing a board
+    Browse,
+    /// Typing the name of a new board
+    Create,
+    /// Typing the new name of the highlighted board
+    Rename,
+}
+
+/// Represents the transient state of the board picker popup.
+pub struct BoardPickerState<'a> {
+    /// What the user is currently doing in the popup
+    pub mode: BoardPickerMode,
+    /// Index of the highlighted [`Board`] in [`State::boards`]
+    pub selected_idx: usize,
+    /// The name input used when creating or renaming a board
+    pub name: TextArea<'a>,
+}
+
+impl Default for BoardPickerState<'_> {
+    fn default() -> Self {
+        BoardPickerState {
+            mode: BoardPickerMode::Browse,
+            selected_idx: 0,
+            name: TextArea::default(),
+        }
+    }
+}
+
+/// Represents the state of the search mode, which filters the tasks
+/// shown on the board.
+pub struct SearchState<'a> {
+    /// The search input
+    pub query: TextArea<'a>,
+    /// `true` while the query is being typed, `false` once it's
+    /// confirmed and only the filter remains
+    pub is_typing: bool,
+    /// IDs of the matching tasks, `None` while the query is empty
+    pub matches: Option<HashSet<i64>>,
+}
+
+impl Default for SearchState<'_> {
+    fn default() -> Self {
+        SearchState {
+            query: TextArea::default(),
+            is_typing: true,
+            matches: None,
+        }
+    }
+}
+
+/// Represents the state of the archive screen, which lists either the
+/// archived tasks of the board or the deleted ones in the trash.
+#[derive(Default)]
+pub struct ArchiveState<'a> {
+    /// Whether the archive or the trash is shown
+    pub kind: OffBoardKind,
+    /// The search input, the tasks are filtered as it's typed
+    pub query: TextArea<'a>,
+    /// Every [`Task`] of the board of that kind, the most recently
+    /// removed first
+    pub tasks: Vec<OffBoardTask>,
+    /// IDs of the matching tasks, `None` while the query is empty
+    pub matches: Option<HashSet<i64>>,
+    /// Index of the highlighted entry of
+    /// [`State::get_archive_entries`]
+    pub selected_idx: usize,
+}
+
+/// Represents the transient state of the help popup listing every
+/// keybinding.
+#[derive(Default)]
+pub struct HelpState {
+    /// Number of lines scrolled past
+    pub scroll: u16,
+}
+
+/// Represents the transient state of the agenda screen.
+#[derive(Default)]
+pub struct AgendaState {
+    /// Index of the highlighted entry of [`State::get_agenda`]
+    pub selected_idx: usize,
+}
+
+/// Represents the transient state of the popup listing the tasks that
+/// can block the selected one.
+#[derive(Default)]
+pub struct LinkState {
+    /// Index of the highlighted entry of [`State::get_link_candidates`]
+    pub selected_idx: usize,
+}
+
+/// Something that waits for the user to confirm it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConfirmAction {
+    /// Moving the selected [`Task`] to position `task_idx` of another
+    /// [`Column`] even though it's blocked
+    MoveBlockedTask { column_id: i64, task_idx: usize },
+    /// Deleting a [`Board`] along with all of its tasks
+    DeleteBoard { board_id: i64 },
+    /// Moving the selected [`Task`] to the trash
+    DeleteTask,
+    /// Deleting a [`Label`], taking it off every [`Task`]
+    DeleteLabel { label_id: i64 },
+}
+
+/// Represents a yes/no question shown in a popup.
+pub struct ConfirmState {
+    /// The question asked to the user
+    pub message: String,
+    /// What happens if the user says yes
+    pub action: ConfirmAction,
+}
+
+/// Represents the transient state of the label filter popup.
+#[derive(Default)]
+pub struct LabelFilterState {
+    /// Index of the highlighted [`Label`] in [`State::labels`]
+    pub selected_idx: usize,
+}
+
 /// Holds the application's state, including all columns and the
 /// database connection.
 pub struct State<'a> {
     /// The name of the project, currently derived from the name of
     /// the current working directory
     pub project_name: String,
+    /// Every [`Board`] of the database, in the order they were created
+    pub boards: Vec<Board>,
+    /// The index of the [`Board`] being shown
+    pub selected_board_idx: usize,
     /// The index of the currently selected [`Column`]
     pub selected_column_idx: usize,
     /// A vec of all the [`Column`]s
@@ -88,8 +670,67 @@ pub struct State<'a> {
     pub db_conn: db::DBConn,
     /// Flag to check on each loop whether we should exit the app
     pub quit: bool,
+    /// Flag to check on each loop whether the task should be opened in
+    /// the user's editor, see [`State::get_editor_text`]
+    pub open_editor: bool,
     /// If [`Some(TaskState)`] then we are in the task edit form window
     pub task_edit_state: Option<TaskState<'a>>,
+    /// If [`Some(ColumnEditState)`] then we are in the column
+    /// management mode
+    pub column_edit_state: Option<ColumnEditState<'a>>,
+    /// If [`Some(FlowMetrics)`] then the expanded stats view is open
+    pub flow_metrics: Option<FlowMetrics>,
+    /// Message shown in place of the keybindings until the next
+    /// keypress
+    pub status_message: Option<String>,
+    /// The [`Operation`]s that can be undone and redone
+    pub history: History,
+    /// If [`Some(SearchState)`] then the board is being searched and
+    /// only the matching tasks are shown
+    pub search: Option<SearchState<'a>>,
+    /// Every [`Label`], sorted by name. Labels are shared by all the
+    /// boards of the database
+    pub labels: Vec<Label>,
+    /// Names of the [`Label`]s the board is limited to, a [`Task`]
+    /// needs one of them to be shown. Empty when not filtering
+    pub label_filter: BTreeSet<String>,
+    /// If [`Some(LabelFilterState)`] then the label filter popup is
+    /// open
+    pub label_filter_state: Option<LabelFilterState>,
+    /// If [`Some(AgendaState)`] then the agenda screen is open
+    pub agenda: Option<AgendaState>,
+    /// If [`Some(LinkState)`] then the blockers of the selected
+    /// [`Task`] are being picked
+    pub link_state: Option<LinkState>,
+    /// If [`Some(ConfirmState)`] then the user has to answer a
+    /// question before anything else
+    pub confirm: Option<ConfirmState>,
+    /// If [`Some(BoardPickerState)`] then the board picker popup is
+    /// open
+    pub board_picker: Option<BoardPickerState<'a>>,
+    /// If [`Some(ArchiveState)`] then the archive or trash screen is
+    /// open
+    pub archive: Option<ArchiveState<'a>>,
+    /// If [`Some(HelpState)`] then the help popup is open
+    pub help: Option<HelpState>,
+    /// Maps the keys to the actions they trigger
+    pub keymap: Keymap,
+    /// Colors and markers the board is drawn with
+    pub theme: Theme,
+    /// Whether going over a WIP limit is refused or only warned about
+    pub wip_limit_mode: WipLimitMode,
+    /// Sizes the board is laid out with
+    pub layout: BoardLayout,
+    /// Index of the leftmost column drawn when they don't all fit
+    pub column_offset: usize,
+    /// Whether the task info, history and stats panels are shown
+    pub show_panels: bool,
+    /// Whether tasks are drawn as a line each or as cards
+    pub task_view: TaskView,
+    /// Where the tasks and buttons were drawn in the last frame
+    pub click_areas: ClickAreas,
+    /// Id of the [`Task`] being dragged with the mouse, if any
+    pub dragged_task: Option<i64>,
 }
 
 impl<'a> State<'a> {
@@ -100,8 +741,20 @@ impl<'a> State<'a> {
     /// Returns an error if we can't read the database columns
     pub fn new(conn: Connection) -> Result<Self, Error> {
         let db_conn = db::DBConn::new(conn);
-        let columns = db_conn.get_all_columns()?;
-        let selected_column = db_conn.get_selected_column()?;
+        let mut boards = db_conn.get_all_boards()?;
+        if boards.is_empty() {
+            boards.push(db_conn.create_board("Main")?);
+        }
+        // Boards can be deleted, so fall back to the first one
+        let selected_board_id = db_conn.get_selected_board()?;
+        let selected_board_idx = boards
+            .iter()
+            .position(|b| Some(b.id) == selected_board_id)
+            .unwrap_or(0);
+        let board = &boards[selected_board_idx];
+        let columns = db_conn.get_all_columns(board.id)?;
+        // Columns can be deleted, so don't trust the saved index blindly
+        let selected_column = min(board.selected_column_idx, columns.len().saturating_sub(1));
 
         let project_name = std::env::current_dir()?
             .file_name()
@@ -111,14 +764,177 @@ impl<'a> State<'a> {
 
         Ok(State {
             project_name,
+            boards,
+            selected_board_idx,
             columns,
             selected_column_idx: selected_column,
             quit: false,
+            open_editor: false,
             task_edit_state: None,
+            column_edit_state: None,
+            flow_metrics: None,
+            status_message: None,
+            history: History::default(),
+            search: None,
+            labels: db_conn.get_all_labels()?,
+            label_filter: BTreeSet::new(),
+            label_filter_state: None,
+            agenda: None,
+            link_state: None,
+            confirm: None,
+            board_picker: None,
+            archive: None,
+            help: None,
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            wip_limit_mode: WipLimitMode::default(),
+            layout: BoardLayout::default(),
+            column_offset: 0,
+            show_panels: true,
+            task_view: TaskView::default(),
+            click_areas: ClickAreas::default(),
+            dragged_task: None,
             db_conn,
         })
     }
 
+    /// Returns a reference to the [`Board`] being shown.
+    #[must_use]
+    pub fn get_selected_board(&self) -> &Board {
+        &self.boards[self.selected_board_idx]
+    }
+
+    /// Shows the [`Board`] at `board_idx`, starting on the [`Column`]
+    /// it was left on, and remembers it for the next launch. The undo
+    /// history, the search and the label filter are about the
+    /// previous board so they get reset.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn switch_board(&mut self, board_idx: usize) -> Result<(), Error> {
+        // Reload so the selected column of the board is up to date
+        self.boards = self.db_conn.get_all_boards()?;
+        self.selected_board_idx = min(board_idx, self.boards.len().saturating_sub(1));
+        let board = &self.boards[self.selected_board_idx];
+        self.columns = self.db_conn.get_all_columns(board.id)?;
+        self.selected_column_idx = min(
+            board.selected_column_idx,
+            self.columns.len().saturating_sub(1),
+        );
+        self.db_conn.set_selected_board(board.id)?;
+        self.history.clear();
+        self.search = None;
+        self.label_filter.clear();
+        Ok(())
+    }
+
+    /// Switches to the [`Board`] with the given name, ignoring case.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no board with that name, SQL
+    /// related errors get bubbled up to here.
+    pub fn switch_board_by_name(&mut self, name: &str) -> Result<(), Error> {
+        let Some(board_idx) = self
+            .boards
+            .iter()
+            .position(|b| b.name.eq_ignore_ascii_case(name))
+        else {
+            bail!("No board named \"{name}\"");
+        };
+        self.switch_board(board_idx)
+    }
+
+    /// Returns `true` if a [`Board`] other than the one at
+    /// `except_idx` already has this name, ignoring case, and tells
+    /// the user in [`State::status_message`]. The DB refuses
+    /// duplicates so this is checked up front.
+    fn is_board_name_taken(&mut self, name: &str, except_idx: Option<usize>) -> bool {
+        let taken = self
+            .boards
+            .iter()
+            .enumerate()
+            .any(|(i, b)| Some(i) != except_idx && b.name.eq_ignore_ascii_case(name));
+        if taken {
+            self.status_message = Some(format!("There is already a board named \"{name}\""));
+        }
+        taken
+    }
+
+    /// Creates a new [`Board`] with the default columns and switches
+    /// to it. A name that is already taken is refused with a
+    /// [`State::status_message`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn add_board(&mut self, name: &str) -> Result<(), Error> {
+        if self.is_board_name_taken(name, None) {
+            return Ok(());
+        }
+        self.db_conn.create_board(name)?;
+        self.switch_board(self.boards.len())
+    }
+
+    /// Renames the [`Board`] at `board_idx`. A name that is already
+    /// taken is refused with a [`State::status_message`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn rename_board(&mut self, board_idx: usize, name: &str) -> Result<(), Error> {
+        if self.is_board_name_taken(name, Some(board_idx)) {
+            return Ok(());
+        }
+        let board = &mut self.boards[board_idx];
+        board.name = name.to_string();
+        self.db_conn.rename_board(board.id, name)?;
+        Ok(())
+    }
+
+    /// Asks the user to confirm deleting the [`Board`] at
+    /// `board_idx`. The last board can't be deleted.
+    pub fn ask_delete_board(&mut self, board_idx: usize) {
+        if self.boards.len() < 2 {
+            self.status_message = Some(String::from("The last board can't be deleted"));
+            return;
+        }
+        let board = &self.boards[board_idx];
+        // The first remaining board takes the tasks in its trash
+        let heir = self
+            .boards
+            .iter()
+            .find(|b| b.id != board.id)
+            .unwrap_or(board);
+        self.confirm = Some(ConfirmState {
+            message: format!(
+                "Delete the board \"{}\"? Its tasks go to the trash of \"{}\"",
+                board.name, heir.name
+            ),
+            action: ConfirmAction::DeleteBoard { board_id: board.id },
+        });
+    }
+
+    /// Deletes a [`Board`] with its columns, sending its tasks to the
+    /// trash of the first remaining board. If it's the one being shown,
+    /// the first remaining board is shown instead.
+    fn delete_board(&mut self, board_id: i64) -> Result<(), Error> {
+        self.db_conn.delete_board(board_id)?;
+        let shown_id = self.get_selected_board().id;
+        self.boards = self.db_conn.get_all_boards()?;
+        if let Some(picker) = &mut self.board_picker {
+            picker.selected_idx = min(picker.selected_idx, self.boards.len().saturating_sub(1));
+        }
+        match self.boards.iter().position(|b| b.id == shown_id) {
+            Some(board_idx) => {
+                self.selected_board_idx = board_idx;
+                Ok(())
+            }
+            None => self.switch_board(0),
+        }
+    }
+
     /// Returns a reference to the currently selected [`Column`].
     #[must_use]
     pub fn get_selected_column(&self) -> &Column {
@@ -139,7 +955,9 @@ impl<'a> State<'a> {
     /// SQL related errors get bubbled up to here.
     pub fn select_column_left(&mut self) -> Result<(), Error> {
         self.selected_column_idx = self.selected_column_idx.saturating_sub(1);
-        self.db_conn.set_selected_column(self.selected_column_idx)
+        self.db_conn
+            .set_selected_column(self.get_selected_board().id, self.selected_column_idx)?;
+        self.reveal_selected_task()
     }
 
     /// Selects the [`Column`] on the right. Does nothing if on the
@@ -150,7 +968,9 @@ impl<'a> State<'a> {
     /// SQL related errors get bubbled up to here.
     pub fn select_column_right(&mut self) -> Result<(), Error> {
         self.selected_column_idx = min(self.selected_column_idx + 1, self.columns.len() - 1);
-        self.db_conn.set_selected_column(self.selected_column_idx)
+        self.db_conn
+            .set_selected_column(self.get_selected_board().id, self.selected_column_idx)?;
+        self.reveal_selected_task()
     }
 
     /// Returns a reference to the currently selected [`Task`].
@@ -189,37 +1009,49 @@ impl<'a> State<'a> {
         column.tasks.get_mut(column.selected_task_idx)
     }
 
-    /// Selects the [`Task`] above the current one. Does nothing if
-    /// it's the first task on the list
+    /// Selects the [`Task`] above the current one, skipping the ones
+    /// hidden by the search. Does nothing if it's the first task on
+    /// the list
     ///
     /// # Errors
     ///
     /// SQL related errors get bubbled up to here.
     pub fn select_task_above(&mut self) -> Result<(), Error> {
+        let column = self.get_selected_column();
+        let task_idx = (0..column.selected_task_idx)
+            .rev()
+            .find(|&i| self.is_task_visible(&column.tasks[i]))
+            .unwrap_or(min(
+                column.selected_task_idx,
+                column.tasks.len().saturating_sub(1),
+            ));
         let column = self.get_selected_column_mut();
-        column.selected_task_idx = column.selected_task_idx.saturating_sub(1);
+        column.selected_task_idx = task_idx;
 
-        let task_idx = column.selected_task_idx;
         let col_id = column.id;
         self.db_conn
             .set_selected_task_for_column(task_idx, col_id)?;
         Ok(())
     }
 
-    /// Selects the [`Task`] below the current one. Does nothing if
-    /// it's the last task on the list
+    /// Selects the [`Task`] below the current one, skipping the ones
+    /// hidden by the search. Does nothing if it's the last task on the
+    /// list
     ///
     /// # Errors
     ///
     /// SQL related errors get bubbled up to here.
     pub fn select_task_below(&mut self) -> Result<(), Error> {
+        let column = self.get_selected_column();
+        let task_idx = (column.selected_task_idx + 1..column.tasks.len())
+            .find(|&i| self.is_task_visible(&column.tasks[i]))
+            .unwrap_or(min(
+                column.selected_task_idx,
+                column.tasks.len().saturating_sub(1),
+            ));
         let column = self.get_selected_column_mut();
-        column.selected_task_idx = min(
-            column.selected_task_idx + 1,
-            column.tasks.len().saturating_sub(1),
-        );
+        column.selected_task_idx = task_idx;
 
-        let task_idx = column.selected_task_idx;
         let col_id = column.id;
         self.db_conn
             .set_selected_task_for_column(task_idx, col_id)?;
@@ -268,7 +1100,14 @@ impl<'a> State<'a> {
         self.get_selected_task().map(|t| TaskState {
             title: TextArea::from(t.title.lines()),
             description: TextArea::from(t.description.lines()),
+            labels: TextArea::from([t.labels.join(", ")]),
             focus: TaskEditFocus::Title,
+            priority: t.priority,
+            due_date: TextArea::from(t.due_date.map(|d| d.format(DUE_DATE_FORMAT).to_string())),
+            checklist: t.checklist.clone(),
+            checklist_idx: 0,
+            checklist_input: None,
+            is_checklist_edit: false,
             is_edit: true,
         })
     }
@@ -302,10 +1141,17 @@ impl<'a> State<'a> {
             self.get_task_above()
         };
         if let (Some(task1), Some(task2)) = (self.get_selected_task(), other_task) {
+            if self.get_selected_column().sort_by_priority && task1.priority != task2.priority {
+                self.status_message = Some(String::from(
+                    "The column is sorted by priority, only tasks with the same one can be reordered",
+                ));
+                return Ok(());
+            }
             let t1_id = task1.id;
             let t2_id = task2.id;
             let column = self.get_selected_column_mut();
             let task_idx = column.selected_task_idx;
+            let from_idx = task_idx;
 
             let other_idx = if is_down { task_idx + 1 } else { task_idx - 1 };
             column.tasks.swap(task_idx, other_idx);
@@ -320,6 +1166,11 @@ impl<'a> State<'a> {
             self.db_conn.swap_task_order(t1_id, t2_id)?;
             self.db_conn
                 .set_selected_task_for_column(task_idx, col_id)?;
+            self.history.record(Operation::ReorderTask {
+                column_id: col_id,
+                from: from_idx,
+                to: task_idx,
+            });
        }
         Ok(())
     }
@@ -331,7 +1182,10 @@ impl<'a> State<'a> {
     ///
     /// SQL related errors get bubbled up to here.
     pub fn move_task_column_left(&mut self) -> Result<(), Error> {
-        self.move_task_to_column(false)
+        match self.selected_column_idx.checked_sub(1) {
+            Some(col_idx) => self.move_task_to(col_idx, self.columns[col_idx].tasks.len()),
+            None => Ok(()),
+        }
     }
 
     /// Moves the current [`Task`] to the [`Column`] on the right. Does
@@ -341,49 +1195,178 @@ impl<'a> State<'a> {
     ///
     /// SQL related errors get bubbled up to here.
     pub fn move_task_column_right(&mut self) -> Result<(), Error> {
-        self.move_task_to_column(true)
+        let col_idx = self.selected_column_idx + 1;
+        match self.columns.get(col_idx) {
+            Some(column) => self.move_task_to(col_idx, column.tasks.len()),
+            None => Ok(()),
+        }
     }
 
-    fn move_task_to_column(&mut self, move_right: bool) -> Result<(), Error> {
-        let can_move_right = move_right && self.selected_column_idx < self.columns.len() - 1;
-        let can_move_left = !move_right && self.selected_column_idx > 0;
-
-        let first_col = self.get_selected_column_mut();
-        if first_col.tasks.is_empty() || !can_move_right && !can_move_left {
-            // We're at the bounds so just ignore
-            return Ok(());
+    /// Moves the selected [`Task`] to position `task_idx` of the
+    /// [`Column`] at `col_idx`, or to its end if `task_idx` is past
+    /// it. This is what `H`/`L` and dropping a dragged card do. Only
+    /// the target column is checked: its WIP limit is handled
+    /// according to [`State::wip_limit_mode`] and moving a blocked
+    /// task into a [`ColumnRole::Active`] one asks for a confirmation
+    /// first. A single undo reverts the whole move.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn move_task_to(&mut self, col_idx: usize, task_idx: usize) -> Result<(), Error> {
+        if col_idx == self.selected_column_idx {
+            self.history.start_batch();
+            let moved = self.reorder_selected_task(task_idx);
+            self.history.finish_batch();
+            moved
+        } else {
+            self.move_task_to_column(col_idx, task_idx, false)
         }
-        let t = first_col.tasks.remove(first_col.selected_task_idx);
+    }
 
-        // Only move it if it was the last task
-        if first_col.selected_task_idx == first_col.tasks.len() {
-            self.select_task_above()?;
+    /// Moves the selected [`Task`] up or down its [`Column`] one step
+    /// at a time until it reaches `task_idx`.
+    fn reorder_selected_task(&mut self, task_idx: usize) -> Result<(), Error> {
+        loop {
+            let column = self.get_selected_column();
+            let from_idx = column.selected_task_idx;
+            let target_idx = min(task_idx, column.tasks.len().saturating_sub(1));
+            if from_idx == target_idx {
+                return Ok(());
+            }
+            self.move_task(target_idx > from_idx)?;
+            // Sorting by priority can keep it from moving any further
+            if self.get_selected_column().selected_task_idx == from_idx {
+                return Ok(());
+            }
         }
+    }
 
-        if move_right {
-            self.select_column_right()?;
-        } else {
-            self.select_column_left()?;
+    /// Moves the selected [`Task`] straight into another [`Column`],
+    /// skipping the blocked check if `force` is `true`.
+    fn move_task_to_column(
+        &mut self,
+        col_idx: usize,
+        task_idx: usize,
+        force: bool,
+    ) -> Result<(), Error> {
+        let from_col_idx = self.selected_column_idx;
+        if col_idx >= self.columns.len() || self.get_selected_task().is_none() {
+            return Ok(());
+        }
+        if !self.check_wip_limit(col_idx) {
+            return Ok(());
+        }
+        let target = &self.columns[col_idx];
+        if let Some(task) = self.get_selected_task().filter(|_| !force) {
+            if target.role == ColumnRole::Active && self.is_task_blocked(task) {
+                self.confirm = Some(ConfirmState {
+                    message: format!(
+                        "\"{}\" is still blocked, move it to {} anyway?",
+                        task.title, target.name
+                    ),
+                    action: ConfirmAction::MoveBlockedTask {
+                        column_id: target.id,
+                        task_idx,
+                    },
+                });
+                return Ok(());
+            }
         }
 
-        let col = self.get_selected_column_mut();
-        col.tasks.push(t);
-        self.select_last_task()?;
+        let from_col = self.get_selected_column_mut();
+        let from_column_id = from_col.id;
+        let from_index = from_col.selected_task_idx;
+        let task = from_col.tasks.remove(from_index);
+        // Keeps the same index so the task below gets selected
+        self.select_task_at(from_col_idx, from_index)?;
+
+        self.db_conn
+            .move_task_to_column(&task, &self.columns[col_idx])?;
+        let target = &mut self.columns[col_idx];
+        let index = min(task_idx, target.tasks.len());
+        target.tasks.insert(index, self.db_conn.get_task(task.id)?);
+        self.save_task_order(col_idx)?;
+        self.select_task_at(col_idx, index)?;
+        self.history.record(Operation::MoveTask {
+            task_id: task.id,
+            from_column_id,
+            from_index,
+            to_column_id: self.columns[col_idx].id,
+            to_index: index,
+        });
+        self.resort_column(col_idx, task.id)
+    }
+
+    /// Reloads the selected [`Task`] from the DB to pick up the
+    /// fields the DB sets on its own, like the timestamps.
+    fn refresh_selected_task(&mut self) -> Result<(), Error> {
         if let Some(task) = self.get_selected_task() {
-            let col = self.get_selected_column();
-            self.db_conn.move_task_to_column(task, col)?;
-            self.db_conn.set_selected_column(self.selected_column_idx)?;
+            let task = self.db_conn.get_task(task.id)?;
+            if let Some(selected_task) = self.get_selected_task_mut() {
+                *selected_task = task;
+            }
         }
         Ok(())
     }
 
+    /// Returns the activity history of the selected [`Task`], oldest
+    /// first. Returns an empty list if the [`Column`] is empty.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn get_selected_task_history(&self) -> Result<Vec<TaskEvent>, Error> {
+        match self.get_selected_task() {
+            Some(task) => Ok(self.db_conn.get_task_events(task.id)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Checks whether one more [`Task`] fits in the [`Column`] at
+    /// `col_idx` under its WIP limit. Returns `false` if the task
+    /// should stay out, which only happens with
+    /// [`WipLimitMode::Refuse`]. Either way [`State::status_message`]
+    /// explains what's wrong when the limit would be broken.
+    pub fn check_wip_limit(&mut self, col_idx: usize) -> bool {
+        let column = &self.columns[col_idx];
+        let Some(limit) = column.wip_limit else {
+            return true;
+        };
+        let count = column.tasks.len() + 1;
+        if count <= limit {
+            return true;
+        }
+        match self.wip_limit_mode {
+            WipLimitMode::Warn => {
+                self.status_message = Some(format!(
+                    "{} is over its WIP limit ({count}/{limit})",
+                    column.name
+                ));
+                true
+            }
+            WipLimitMode::Refuse => {
+                self.status_message = Some(format!(
+                    "{} is at its WIP limit of {limit}, finish something first",
+                    column.name
+                ));
+                false
+            }
+        }
+    }
+
     /// Inserts a new [`Task`] into [`Column::tasks`] at the bottom of
-    /// the list and saves the state to the DB.
+    /// the list and saves the state to the DB. Going over the WIP limit
+    /// of the [`Column`] is handled according to
+    /// [`State::wip_limit_mode`], see [`State::check_wip_limit`].
     ///
     /// # Errors
     ///
     /// SQL related errors get bubbled up to here.
     pub fn add_new_task(&mut self, title: String, description: String) -> Result<(), Error> {
+        if !self.check_wip_limit(self.selected_column_idx) {
+            return Ok(());
+        }
         let col_id = self.get_selected_column().id;
         let task = self.db_conn.create_new_task(title, description, col_id)?;
 
@@ -392,30 +1375,109 @@ impl<'a> State<'a> {
         self.db_conn
             .set_selected_task_for_column(selected_task_idx, col_id)?;
 
+        self.history.record(Operation::AddTask {
+            column_id: col_id,
+            index: self.get_selected_column().tasks.len(),
+            task: task.clone(),
+        });
+        let task_id = task.id;
         self.get_selected_column_mut().tasks.push(task);
         self.select_last_task()?;
+        self.resort_column(self.selected_column_idx, task_id)?;
         Ok(())
     }
 
     /// Edits the selected [`Task`] changing only it's title and/or
-    /// description. Does nothing if the [`Column`] is empty.
+    /// description. Does nothing if the [`Column`] is empty or if
+    /// neither of them changed.
     ///
     /// # Errors
     ///
     /// SQL related errors get bubbled up to here.
     pub fn edit_task(&mut self, title: String, description: String) -> Result<(), Error> {
+        let Some(before) = self.get_selected_task().cloned() else {
+            return Ok(());
+        };
+        if title == before.title && description == before.description {
+            return Ok(());
+        }
         if let Some(selected_task) = self.get_selected_task_mut() {
             selected_task.title = title;
             selected_task.description = description;
         }
         if let Some(task) = self.get_selected_task() {
             self.db_conn.update_task_text(task)?;
+            self.refresh_selected_task()?;
+        }
+        if let Some(after) = self.get_selected_task().cloned() {
+            self.history.record(Operation::EditTask { before, after });
         }
         Ok(())
     }
 
-    /// Deletes the selected [`Task`] from the list. Does nothing if
-    /// the [`Column`] is empty.
+    /// The text to open in the editor when [`State::open_editor`] is
+    /// set, taken from the task form if it's open and from the selected
+    /// [`Task`] otherwise. `None` if there's nothing to edit.
+    #[must_use]
+    pub fn get_editor_text(&self) -> Option<String> {
+        if let Some(task) = &self.task_edit_state {
+            return Some(format_task_for_editor(
+                &task.title.lines().join(" "),
+                &task.description.lines().join("\n"),
+            ));
+        }
+        self.get_selected_task()
+            .map(|task| format_task_for_editor(&task.title, &task.description))
+    }
+
+    /// Reads back the text saved in the editor. It replaces the title
+    /// and description of the task form if it's open, otherwise the
+    /// selected [`Task`] is updated through [`State::edit_task`]. A
+    /// text without a title changes nothing and says so in
+    /// [`State::status_message`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn apply_editor_text(&mut self, text: &str) -> Result<(), Error> {
+        let (title, description) = match parse_task_from_editor(text) {
+            Ok(task) => task,
+            Err(e) => {
+                self.status_message = Some(e.to_string());
+                return Ok(());
+            }
+        };
+        if let Some(task) = &mut self.task_edit_state {
+            task.title = TextArea::from([title]);
+            task.description = TextArea::from(description.lines());
+            return Ok(());
+        }
+        match self.get_selected_task() {
+            // Leave the history alone if the editor was closed without
+            // changes
+            Some(task) if task.title != title || task.description != description => {
+                self.edit_task(title, description)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Asks the user to confirm deleting the selected [`Task`]. Does
+    /// nothing if the [`Column`] is empty.
+    pub fn ask_delete_task(&mut self) {
+        if let Some(task) = self.get_selected_task() {
+            self.confirm = Some(ConfirmState {
+                message: format!(
+                    "Delete \"{}\"? It can be restored from the trash",
+                    task.title
+                ),
+                action: ConfirmAction::DeleteTask,
+            });
+        }
+    }
+
+    /// Moves the selected [`Task`] to the trash. Does nothing if the
+    /// [`Column`] is empty.
     ///
     /// # Errors
     ///
@@ -427,7 +1489,13 @@ impl<'a> State<'a> {
             let mut task_idx = column.selected_task_idx;
             let col_id = column.id;
 
-            column.tasks.remove(task_idx);
+            let task = column.tasks.remove(task_idx);
+            self.history.record(Operation::DeleteTask {
+                column_id: col_id,
+                index: task_idx,
+                task,
+            });
+            let column = self.get_selected_column_mut();
 
             if column.selected_task_idx >= column.tasks.len() {
                 self.select_task_above()?;
@@ -437,7 +1505,1166 @@ impl<'a> State<'a> {
             self.db_conn.delete_task(task_id)?;
             self.db_conn
                 .set_selected_task_for_column(task_idx, col_id)?;
+            self.reload_task_links()?;
         }
         Ok(())
     }
+
+    /// Archives the selected [`Task`], which takes it off the board
+    /// until it's restored from the archive screen. Does nothing if
+    /// the [`Column`] is empty.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn archive_task(&mut self) -> Result<(), Error> {
+        let col_idx = self.selected_column_idx;
+        let column = &mut self.columns[col_idx];
+        let task_idx = column.selected_task_idx;
+        if task_idx >= column.tasks.len() {
+            return Ok(());
+        }
+        let task = column.tasks.remove(task_idx);
+        self.db_conn.archive_task(task.id)?;
+        self.history.record(Operation::ArchiveTask {
+            column_id: column.id,
+            index: task_idx,
+            task,
+        });
+        self.select_task_at(col_idx, task_idx)
+    }
+
+    /// Opens the archive screen with every archived [`Task`] of the
+    /// board, or every deleted one for [`OffBoardKind::Deleted`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn open_archive(&mut self, kind: OffBoardKind) -> Result<(), Error> {
+        let board_id = self.get_selected_board().id;
+        let tasks = match kind {
+            OffBoardKind::Archived => self.db_conn.get_archived_tasks(board_id)?,
+            OffBoardKind::Deleted => self.db_conn.get_trashed_tasks(board_id)?,
+        };
+        self.archive = Some(ArchiveState {
+            kind,
+            tasks,
+            ..ArchiveState::default()
+        });
+        Ok(())
+    }
+
+    /// The tasks matching the query of the archive screen. Empty if it
+    /// isn't open.
+    #[must_use]
+    pub fn get_archive_entries(&self) -> Vec<&OffBoardTask> {
+        let Some(archive) = &self.archive else {
+            return Vec::new();
+        };
+        archive
+            .tasks
+            .iter()
+            .filter(|a| {
+                archive
+                    .matches
+                    .as_ref()
+                    .map_or(true, |m| m.contains(&a.task.id))
+            })
+            .collect()
+    }
+
+    /// Updates the matches of the archive screen after its query
+    /// changed, keeping the highlighted entry in range.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn update_archive_search(&mut self) -> Result<(), Error> {
+        if let Some(archive) = &mut self.archive {
+            let query = archive.query.lines().join(" ");
+            archive.matches = if query.trim().is_empty() {
+                None
+            } else if archive.kind == OffBoardKind::Deleted {
+                Some(self.db_conn.search_trashed_tasks(&query)?)
+            } else {
+                Some(self.db_conn.search_tasks(&query)?)
+            };
+        }
+        let last_idx = self.get_archive_entries().len().saturating_sub(1);
+        if let Some(archive) = &mut self.archive {
+            archive.selected_idx = min(archive.selected_idx, last_idx);
+        }
+        Ok(())
+    }
+
+    /// Puts the highlighted entry of the archive screen back at the
+    /// end of its [`Column`] and selects it.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn restore_off_board_task(&mut self) -> Result<(), Error> {
+        let Some((kind, selected_idx)) = self.archive.as_ref().map(|a| (a.kind, a.selected_idx))
+        else {
+            return Ok(());
+        };
+        let Some(archived) = self
+            .get_archive_entries()
+            .get(selected_idx)
+            .copied()
+            .cloned()
+        else {
+            return Ok(());
+        };
+        // The first column takes the task if its own one is gone
+        let col_idx = self.find_column(archived.column_id).unwrap_or(0);
+        let index = self.columns[col_idx].tasks.len();
+        let (column_id, task) = (self.columns[col_idx].id, archived.task.clone());
+        let operation = match kind {
+            OffBoardKind::Archived => {
+                self.db_conn.unarchive_task(task.id)?;
+                Operation::UnarchiveTask {
+                    column_id,
+                    index,
+                    task,
+                }
+            }
+            OffBoardKind::Deleted => {
+                self.db_conn.restore_task(&task, column_id)?;
+                Operation::AddTask {
+                    column_id,
+                    index,
+                    task,
+                }
+            }
+        };
+        self.history.record(operation);
+        self.columns[col_idx]
+            .tasks
+            .push(self.db_conn.get_task(archived.task.id)?);
+        self.select_task_at(col_idx, index)?;
+        self.resort_column(col_idx, archived.task.id)?;
+        self.reload_task_links()?;
+        self.status_message = Some(format!(
+            "Restored \"{}\" to {}",
+            archived.task.title, self.columns[col_idx].name
+        ));
+
+        if let Some(archive) = &mut self.archive {
+            archive.tasks.retain(|a| a.task.id != archived.task.id);
+        }
+        self.update_archive_search()
+    }
+
+    /// Inserts a new [`Column`] at the end of the board, selects it
+    /// and saves the state to the DB. New columns are
+    /// [`ColumnRole::Active`] since they are usually workflow steps.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn add_column(&mut self, name: String) -> Result<(), Error> {
+        let board_id = self.get_selected_board().id;
+        let column = self
+            .db_conn
+            .create_column(board_id, name, ColumnRole::Active)?;
+        self.columns.push(column);
+        self.selected_column_idx = self.columns.len() - 1;
+        self.db_conn
+            .set_selected_column(self.get_selected_board().id, self.selected_column_idx)
+    }
+
+    /// Renames the selected [`Column`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn rename_column(&mut self, name: String) -> Result<(), Error> {
+        let column = self.get_selected_column_mut();
+        column.name = name;
+        let col_id = column.id;
+        self.db_conn
+            .rename_column(col_id, &self.get_selected_column().name)?;
+        Ok(())
+    }
+
+    /// Sets the WIP limit of the selected [`Column`], `None` removing
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn set_column_wip_limit(&mut self, wip_limit: Option<usize>) -> Result<(), Error> {
+        let column = self.get_selected_column_mut();
+        column.wip_limit = wip_limit;
+        let col_id = column.id;
+        self.db_conn.set_column_wip_limit(col_id, wip_limit)?;
+        Ok(())
+    }
+
+    /// Changes the [`ColumnRole`] of the selected [`Column`] to the
+    /// next one.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn cycle_column_role(&mut self) -> Result<(), Error> {
+        let column = self.get_selected_column_mut();
+        column.role = column.role.next();
+        let (col_id, role) = (column.id, column.role);
+        self.db_conn.set_column_role(col_id, role)?;
+        Ok(())
+    }
+
+    /// Moves the selected [`Column`] one position to the left. Does
+    /// nothing if it's the first column.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn move_column_left(&mut self) -> Result<(), Error> {
+        self.move_column(false)
+    }
+
+    /// Moves the selected [`Column`] one position to the right. Does
+    /// nothing if it's the last column.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn move_column_right(&mut self) -> Result<(), Error> {
+        self.move_column(true)
+    }
+
+    fn move_column(&mut self, move_right: bool) -> Result<(), Error> {
+        let col_idx = self.selected_column_idx;
+        let other_idx = if move_right {
+            col_idx + 1
+        } else if col_idx > 0 {
+            col_idx - 1
+        } else {
+            return Ok(());
+        };
+        if other_idx >= self.columns.len() {
+            return Ok(());
+        }
+
+        let col1_id = self.columns[col_idx].id;
+        let col2_id = self.columns[other_idx].id;
+        self.columns.swap(col_idx, other_idx);
+        self.selected_column_idx = other_idx;
+
+        self.db_conn.swap_column_order(col1_id, col2_id)?;
+        self.db_conn
+            .set_selected_column(self.get_selected_board().id, self.selected_column_idx)
+    }
+
+    /// Returns the index of the [`Column`] that receives the tasks of
+    /// the selected column when it gets deleted, which is the one on
+    /// its left, or the one on its right if it's the first column.
+    /// Returns `None` if it's the only column.
+    #[must_use]
+    pub fn get_column_delete_target(&self) -> Option<usize> {
+        if self.columns.len() < 2 {
+            None
+        } else if self.selected_column_idx > 0 {
+            Some(self.selected_column_idx - 1)
+        } else {
+            Some(1)
+        }
+    }
+
+    /// Deletes the selected [`Column`]. When `move_tasks` is true its
+    /// tasks are appended to the column returned by
+    /// [`get_column_delete_target`][`State::get_column_delete_target`],
+    /// otherwise they go to the trash. Does nothing if it's the only
+    /// column, since the board needs at least one.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn delete_column(&mut self, move_tasks: bool) -> Result<(), Error> {
+        let Some(target_idx) = self.get_column_delete_target() else {
+            return Ok(());
+        };
+        let col_idx = self.selected_column_idx;
+        let column = self.columns.remove(col_idx);
+        // The target shifts one position to the left when it comes
+        // after the deleted column
+        let target_idx = if target_idx > col_idx { target_idx - 1 } else { target_idx };
+
+        if move_tasks {
+            let target = &mut self.columns[target_idx];
+            self.db_conn.delete_column(column.id, Some(target.id))?;
+            // Reload instead of appending so the moved tasks pick up
+            // the timestamps set by the DB
+            target.tasks = self.db_conn.get_tasks_by_column(target.id)?;
+        } else {
+            self.db_conn.delete_column(column.id, None)?;
+        }
+        // The recorded operations may point to the deleted column
+        self.history.clear();
+
+        self.selected_column_idx = target_idx;
+        self.db_conn
+            .set_selected_column(self.get_selected_board().id, self.selected_column_idx)
+    }
+
+    /// Returns how many tasks are done and how many there are to
+    /// complete in total, which leaves out the
+    /// [`ColumnRole::Parked`] columns.
+    #[must_use]
+    pub fn get_progress(&self) -> (usize, usize) {
+        self.columns
+            .iter()
+            .fold((0, 0), |(done, total), column| match column.role {
+                ColumnRole::Done => (done + column.tasks.len(), total + column.tasks.len()),
+                ColumnRole::Backlog | ColumnRole::Active => (done, total + column.tasks.len()),
+                ColumnRole::Parked => (done, total),
+            })
+    }
+
+    /// Computes the [`FlowMetrics`] of the board from the recorded
+    /// column transitions of every [`Task`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn get_flow_metrics(&self) -> Result<FlowMetrics, Error> {
+        let events = self
+            .db_conn
+            .get_board_task_events(self.get_selected_board().id)?;
+        let done_column_ids: Vec<i64> = self
+            .columns
+            .iter()
+            .filter(|c| c.role == ColumnRole::Done)
+            .map(|c| c.id)
+            .collect();
+        let now = chrono::Utc::now().timestamp();
+        Ok(FlowMetrics::from_events(
+            &events,
+            &self.columns,
+            &done_column_ids,
+            now,
+        ))
+    }
+
+    /// Opens the expanded stats view with freshly computed
+    /// [`FlowMetrics`], or closes it if it's already open.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn toggle_flow_metrics(&mut self) -> Result<(), Error> {
+        self.flow_metrics = match self.flow_metrics {
+            Some(_) => None,
+            None => Some(self.get_flow_metrics()?),
+        };
+        Ok(())
+    }
+
+    /// Where [`State::export_to_file`] writes the board: next to the
+    /// database with the extension of the [`ExportFormat`], or in the
+    /// current directory for an in-memory database.
+    #[must_use]
+    pub fn get_export_path(&self, format: ExportFormat) -> PathBuf {
+        let db_path = match self.db_conn.path() {
+            Some(path) if !path.is_empty() => Path::new(path),
+            _ => Path::new("kanban.db"),
+        };
+        db_path.with_extension(format.extension())
+    }
+
+    /// Exports the whole board to [`State::get_export_path`] and
+    /// reports the outcome in [`State::status_message`], so a failed
+    /// write doesn't take the app down.
+    pub fn export_to_file(&mut self, format: ExportFormat) {
+        let path = self.get_export_path(format);
+        let result = export_to_string(&self.db_conn, self.get_selected_board().id, format)
+            .and_then(|export| Ok(std::fs::write(&path, export)?));
+        self.status_message = Some(match result {
+            Ok(()) => format!("Exported the board to {}", path.display()),
+            Err(e) => format!("Export failed: {e}"),
+        });
+    }
+
+    /// Reverts the last change made to the tasks, reporting what was
+    /// undone in [`State::status_message`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn undo(&mut self) -> Result<(), Error> {
+        let Some(operation) = self.history.pop_undo() else {
+            self.status_message = Some(String::from("Nothing to undo"));
+            return Ok(());
+        };
+        let applied = self.apply_operation_atomically(&operation.inverse())?;
+        self.report_history_step("Undid", &operation, applied);
+        Ok(())
+    }
+
+    /// Applies again the last change reverted by
+    /// [`undo`][`State::undo`], reporting what was redone in
+    /// [`State::status_message`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn redo(&mut self) -> Result<(), Error> {
+        let Some(operation) = self.history.pop_redo() else {
+            self.status_message = Some(String::from("Nothing to redo"));
+            return Ok(());
+        };
+        let applied = self.apply_operation_atomically(&operation)?;
+        self.report_history_step("Redid", &operation, applied);
+        Ok(())
+    }
+
+    fn report_history_step(&mut self, verb: &str, operation: &Operation, applied: bool) {
+        self.status_message = Some(if applied {
+            format!("{verb} {}", operation.describe())
+        } else {
+            // The board was changed behind our back, e.g. from the CLI
+            self.history.clear();
+            format!(
+                "Couldn't {} anymore, the undo history was cleared",
+                operation.describe()
+            )
+        });
+    }
+
+    /// Returns the index of the [`Column`] and of the [`Task`] with
+    /// the given ID.
+    fn find_task(&self, task_id: i64) -> Option<(usize, usize)> {
+        self.columns.iter().enumerate().find_map(|(col_idx, c)| {
+            c.tasks
+                .iter()
+                .position(|t| t.id == task_id)
+                .map(|task_idx| (col_idx, task_idx))
+        })
+    }
+
+    fn find_column(&self, column_id: i64) -> Option<usize> {
+        self.columns.iter().position(|c| c.id == column_id)
+    }
+
+    /// Selects a [`Task`] by index, clamping it to the tasks of the
+    /// [`Column`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn select_task_at(&mut self, col_idx: usize, task_idx: usize) -> Result<(), Error> {
+        let column = &mut self.columns[col_idx];
+        column.selected_task_idx = min(task_idx, column.tasks.len().saturating_sub(1));
+        self.db_conn
+            .set_selected_task_for_column(column.selected_task_idx, column.id)?;
+        self.selected_column_idx = col_idx;
+        self.db_conn
+            .set_selected_column(self.get_selected_board().id, col_idx)
+    }
+
+    /// Persists the order of [`Column::tasks`] to the DB.
+    fn save_task_order(&mut self, col_idx: usize) -> Result<(), Error> {
+        let column = &self.columns[col_idx];
+        let ids: Vec<i64> = column.tasks.iter().map(|t| t.id).collect();
+        self.db_conn.set_task_order(column.id, &ids)?;
+        Ok(())
+    }
+
+    /// Runs [`apply_operation`][`State::apply_operation`] inside a
+    /// savepoint, so an [`Operation::Batch`] that stops half way
+    /// through is rolled back as a whole and the columns are reloaded
+    /// to match the DB again.
+    fn apply_operation_atomically(&mut self, operation: &Operation) -> Result<bool, Error> {
+        // A rusqlite transaction would borrow the connection for as
+        // long as the operation runs, so the savepoint is done by hand
+        self.db_conn.execute_batch("savepoint apply_operation")?;
+        let result = self.apply_operation(operation);
+        if matches!(result, Ok(true)) {
+            self.db_conn.execute_batch("release apply_operation")?;
+            return result;
+        }
+        self.db_conn
+            .execute_batch("rollback to apply_operation; release apply_operation")?;
+        let board = &self.boards[self.selected_board_idx];
+        self.columns = self.db_conn.get_all_columns(board.id)?;
+        self.selected_column_idx = min(
+            self.selected_column_idx,
+            self.columns.len().saturating_sub(1),
+        );
+        result
+    }
+
+    /// Applies an [`Operation`] to both [`State::columns`] and the DB
+    /// and selects the [`Task`] it affected. Returns `false` if the
+    /// board doesn't match the operation anymore, an
+    /// [`Operation::Batch`] keeping the steps applied before that
+    /// until [`apply_operation_atomically`][`State::apply_operation_atomically`]
+    /// rolls them back.
+    fn apply_operation(&mut self, operation: &Operation) -> Result<bool, Error> {
+        match operation {
+            Operation::AddTask {
+                column_id,
+                index,
+                task,
+            }
+            | Operation::UnarchiveTask {
+                column_id,
+                index,
+                task,
+            } => {
+                let Some(col_idx) = self.find_column(*column_id) else {
+                    return Ok(false);
+                };
+                if self.find_task(task.id).is_some() {
+                    return Ok(false);
+                }
+                let restored = if matches!(operation, Operation::AddTask { .. }) {
+                    self.db_conn.restore_task(task, *column_id)?;
+                    task.clone()
+                } else {
+                    self.db_conn.unarchive_task(task.id)?;
+                    self.db_conn.get_task(task.id)?
+                };
+                let column = &mut self.columns[col_idx];
+                let index = min(*index, column.tasks.len());
+                column.tasks.insert(index, restored);
+                self.save_task_order(col_idx)?;
+                self.select_task_at(col_idx, index)?;
+                self.resort_column(col_idx, task.id)?;
+            }
+            Operation::DeleteTask { task, .. } | Operation::ArchiveTask { task, .. } => {
+                let Some((col_idx, task_idx)) = self.find_task(task.id) else {
+                    return Ok(false);
+                };
+                self.columns[col_idx].tasks.remove(task_idx);
+                if matches!(operation, Operation::DeleteTask { .. }) {
+                    self.db_conn.delete_task(task.id)?;
+                } else {
+                    self.db_conn.archive_task(task.id)?;
+                }
+                self.select_task_at(col_idx, task_idx)?;
+            }
+            Operation::EditTask { after, .. } => {
+                let Some((col_idx, task_idx)) = self.find_task(after.id) else {
+                    return Ok(false);
+                };
+                self.apply_task_edit(col_idx, task_idx, after)?;
+            }
+            Operation::ReorderTask {
+                column_id,
+                from,
+                to,
+            } => {
+                let Some(col_idx) = self.find_column(*column_id) else {
+                    return Ok(false);
+                };
+                let tasks = &mut self.columns[col_idx].tasks;
+                let (Some(moved), Some(other)) = (tasks.get(*from), tasks.get(*to)) else {
+                    return Ok(false);
+                };
+                let (moved_id, other_id) = (moved.id, other.id);
+                tasks.swap(*from, *to);
+                self.db_conn.swap_task_order(moved_id, other_id)?;
+                self.select_task_at(col_idx, *to)?;
+            }
+            Operation::MoveTask {
+                task_id,
+                to_column_id,
+                to_index,
+                ..
+            } => {
+                let (Some((col_idx, task_idx)), Some(target_idx)) =
+                    (self.find_task(*task_id), self.find_column(*to_column_id))
+                else {
+                    return Ok(false);
+                };
+                let task = self.columns[col_idx].tasks.remove(task_idx);
+                self.select_task_at(col_idx, self.columns[col_idx].selected_task_idx)?;
+
+                self.db_conn
+                    .move_task_to_column(&task, &self.columns[target_idx])?;
+                let target = &mut self.columns[target_idx];
+                let index = min(*to_index, target.tasks.len());
+                target.tasks.insert(index, self.db_conn.get_task(task.id)?);
+                self.save_task_order(target_idx)?;
+                self.select_task_at(target_idx, index)?;
+                self.resort_column(target_idx, task.id)?;
+            }
+            Operation::Batch(operations) => {
+                for operation in operations {
+                    if !self.apply_operation(operation)? {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+        // Links can change on both ends, e.g. restoring a task that
+        // was blocking others
+        self.reload_task_links()?;
+        Ok(true)
+    }
+
+    /// Overwrites the editable fields of the [`Task`] at the given
+    /// position with the ones of `after`.
+    fn apply_task_edit(
+        &mut self,
+        col_idx: usize,
+        task_idx: usize,
+        after: &Task,
+    ) -> Result<(), Error> {
+        let task = &mut self.columns[col_idx].tasks[task_idx];
+        // Undoing isn't an edit of its own, so it stays out of the
+        // history of the task
+        if task.title != after.title || task.description != after.description {
+            self.db_conn.set_task_text(after)?;
+        }
+        self.db_conn.set_task_labels(task.id, &after.labels)?;
+        self.db_conn.set_task_priority(task.id, after.priority)?;
+        self.db_conn.set_task_due_date(task.id, after.due_date)?;
+        self.db_conn.set_checklist(task.id, &after.checklist)?;
+        self.db_conn.set_task_blockers(task.id, &after.blocked_by)?;
+        *task = self.db_conn.get_task(after.id)?;
+        self.labels = self.db_conn.get_all_labels()?;
+        self.select_task_at(col_idx, task_idx)?;
+        self.resort_column(col_idx, after.id)?;
+        Ok(())
+    }
+
+    /// Reloads the tasks of a [`Column`] that sorts by [`Priority`],
+    /// since a change can move a task within it, and selects the
+    /// [`Task`] with the given ID again. Does nothing for the other
+    /// columns.
+    fn resort_column(&mut self, col_idx: usize, task_id: i64) -> Result<(), Error> {
+        let column = &mut self.columns[col_idx];
+        if !column.sort_by_priority {
+            return Ok(());
+        }
+        column.tasks = self.db_conn.get_tasks_by_column(column.id)?;
+        if let Some(task_idx) = column.tasks.iter().position(|t| t.id == task_id) {
+            column.selected_task_idx = task_idx;
+            self.db_conn
+                .set_selected_task_for_column(task_idx, column.id)?;
+        }
+        Ok(())
+    }
+
+    /// Opens the search input, keeping the current query if a search
+    /// is already active.
+    pub fn start_search(&mut self) {
+        self.search
+            .get_or_insert_with(SearchState::default)
+            .is_typing = true;
+    }
+
+    /// Closes the search and shows every [`Task`] again.
+    pub fn clear_search(&mut self) {
+        self.search = None;
+    }
+
+    /// Returns `true` if the search or the label filter hide some
+    /// tasks.
+    #[must_use]
+    pub fn is_filtering(&self) -> bool {
+        self.search.as_ref().is_some_and(|s| s.matches.is_some()) || !self.label_filter.is_empty()
+    }
+
+    /// Returns `false` if the [`Task`] is hidden by the search or by
+    /// the label filter.
+    #[must_use]
+    pub fn is_task_visible(&self, task: &Task) -> bool {
+        let matches_search = match self.search.as_ref().and_then(|s| s.matches.as_ref()) {
+            Some(matches) => matches.contains(&task.id),
+            None => true,
+        };
+        let matches_labels = self.label_filter.is_empty()
+            || task.labels.iter().any(|l| self.label_filter.contains(l));
+        matches_search && matches_labels
+    }
+
+    /// Runs the search query against the DB again, which is needed
+    /// after the tasks change since the matches are only IDs.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn refresh_search_matches(&mut self) -> Result<(), Error> {
+        if let Some(search) = &mut self.search {
+            let query = search.query.lines().join(" ");
+            search.matches = if query.trim().is_empty() {
+                None
+            } else {
+                Some(self.db_conn.search_tasks(&query)?)
+            };
+        }
+        Ok(())
+    }
+
+    /// Updates the matches after the query changed and moves the
+    /// selection to the next match if the selected [`Task`] got
+    /// hidden.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn update_search(&mut self) -> Result<(), Error> {
+        self.refresh_search_matches()?;
+        self.select_visible_task()
+    }
+
+    /// Moves the selection to the next visible [`Task`] if the
+    /// selected one is hidden.
+    fn select_visible_task(&mut self) -> Result<(), Error> {
+        if self
+            .get_selected_task()
+            .map_or(true, |t| !self.is_task_visible(t))
+        {
+            self.select_match(true)?;
+        }
+        Ok(())
+    }
+
+    /// Selects the next or previous [`Task`] matching the search,
+    /// going through the columns in order and wrapping around at the
+    /// ends of the board. Reports in [`State::status_message`] when
+    /// nothing matches.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn select_next_match(&mut self, forward: bool) -> Result<(), Error> {
+        if !self.select_match(forward)? {
+            self.status_message = Some(String::from("No matches"));
+        }
+        Ok(())
+    }
+
+    fn select_match(&mut self, forward: bool) -> Result<bool, Error> {
+        let matches: Vec<(usize, usize)> = self
+            .columns
+            .iter()
+            .enumerate()
+            .flat_map(|(col_idx, column)| {
+                column
+                    .tasks
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, task)| self.is_task_visible(task))
+                    .map(move |(task_idx, _)| (col_idx, task_idx))
+            })
+            .collect();
+        let current = (
+            self.selected_column_idx,
+            self.get_selected_column().selected_task_idx,
+        );
+        let next = if forward {
+            matches.iter().find(|&&m| m > current).or(matches.first())
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|&&m| m < current)
+                .or(matches.last())
+        };
+        match next {
+            Some(&(col_idx, task_idx)) => {
+                self.select_task_at(col_idx, task_idx)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Selects the first visible [`Task`] of the selected [`Column`]
+    /// if the search hides the selected one.
+    fn reveal_selected_task(&mut self) -> Result<(), Error> {
+        let column = self.get_selected_column();
+        if column
+            .tasks
+            .get(column.selected_task_idx)
+            .map_or(true, |t| self.is_task_visible(t))
+        {
+            return Ok(());
+        }
+        if let Some(task_idx) = column.tasks.iter().position(|t| self.is_task_visible(t)) {
+            self.select_task_at(self.selected_column_idx, task_idx)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the [`Label`]s of the selected [`Task`], creating the
+    /// ones that don't exist yet. Does nothing if the [`Column`] is
+    /// empty or if the labels didn't change.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn set_selected_task_labels(&mut self, labels: &[String]) -> Result<(), Error> {
+        let Some(before) = self.get_selected_task().cloned() else {
+            return Ok(());
+        };
+        let mut sorted = labels.to_vec();
+        sorted.sort_by_key(|l| l.to_lowercase());
+        if sorted == before.labels {
+            return Ok(());
+        }
+        self.db_conn.set_task_labels(before.id, labels)?;
+        self.refresh_selected_task()?;
+        self.labels = self.db_conn.get_all_labels()?;
+        if let Some(after) = self.get_selected_task().cloned() {
+            self.history.record(Operation::EditTask { before, after });
+        }
+        Ok(())
+    }
+
+    /// Shows or hides the [`Task`]s with the [`Label`] at `label_idx`
+    /// in [`State::labels`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn toggle_label_filter(&mut self, label_idx: usize) -> Result<(), Error> {
+        let Some(label) = self.labels.get(label_idx) else {
+            return Ok(());
+        };
+        if !self.label_filter.remove(&label.name) {
+            self.label_filter.insert(label.name.clone());
+        }
+        self.select_visible_task()
+    }
+
+    /// Shows every [`Task`] again.
+    pub fn clear_label_filter(&mut self) {
+        self.label_filter.clear();
+    }
+
+    /// Changes the [`LabelColor`] of the [`Label`] at `label_idx` to
+    /// the next one.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn cycle_label_color(&mut self, label_idx: usize) -> Result<(), Error> {
+        if let Some(label) = self.labels.get_mut(label_idx) {
+            label.color = label.color.next();
+            self.db_conn.set_label_color(label.id, label.color)?;
+        }
+        Ok(())
+    }
+
+    /// Asks the user to confirm deleting the [`Label`] at `label_idx`.
+    pub fn ask_delete_label(&mut self, label_idx: usize) {
+        if let Some(label) = self.labels.get(label_idx) {
+            self.confirm = Some(ConfirmState {
+                message: format!("Delete the label \"{}\" from every task?", label.name),
+                action: ConfirmAction::DeleteLabel { label_id: label.id },
+            });
+        }
+    }
+
+    /// Deletes the [`Label`] at `label_idx`, taking it off every
+    /// [`Task`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn delete_label(&mut self, label_idx: usize) -> Result<(), Error> {
+        if label_idx >= self.labels.len() {
+            return Ok(());
+        }
+        let label = self.labels.remove(label_idx);
+        self.db_conn.delete_label(label.id)?;
+        self.label_filter.remove(&label.name);
+        for task in self.columns.iter_mut().flat_map(|c| c.tasks.iter_mut()) {
+            task.labels.retain(|l| *l != label.name);
+        }
+        if let Some(filter) = &mut self.label_filter_state {
+            filter.selected_idx = min(filter.selected_idx, self.labels.len().saturating_sub(1));
+        }
+        Ok(())
+    }
+
+    /// Changes the [`Priority`] of the selected [`Task`], `None`
+    /// removing it. Does nothing if the [`Column`] is empty or if the
+    /// priority didn't change.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn set_selected_task_priority(&mut self, priority: Option<Priority>) -> Result<(), Error> {
+        let Some(before) = self.get_selected_task().cloned() else {
+            return Ok(());
+        };
+        if priority == before.priority {
+            return Ok(());
+        }
+        let task_id = before.id;
+        self.db_conn.set_task_priority(task_id, priority)?;
+        self.refresh_selected_task()?;
+        if let Some(after) = self.get_selected_task().cloned() {
+            self.history.record(Operation::EditTask { before, after });
+        }
+        self.resort_column(self.selected_column_idx, task_id)
+    }
+
+    /// Quick way to set the [`Priority`] of the selected [`Task`],
+    /// giving it the same one again removes it.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn toggle_selected_task_priority(&mut self, priority: Priority) -> Result<(), Error> {
+        let current = self.get_selected_task().and_then(|t| t.priority);
+        if current == Some(priority) {
+            self.set_selected_task_priority(None)
+        } else {
+            self.set_selected_task_priority(Some(priority))
+        }
+    }
+
+    /// Switches the selected [`Column`] between sorting its tasks by
+    /// [`Priority`] and only by their manual order, keeping the same
+    /// [`Task`] selected. The undo history refers to tasks by their
+    /// position so it gets cleared.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn toggle_priority_sort(&mut self) -> Result<(), Error> {
+        let task_id = self.get_selected_task().map(|t| t.id);
+        let column = self.get_selected_column_mut();
+        column.sort_by_priority = !column.sort_by_priority;
+        let (column_id, enabled) = (column.id, column.sort_by_priority);
+        self.db_conn
+            .set_column_sort_by_priority(column_id, enabled)?;
+        self.history.clear();
+
+        let column = &mut self.columns[self.selected_column_idx];
+        column.tasks = self.db_conn.get_tasks_by_column(column_id)?;
+        if let Some(task_idx) = task_id.and_then(|id| column.tasks.iter().position(|t| t.id == id))
+        {
+            column.selected_task_idx = task_idx;
+            self.db_conn
+                .set_selected_task_for_column(task_idx, column_id)?;
+        }
+        self.status_message = Some(String::from(if enabled {
+            "Sorting the column by priority"
+        } else {
+            "Sorting the column manually"
+        }));
+        Ok(())
+    }
+
+    /// Changes the due date of the selected [`Task`], `None` removing
+    /// it. Does nothing if the [`Column`] is empty or if the date
+    /// didn't change.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn set_selected_task_due_date(&mut self, due_date: Option<NaiveDate>) -> Result<(), Error> {
+        let Some(before) = self.get_selected_task().cloned() else {
+            return Ok(());
+        };
+        if due_date == before.due_date {
+            return Ok(());
+        }
+        self.db_conn.set_task_due_date(before.id, due_date)?;
+        self.refresh_selected_task()?;
+        if let Some(after) = self.get_selected_task().cloned() {
+            self.history.record(Operation::EditTask { before, after });
+        }
+        Ok(())
+    }
+
+    /// Every [`Task`] of the board as the index of its [`Column`] and
+    /// its index in it, sorted by due date with the tasks without one
+    /// last. Tasks due the same day are sorted by [`Priority`], then
+    /// by their place on the board.
+    #[must_use]
+    pub fn get_agenda(&self) -> Vec<(usize, usize)> {
+        let mut agenda: Vec<(usize, usize)> = self
+            .columns
+            .iter()
+            .enumerate()
+            .flat_map(|(col_idx, c)| (0..c.tasks.len()).map(move |task_idx| (col_idx, task_idx)))
+            .collect();
+        // Stable, so the board order is kept for the rest
+        agenda.sort_by_key(|&(col_idx, task_idx)| {
+            let task = &self.columns[col_idx].tasks[task_idx];
+            (
+                task.due_date.is_none(),
+                task.due_date,
+                task.priority.is_none(),
+                task.priority,
+            )
+        });
+        agenda
+    }
+
+    /// Closes the agenda and selects the [`Task`] at `agenda_idx` in
+    /// [`State::get_agenda`] on the board, clearing the search and the
+    /// label filter if they hide it.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn open_agenda_entry(&mut self, agenda_idx: usize) -> Result<(), Error> {
+        self.agenda = None;
+        let Some(&(col_idx, task_idx)) = self.get_agenda().get(agenda_idx) else {
+            return Ok(());
+        };
+        if !self.is_task_visible(&self.columns[col_idx].tasks[task_idx]) {
+            self.clear_search();
+            self.clear_label_filter();
+        }
+        self.select_task_at(col_idx, task_idx)
+    }
+
+    /// Replaces the [`ChecklistItem`]s of the selected [`Task`]. Does
+    /// nothing if the [`Column`] is empty or if the checklist didn't
+    /// change.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn set_selected_task_checklist(
+        &mut self,
+        checklist: &[ChecklistItem],
+    ) -> Result<(), Error> {
+        let Some(before) = self.get_selected_task().cloned() else {
+            return Ok(());
+        };
+        if checklist == before.checklist {
+            return Ok(());
+        }
+        let tx = self.db_conn.unchecked_transaction()?;
+        self.db_conn.set_checklist(before.id, checklist)?;
+        tx.commit()?;
+        self.refresh_selected_task()?;
+        if let Some(after) = self.get_selected_task().cloned() {
+            self.history.record(Operation::EditTask { before, after });
+        }
+        Ok(())
+    }
+
+    /// Returns the [`Task`] with the given ID and the [`Column`] it's
+    /// in.
+    #[must_use]
+    pub fn get_task_by_id(&self, task_id: i64) -> Option<(&Column, &Task)> {
+        self.find_task(task_id).map(|(col_idx, task_idx)| {
+            (
+                &self.columns[col_idx],
+                &self.columns[col_idx].tasks[task_idx],
+            )
+        })
+    }
+
+    /// Returns `true` while any of the tasks blocking `task` isn't in
+    /// a [`ColumnRole::Done`] column, looking on every board. A
+    /// failing lookup counts as not blocked.
+    #[must_use]
+    pub fn is_task_blocked(&self, task: &Task) -> bool {
+        !task.blocked_by.is_empty()
+            && self
+                .db_conn
+                .get_unfinished_blocker(task.id)
+                .is_ok_and(|blocker| blocker.is_some())
+    }
+
+    /// Reloads [`Task::blocked_by`] and [`Task::blocks`] of every
+    /// task, since a change to a link or a task affects both ends.
+    fn reload_task_links(&mut self) -> Result<(), Error> {
+        let links = self.db_conn.get_task_links()?;
+        for task in self.columns.iter_mut().flat_map(|c| c.tasks.iter_mut()) {
+            task.blocked_by = links
+                .iter()
+                .filter(|l| l.1 == task.id)
+                .map(|l| l.0)
+                .collect();
+            task.blocked_by.sort_unstable();
+            task.blocks = links
+                .iter()
+                .filter(|l| l.0 == task.id)
+                .map(|l| l.1)
+                .collect();
+            task.blocks.sort_unstable();
+        }
+        Ok(())
+    }
+
+    /// Every [`Task`] that could block the selected one, as the index
+    /// of its [`Column`] and its index in it, in board order.
+    #[must_use]
+    pub fn get_link_candidates(&self) -> Vec<(usize, usize)> {
+        let selected_id = self.get_selected_task().map(|t| t.id);
+        self.columns
+            .iter()
+            .enumerate()
+            .flat_map(|(col_idx, c)| {
+                c.tasks
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, t)| Some(t.id) != selected_id)
+                    .map(move |(task_idx, _)| (col_idx, task_idx))
+            })
+            .collect()
+    }
+
+    /// Makes the [`Task`] with the given ID block the selected one, or
+    /// stop blocking it if it already does. Links that would make a
+    /// task wait on itself are refused with a
+    /// [`State::status_message`].
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn toggle_selected_task_blocker(&mut self, blocker_id: i64) -> Result<(), Error> {
+        let Some(before) = self.get_selected_task().cloned() else {
+            return Ok(());
+        };
+        if before.blocked_by.contains(&blocker_id) {
+            self.db_conn.remove_task_link(blocker_id, before.id)?;
+        } else if self.db_conn.would_create_cycle(blocker_id, before.id)? {
+            self.status_message = Some(String::from(
+                "Can't link them, the task would end up waiting on itself",
+            ));
+            return Ok(());
+        } else {
+            self.db_conn.add_task_link(blocker_id, before.id)?;
+        }
+        self.reload_task_links()?;
+        if let Some(after) = self.get_selected_task().cloned() {
+            self.history.record(Operation::EditTask { before, after });
+        }
+        Ok(())
+    }
+
+    /// Runs the action of [`State::confirm`] and closes it.
+    ///
+    /// # Errors
+    ///
+    /// SQL related errors get bubbled up to here.
+    pub fn confirm_action(&mut self) -> Result<(), Error> {
+        match self.confirm.take().map(|c| c.action) {
+            Some(ConfirmAction::MoveBlockedTask {
+                column_id,
+                task_idx,
+            }) => match self.find_column(column_id) {
+                Some(col_idx) => self.move_task_to_column(col_idx, task_idx, true),
+                None => Ok(()),
+            },
+            Some(ConfirmAction::DeleteBoard { board_id }) => self.delete_board(board_id),
+            Some(ConfirmAction::DeleteTask) => self.delete_task(),
+            Some(ConfirmAction::DeleteLabel { label_id }) => {
+                match self.labels.iter().position(|l| l.id == label_id) {
+                    Some(label_idx) => self.delete_label(label_idx),
+                    None => Ok(()),
+                }
+            }
+            None => Ok(()),
+        }
+    }
 }
@@ -0,0 +1,77 @@
+//! Fitting the text of a task into a card of the card view: the title
+//! wrapped over several lines and a one line preview of the
+//! description.
+
+use std::mem::take;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Splits `text` into lines no wider than `width`, breaking between
+/// words when possible. The first line is only `first_width` wide to
+/// leave room for what goes before it, like the priority. Always
+/// returns at least one line.
+#[must_use]
+pub fn wrap_text(text: &str, first_width: usize, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+    let mut max_width = first_width.max(1);
+    for word in text.split_whitespace() {
+        if line_width > 0 {
+            if line_width + 1 + word.width() <= max_width {
+                line.push(' ');
+                line_width += 1;
+            } else {
+                lines.push(take(&mut line));
+                line_width = 0;
+                max_width = width;
+            }
+        }
+        // Words wider than a whole line are cut wherever they reach
+        // the edge
+        for c in word.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if line_width + char_width > max_width && line_width > 0 {
+                lines.push(take(&mut line));
+                line_width = 0;
+                max_width = width;
+            }
+            line.push(c);
+            line_width += char_width;
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// Cuts `text` to `width`, ending it with `…` if anything was left
+/// out.
+#[must_use]
+pub fn truncate_text(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let mut truncated_width = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if truncated_width + char_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        truncated_width += char_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// The first line of a description that isn't blank, cut to `width`.
+/// `None` if the description is empty.
+#[must_use]
+pub fn description_preview(description: &str, width: usize) -> Option<String> {
+    description
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| truncate_text(line, width))
+}
@@ -0,0 +1,715 @@
+use anyhow::{anyhow, bail, Error};
+use chrono::Utc;
+use clap::Subcommand;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::app::{
+    Board, Column, ColumnRole, OffBoardKind, OffBoardTask, Priority, Task, TaskEvent, WipLimitMode,
+};
+use crate::db::DBConn;
+use crate::due::{parse_due_date, today, DUE_DATE_FORMAT};
+use crate::export::{export_to_string, import_board, BoardExport, ExportFormat, ImportMode};
+use crate::ui::format_timestamp;
+
+/// Non-interactive subcommands to script the board without the TUI.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Add a new task, printing its ID
+    Add {
+        /// Title of the task
+        title: String,
+        /// Description of the task
+        #[arg(short, long, default_value = "")]
+        description: String,
+        /// Name of the column to add it to, defaults to the first one
+        #[arg(short, long)]
+        column: Option<String>,
+        /// Label to put on the task, can be repeated
+        #[arg(short, long = "label")]
+        labels: Vec<String>,
+        /// Priority of the task
+        #[arg(short, long, value_enum)]
+        priority: Option<Priority>,
+        /// Due date, like 2024-05-31, tomorrow, +3d or fri
+        #[arg(long)]
+        due: Option<String>,
+        /// Add it even if the column is at its WIP limit
+        #[arg(long)]
+        force: bool,
+    },
+    /// List the tasks of every column
+    List {
+        /// Only list the tasks of this column
+        #[arg(short, long)]
+        column: Option<String>,
+    },
+    /// Move a task to the end of another column
+    Move {
+        /// ID of the task
+        id: i64,
+        /// Name of the target column
+        column: String,
+        /// Move it even if the column is at its WIP limit or the task
+        /// is blocked
+        #[arg(long)]
+        force: bool,
+    },
+    /// Change the title, description, priority and/or due date of a task
+    Edit {
+        /// ID of the task
+        id: i64,
+        /// New title
+        #[arg(short, long)]
+        title: Option<String>,
+        /// New description
+        #[arg(short, long)]
+        description: Option<String>,
+        /// New priority
+        #[arg(short, long, value_enum, conflicts_with = "no_priority")]
+        priority: Option<Priority>,
+        /// Remove the priority
+        #[arg(long)]
+        no_priority: bool,
+        /// New due date, like 2024-05-31, tomorrow, +3d or fri. An
+        /// empty one removes it
+        #[arg(long)]
+        due: Option<String>,
+    },
+    /// Mark a task as blocked by another one until that one is done
+    Link {
+        /// ID of the blocking task
+        blocker: i64,
+        /// ID of the blocked task
+        blocked: i64,
+    },
+    /// Remove a link added with `link`
+    Unlink {
+        /// ID of the blocking task
+        blocker: i64,
+        /// ID of the blocked task
+        blocked: i64,
+    },
+    /// Move a task to the trash
+    Rm {
+        /// ID of the task
+        id: i64,
+    },
+    /// Archive a task, or every done task completed a while ago
+    Archive {
+        /// ID of the task
+        #[arg(
+            required_unless_present = "done_older_than",
+            conflicts_with = "done_older_than"
+        )]
+        id: Option<i64>,
+        /// Archive the tasks of the done columns completed more than
+        /// this many days ago
+        #[arg(long, value_name = "DAYS")]
+        done_older_than: Option<u32>,
+    },
+    /// List the archived tasks, the most recently archived first
+    Archived,
+    /// Put an archived task back at the end of its column
+    Unarchive {
+        /// ID of the task
+        id: i64,
+    },
+    /// List the deleted tasks in the trash, the most recently deleted
+    /// first
+    Trash,
+    /// Put a deleted task back at the end of its column
+    Restore {
+        /// ID of the task
+        id: i64,
+    },
+    /// Show a task along with its history
+    Show {
+        /// ID of the task
+        id: i64,
+    },
+    /// Export the whole board
+    Export {
+        /// Format of the export
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// File to write the export to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// List the boards, marking the one the app opens with `*`
+    Boards,
+    /// Add a new board with the default columns
+    AddBoard {
+        /// Name of the board
+        name: String,
+    },
+    /// Import a board from a JSON export
+    Import {
+        /// The JSON export to read
+        file: PathBuf,
+        /// Delete the current board instead of merging into it
+        #[arg(long)]
+        replace: bool,
+    },
+}
+
+/// A [`Task`] along with the name of the [`Column`] it's in, which is
+/// how tasks are printed as JSON.
+#[derive(Serialize)]
+struct TaskOutput<'a> {
+    #[serde(flatten)]
+    task: &'a Task,
+    column: &'a str,
+}
+
+/// Same as [`TaskOutput`] with the history of the [`Task`].
+#[derive(Serialize)]
+struct TaskDetailsOutput<'a> {
+    #[serde(flatten)]
+    task: TaskOutput<'a>,
+    history: &'a [TaskEvent],
+}
+
+/// Finds a [`Board`] by name, ignoring case, or the one the app opens
+/// when there's no name.
+fn find_board(db_conn: &DBConn, name: Option<&str>) -> Result<Board, Error> {
+    let boards = db_conn.get_all_boards()?;
+    if let Some(name) = name {
+        return boards
+            .into_iter()
+            .find(|b| b.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow!("No board named \"{name}\""));
+    }
+    let selected_id = db_conn.get_selected_board()?;
+    let idx = boards
+        .iter()
+        .position(|b| Some(b.id) == selected_id)
+        .unwrap_or(0);
+    boards
+        .into_iter()
+        .nth(idx)
+        .ok_or_else(|| anyhow!("The database has no boards"))
+}
+
+/// Finds a [`Column`] by name, ignoring case.
+fn find_column<'a>(columns: &'a [Column], name: &str) -> Result<&'a Column, Error> {
+    columns
+        .iter()
+        .find(|c| c.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| anyhow!("No column named \"{name}\""))
+}
+
+/// Finds a [`Task`] by ID along with the [`Column`] it's in.
+fn find_task(columns: &[Column], id: i64) -> Result<(&Column, &Task), Error> {
+    columns
+        .iter()
+        .find_map(|c| c.tasks.iter().find(|t| t.id == id).map(|t| (c, t)))
+        .ok_or_else(|| anyhow!("No task with ID {id}"))
+}
+
+/// Writes `task` as a [`TaskOutput`].
+fn write_task_json(out: &mut impl Write, task: &Task, column: &str) -> Result<(), Error> {
+    let output = TaskOutput { task, column };
+    writeln!(out, "{}", serde_json::to_string_pretty(&output)?)?;
+    Ok(())
+}
+
+fn write_task_line(out: &mut impl Write, task: &Task) -> Result<(), Error> {
+    match task.priority {
+        Some(priority) => writeln!(out, "#{} [{}] {}", task.id, priority.as_str(), task.title)?,
+        None => writeln!(out, "#{} {}", task.id, task.title)?,
+    }
+    Ok(())
+}
+
+/// Runs a [`Command`] against the [`Board`] with the given name, or
+/// the one the app opens, writing the result to `out` either as plain
+/// text or as JSON. Going over a WIP limit is handled according to
+/// `wip_limit_mode`, like on the board.
+///
+/// # Errors
+///
+/// Returns an error if a board, task or column can't be found, if
+/// reading or writing fails, or if something is wrong with the SQL.
+pub fn run_command(
+    db_conn: &mut DBConn,
+    board: Option<&str>,
+    command: Command,
+    json: bool,
+    wip_limit_mode: WipLimitMode,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let board = find_board(db_conn, board)?;
+    let columns = db_conn.get_all_columns(board.id)?;
+    match command {
+        Command::Add {
+            title,
+            description,
+            column,
+            labels,
+            priority,
+            due,
+            force,
+        } => {
+            let due_date = parse_due_date(due.as_deref().unwrap_or_default(), today())?;
+            let column = match column {
+                Some(name) => find_column(&columns, &name)?,
+                None => columns.first().ok_or_else(|| anyhow!("The board has no columns"))?,
+            };
+            if !force {
+                check_wip_limit(column, wip_limit_mode)?;
+            }
+            let task = db_conn.create_new_task(title, description, column.id)?;
+            db_conn.set_task_labels(task.id, &labels)?;
+            db_conn.set_task_priority(task.id, priority)?;
+            db_conn.set_task_due_date(task.id, due_date)?;
+            let task = db_conn.get_task(task.id)?;
+            if json {
+                write_task_json(out, &task, &column.name)?;
+            } else {
+                writeln!(out, "{}", task.id)?;
+            }
+        }
+        Command::List { column } => list_tasks(&columns, column.as_deref(), json, out)?,
+        Command::Move { id, column, force } => {
+            move_task(db_conn, &columns, id, &column, force, wip_limit_mode)?;
+            write_changed_task(db_conn, &board, id, json, out)?;
+        }
+        Command::Edit {
+            id,
+            title,
+            description,
+            priority,
+            no_priority,
+            due,
+        } => {
+            let (_, task) = find_task(&columns, id)?;
+            edit_task(
+                db_conn,
+                task,
+                title,
+                description,
+                priority,
+                no_priority,
+                due,
+            )?;
+            write_changed_task(db_conn, &board, id, json, out)?;
+        }
+        Command::Link { blocker, blocked } => {
+            link_tasks(db_conn, &columns, blocker, blocked, true)?;
+            write_changed_task(db_conn, &board, blocked, json, out)?;
+        }
+        Command::Unlink { blocker, blocked } => {
+            link_tasks(db_conn, &columns, blocker, blocked, false)?;
+            write_changed_task(db_conn, &board, blocked, json, out)?;
+        }
+        Command::Rm { id } => {
+            find_task(&columns, id)?;
+            db_conn.delete_task(id)?;
+            write_changed_task(db_conn, &board, id, json, out)?;
+        }
+        Command::Archive {
+            id,
+            done_older_than,
+        } => archive_tasks(db_conn, &board, &columns, id, done_older_than, json, out)?,
+        Command::Archived => {
+            list_off_board_tasks(&db_conn.get_archived_tasks(board.id)?, json, out)?;
+        }
+        Command::Unarchive { id } => {
+            restore_task(db_conn, &board, id, OffBoardKind::Archived)?;
+            write_changed_task(db_conn, &board, id, json, out)?;
+        }
+        Command::Trash => list_off_board_tasks(&db_conn.get_trashed_tasks(board.id)?, json, out)?,
+        Command::Restore { id } => {
+            restore_task(db_conn, &board, id, OffBoardKind::Deleted)?;
+            write_changed_task(db_conn, &board, id, json, out)?;
+        }
+        Command::Show { id } => show_task(db_conn, &columns, id, json, out)?,
+        Command::Export { format, output } => {
+            let export = export_to_string(db_conn, board.id, format)?;
+            match output {
+                Some(path) => std::fs::write(path, export)?,
+                None => writeln!(out, "{export}")?,
+            }
+        }
+        Command::Boards => list_boards(db_conn, &board, json, out)?,
+        Command::AddBoard { name } => add_board(db_conn, &name, json, out)?,
+        Command::Import { file, replace } => {
+            import_file(db_conn, &board, &file, replace, json, out)?;
+        }
+    }
+    Ok(())
+}
+
+fn import_file(
+    db_conn: &DBConn,
+    board: &Board,
+    file: &Path,
+    replace: bool,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let export: BoardExport = serde_json::from_str(&std::fs::read_to_string(file)?)?;
+    let mode = if replace {
+        ImportMode::Replace
+    } else {
+        ImportMode::Merge
+    };
+    let (columns, tasks) = import_board(db_conn, board.id, &export, mode)?;
+    if json {
+        let output = serde_json::json!({ "columns": columns, "tasks": tasks });
+        writeln!(out, "{output}")?;
+    } else {
+        writeln!(out, "Imported {tasks} task(s), created {columns} column(s)")?;
+    }
+    Ok(())
+}
+
+/// Writes the [`Task`] a command just changed, as a single line or as
+/// JSON. Tasks that left the board are looked up in the trash and the
+/// archive.
+fn write_changed_task(
+    db_conn: &DBConn,
+    board: &Board,
+    id: i64,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let columns = db_conn.get_all_columns(board.id)?;
+    let (task, column) = if let Ok((column, task)) = find_task(&columns, id) {
+        (task.clone(), column.name.clone())
+    } else {
+        let mut tasks = db_conn.get_trashed_tasks(board.id)?;
+        tasks.extend(db_conn.get_archived_tasks(board.id)?);
+        let entry = tasks
+            .into_iter()
+            .find(|t| t.task.id == id)
+            .ok_or_else(|| anyhow!("No task with ID {id}"))?;
+        (entry.task, entry.column_name)
+    };
+    if json {
+        write_task_json(out, &task, &column)?;
+    } else {
+        write_task_line(out, &task)?;
+    }
+    Ok(())
+}
+
+/// Changes the fields of `task` given to [`Command::Edit`].
+fn edit_task(
+    db_conn: &DBConn,
+    task: &Task,
+    title: Option<String>,
+    description: Option<String>,
+    priority: Option<Priority>,
+    no_priority: bool,
+    due: Option<String>,
+) -> Result<(), Error> {
+    let due_date = due.map(|due| parse_due_date(&due, today())).transpose()?;
+    // Only a new title or description is an edit of the text, which
+    // bumps the update time and the history
+    if title.is_some() || description.is_some() {
+        let mut task = task.clone();
+        if let Some(title) = title {
+            task.title = title;
+        }
+        if let Some(description) = description {
+            task.description = description;
+        }
+        db_conn.update_task_text(&task)?;
+    }
+    if priority.is_some() || no_priority {
+        db_conn.set_task_priority(task.id, priority)?;
+    }
+    if let Some(due_date) = due_date {
+        db_conn.set_task_due_date(task.id, due_date)?;
+    }
+    Ok(())
+}
+
+/// Moves the task `id` to the end of the column named `column`.
+fn move_task(
+    db_conn: &DBConn,
+    columns: &[Column],
+    id: i64,
+    column: &str,
+    force: bool,
+    wip_limit_mode: WipLimitMode,
+) -> Result<(), Error> {
+    let (from, task) = find_task(columns, id)?;
+    let target = find_column(columns, column)?;
+    if !force && from.id != target.id {
+        check_wip_limit(target, wip_limit_mode)?;
+        check_move(db_conn, task, target)?;
+    }
+    db_conn.move_task_to_column(task, target)?;
+    Ok(())
+}
+
+/// Checks whether one more [`Task`] fits in `column` under its WIP
+/// limit. Going over it is refused with [`WipLimitMode::Refuse`] and
+/// only warned about on stderr with [`WipLimitMode::Warn`].
+fn check_wip_limit(column: &Column, mode: WipLimitMode) -> Result<(), Error> {
+    let Some(limit) = column.wip_limit else {
+        return Ok(());
+    };
+    let count = column.tasks.len() + 1;
+    if count <= limit {
+        return Ok(());
+    }
+    match mode {
+        WipLimitMode::Warn => {
+            eprintln!("{} is over its WIP limit ({count}/{limit})", column.name);
+        }
+        WipLimitMode::Refuse => bail!(
+            "{} is at its WIP limit of {limit}, use --force to go over it",
+            column.name
+        ),
+    }
+    Ok(())
+}
+
+/// Refuses to move `task` into `target` when that starts the task
+/// while something blocks it, the same rule the board follows.
+fn check_move(db_conn: &DBConn, task: &Task, target: &Column) -> Result<(), Error> {
+    if target.role == ColumnRole::Active {
+        if let Some((blocker_id, title)) = db_conn.get_unfinished_blocker(task.id)? {
+            bail!(
+                "#{} is blocked by #{blocker_id} {title}, use --force to move it anyway",
+                task.id
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Adds or removes the link making `blocker_id` block the task `task_id`.
+fn link_tasks(
+    db_conn: &DBConn,
+    columns: &[Column],
+    blocker_id: i64,
+    task_id: i64,
+    link: bool,
+) -> Result<(), Error> {
+    find_task(columns, blocker_id)?;
+    find_task(columns, task_id)?;
+    if !link {
+        db_conn.remove_task_link(blocker_id, task_id)?;
+    } else if db_conn.would_create_cycle(blocker_id, task_id)? {
+        bail!("#{blocker_id} can't block #{task_id}, a task would end up waiting on itself");
+    } else {
+        db_conn.add_task_link(blocker_id, task_id)?;
+    }
+    Ok(())
+}
+
+/// Archives the task `id`, or the done tasks completed more than
+/// `done_older_than` days ago.
+fn archive_tasks(
+    db_conn: &DBConn,
+    board: &Board,
+    columns: &[Column],
+    id: Option<i64>,
+    done_older_than: Option<u32>,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    if let Some(id) = id {
+        find_task(columns, id)?;
+        db_conn.archive_task(id)?;
+        write_changed_task(db_conn, board, id, json, out)?;
+    } else if let Some(days) = done_older_than {
+        let cutoff = Utc::now().timestamp() - i64::from(days) * 24 * 60 * 60;
+        let archived = db_conn.archive_done_tasks(board.id, cutoff)?;
+        if json {
+            let output = serde_json::json!({ "archived": archived.len(), "ids": archived });
+            writeln!(out, "{output}")?;
+        } else {
+            writeln!(out, "Archived {} task(s)", archived.len())?;
+        }
+    }
+    Ok(())
+}
+
+/// Puts an archived or deleted [`Task`] back at the end of its
+/// [`Column`].
+fn restore_task(db_conn: &DBConn, board: &Board, id: i64, kind: OffBoardKind) -> Result<(), Error> {
+    let (tasks, name) = match kind {
+        OffBoardKind::Archived => (db_conn.get_archived_tasks(board.id)?, "archived"),
+        OffBoardKind::Deleted => (db_conn.get_trashed_tasks(board.id)?, "deleted"),
+    };
+    let Some(entry) = tasks.iter().find(|t| t.task.id == id) else {
+        bail!("No {name} task with ID {id}");
+    };
+    match kind {
+        OffBoardKind::Archived => db_conn.unarchive_task(id)?,
+        OffBoardKind::Deleted => db_conn.restore_task(&entry.task, entry.column_id)?,
+    }
+    Ok(())
+}
+
+fn list_off_board_tasks(
+    tasks: &[OffBoardTask],
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    if json {
+        let output: Vec<TaskOutput<'_>> = tasks
+            .iter()
+            .map(|a| TaskOutput {
+                task: &a.task,
+                column: &a.column_name,
+            })
+            .collect();
+        writeln!(out, "{}", serde_json::to_string_pretty(&output)?)?;
+    } else {
+        for t in tasks {
+            write_task_line(out, &t.task)?;
+        }
+    }
+    Ok(())
+}
+
+fn add_board(db_conn: &DBConn, name: &str, json: bool, out: &mut impl Write) -> Result<(), Error> {
+    let boards = db_conn.get_all_boards()?;
+    if boards.iter().any(|b| b.name.eq_ignore_ascii_case(name)) {
+        bail!("There is already a board named \"{name}\"");
+    }
+    let board = db_conn.create_board(name)?;
+    if json {
+        let output = serde_json::json!({ "id": board.id, "name": board.name, "current": false });
+        writeln!(out, "{}", serde_json::to_string_pretty(&output)?)?;
+    }
+    Ok(())
+}
+
+fn list_boards(
+    db_conn: &DBConn,
+    current: &Board,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let boards = db_conn.get_all_boards()?;
+    if json {
+        let output: Vec<serde_json::Value> = boards
+            .iter()
+            .map(|b| serde_json::json!({ "id": b.id, "name": b.name, "current": b == current }))
+            .collect();
+        writeln!(out, "{}", serde_json::to_string_pretty(&output)?)?;
+    } else {
+        for board in &boards {
+            let marker = if board == current { '*' } else { ' ' };
+            writeln!(out, "{marker} {}", board.name)?;
+        }
+    }
+    Ok(())
+}
+
+fn list_tasks(
+    columns: &[Column],
+    column: Option<&str>,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let columns = match column {
+        Some(name) => std::slice::from_ref(find_column(columns, name)?),
+        None => columns,
+    };
+    if json {
+        let output: Vec<TaskOutput<'_>> = columns
+            .iter()
+            .flat_map(|c| {
+                c.tasks.iter().map(|task| TaskOutput {
+                    task,
+                    column: &c.name,
+                })
+            })
+            .collect();
+        writeln!(out, "{}", serde_json::to_string_pretty(&output)?)?;
+    } else if let [column] = columns {
+        for task in &column.tasks {
+            write_task_line(out, task)?;
+        }
+    } else {
+        for column in columns {
+            writeln!(out, "{}", column.name)?;
+            for task in &column.tasks {
+                write!(out, "  ")?;
+                write_task_line(out, task)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn show_task(
+    db_conn: &DBConn,
+    columns: &[Column],
+    id: i64,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let (column, task) = find_task(columns, id)?;
+    let history = db_conn.get_task_events(id)?;
+    if json {
+        let output = TaskDetailsOutput {
+            task: TaskOutput {
+                task,
+                column: &column.name,
+            },
+            history: &history,
+        };
+        writeln!(out, "{}", serde_json::to_string_pretty(&output)?)?;
+        return Ok(());
+    }
+
+    write_task_line(out, task)?;
+    writeln!(out, "Column:    {}", column.name)?;
+    if let Some(priority) = task.priority {
+        writeln!(out, "Priority:  {}", priority.as_str())?;
+    }
+    if let Some(due_date) = task.due_date {
+        writeln!(out, "Due:       {}", due_date.format(DUE_DATE_FORMAT))?;
+    }
+    if !task.labels.is_empty() {
+        writeln!(out, "Labels:    {}", task.labels.join(", "))?;
+    }
+    writeln!(out, "Created:   {}", format_timestamp(task.created_at))?;
+    writeln!(out, "Updated:   {}", format_timestamp(task.updated_at))?;
+    if let Some(completed_at) = task.completed_at {
+        writeln!(out, "Completed: {}", format_timestamp(completed_at))?;
+    }
+    for (title, ids) in [("Blocked by:", &task.blocked_by), ("Blocks:", &task.blocks)] {
+        // Archived tasks aren't in the columns and don't count
+        for (column, linked) in ids.iter().filter_map(|id| find_task(columns, *id).ok()) {
+            writeln!(
+                out,
+                "{title:<10} #{} {} ({})",
+                linked.id, linked.title, column.name
+            )?;
+        }
+    }
+    if !task.checklist.is_empty() {
+        let (done, total) = task.checklist_progress();
+        writeln!(out, "Checklist: {done}/{total}")?;
+        for item in &task.checklist {
+            let check = if item.done { 'x' } else { ' ' };
+            writeln!(out, "  [{check}] {}", item.text)?;
+        }
+    }
+    if !task.description.is_empty() {
+        writeln!(out, "\n{}", task.description)?;
+    }
+    writeln!(out, "\nHistory:")?;
+    for event in &history {
+        writeln!(
+            out,
+            "  {} {}",
+            format_timestamp(event.created_at),
+            event.describe()
+        )?;
+    }
+    Ok(())
+}
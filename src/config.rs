@@ -0,0 +1,164 @@
+//! The optional config file, read from the XDG config dir.
+
+use anyhow::{Context, Error};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::app::WipLimitMode;
+use crate::layout::TaskView;
+
+/// One key, or several keys doing the same thing.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    /// A single key, like `"q"`
+    One(String),
+    /// Several keys, like `["h", "Left"]`
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    /// The keys as a slice, whichever form they were written in.
+    #[must_use]
+    pub fn as_slice(&self) -> &[String] {
+        match self {
+            KeyList::One(key) => std::slice::from_ref(key),
+            KeyList::Many(keys) => keys,
+        }
+    }
+}
+
+/// The `[keys.*]` tables, mapping action names to the keys replacing
+/// their defaults. See [`Keymap`][`crate::Keymap`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct KeysConfig {
+    /// Bindings of the board
+    pub main: BTreeMap<String, KeyList>,
+    /// Bindings of the board while a search is active, tried before
+    /// [`KeysConfig::main`]
+    pub search: BTreeMap<String, KeyList>,
+    /// Bindings of the task form
+    pub task_edit: BTreeMap<String, KeyList>,
+}
+
+/// A style written in the config file, like
+/// `{ fg = "yellow", modifiers = ["bold"] }`. See
+/// [`Theme`][`crate::Theme`] for the names of the colors.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleConfig {
+    /// Color of the text
+    pub fg: Option<String>,
+    /// Color of the background
+    pub bg: Option<String>,
+    /// Modifiers like `bold`, `italic`, `underlined` or `reversed`
+    pub modifiers: Vec<String>,
+}
+
+/// The `[theme]` table, picking a built-in theme and overriding parts
+/// of it. Each style given replaces the one of the built-in theme.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ThemeConfig {
+    /// Name of the built-in theme to start from, `default` if not set
+    pub name: Option<String>,
+    /// Set to `false` for terminals that render emoji badly
+    pub emoji: Option<bool>,
+    /// Borders of the board's panes
+    pub border: Option<StyleConfig>,
+    /// The selected column
+    pub selected_column: Option<StyleConfig>,
+    /// The selected task
+    pub selected_task: Option<StyleConfig>,
+    /// Background of the popups
+    pub popup: Option<StyleConfig>,
+    /// Text of the label chips, their background is the label's color
+    pub label: Option<StyleConfig>,
+    /// Tasks past their due date
+    pub overdue: Option<StyleConfig>,
+    /// Tasks due today
+    pub due_today: Option<StyleConfig>,
+    /// Tasks waiting on an unfinished one
+    pub blocked: Option<StyleConfig>,
+    /// Border of the columns holding more tasks than their WIP limit
+    pub wip_exceeded: Option<StyleConfig>,
+}
+
+/// The `[layout]` table, sizing the board. See
+/// [`BoardLayout`][`crate::BoardLayout`] for the defaults.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct LayoutConfig {
+    /// Columns never get narrower than this many cells
+    pub min_column_width: Option<u16>,
+    /// Below this terminal width the compact mode is used
+    pub compact_width: Option<u16>,
+    /// Below this terminal height the compact mode is used
+    pub compact_height: Option<u16>,
+    /// Set to `false` to start with the info panels hidden
+    pub panels: Option<bool>,
+    /// `lines` or `cards`, how the tasks are drawn at startup
+    pub task_view: TaskView,
+}
+
+/// Everything that can be set in the config file. Every part is
+/// optional and a missing file is the same as an empty one.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Key overrides
+    pub keys: KeysConfig,
+    /// Colors and markers
+    pub theme: ThemeConfig,
+    /// Sizes of the board
+    pub layout: LayoutConfig,
+    /// `warn` or `refuse`, what happens when a column would go over its
+    /// WIP limit
+    pub wip_limit: WipLimitMode,
+    /// How many days deleted tasks stay in the trash before the app
+    /// purges them, 30 when not set
+    pub trash_days: Option<u32>,
+}
+
+impl Config {
+    /// Where the config file lives, `kanban-tui/config.toml` in the
+    /// config dir, which is `$XDG_CONFIG_HOME` or `~/.config` on Linux.
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("kanban-tui").join("config.toml"))
+    }
+
+    /// Reads the config file at [`Config::path`], if there's one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or isn't valid.
+    pub fn load() -> Result<Self, Error> {
+        match Config::path() {
+            Some(path) if path.exists() => Config::load_from(&path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// Reads the config file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or isn't valid.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Couldn't read {}", path.display()))?;
+        Config::from_toml(&text).with_context(|| format!("Invalid config in {}", path.display()))
+    }
+
+    /// Parses the contents of a config file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TOML is invalid or has unknown keys.
+    pub fn from_toml(text: &str) -> Result<Self, Error> {
+        Ok(toml::from_str(text)?)
+    }
+}
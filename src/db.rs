@@ -1,8 +1,85 @@
-use crate::{Column, Task};
+use crate::{
+    Board, ChecklistItem, Column, ColumnRole, Label, LabelColor, OffBoardTask, Priority, Task,
+    TaskEvent, TaskEventKind, DUE_DATE_FORMAT,
+};
 use anyhow::Error;
-use rusqlite::{params, Connection, Result};
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::{BTreeMap, HashSet};
 use std::ops::{Deref, DerefMut};
 
+pub mod migrate;
+
+/// Current time as a Unix timestamp, used for all the `*_at` columns.
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// The columns [`task_from_row`] expects, in the same order as the
+/// [`Task`] fields. The checklist lives in its own table and is loaded
+/// separately. The label names are joined with the ASCII unit
+/// separator since they can contain commas, the linked task IDs with
+/// commas. Links to deleted tasks are left out until they're restored.
+const TASK_COLUMNS: &str = "task.id, title, description, created_at, updated_at, completed_at,
+    (select group_concat(label.name, char(31)) from task_label
+     join label on label.id = task_label.label_id
+     where task_label.task_id = task.id),
+    priority, due_date,
+    (select group_concat(t.id) from task_link join task t on t.id = task_link.blocker_id
+     where task_link.blocked_id = task.id and t.deleted_at is null),
+    (select group_concat(t.id) from task_link join task t on t.id = task_link.blocked_id
+     where task_link.blocker_id = task.id and t.deleted_at is null),
+    archived_at";
+
+/// Splits a list of IDs joined with commas, sorting them.
+fn parse_ids(ids: Option<String>) -> Vec<i64> {
+    let mut ids: Vec<i64> = ids
+        .map(|ids| ids.split(',').filter_map(|id| id.parse().ok()).collect())
+        .unwrap_or_default();
+    ids.sort_unstable();
+    ids
+}
+
+/// Maps a row selected with [`TASK_COLUMNS`].
+fn task_from_row(row: &rusqlite::Row<'_>) -> Result<Task> {
+    let labels: Option<String> = row.get(6)?;
+    let mut labels: Vec<String> = labels
+        .map(|l| l.split('\x1f').map(String::from).collect())
+        .unwrap_or_default();
+    labels.sort_by_key(|l| l.to_lowercase());
+    let priority: Option<i64> = row.get(7)?;
+    let due_date: Option<String> = row.get(8)?;
+    Ok(Task {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+        completed_at: row.get(5)?,
+        labels,
+        priority: priority.and_then(Priority::from_level),
+        due_date: due_date.and_then(|d| NaiveDate::parse_from_str(&d, DUE_DATE_FORMAT).ok()),
+        checklist: Vec::new(),
+        blocked_by: parse_ids(row.get(9)?),
+        blocks: parse_ids(row.get(10)?),
+        archived_at: row.get(11)?,
+    })
+}
+
+/// Maps a row of `id, name, selected_column` from the `board` table.
+fn board_from_row(row: &rusqlite::Row<'_>) -> Result<Board> {
+    Ok(Board {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        selected_column_idx: row.get(2)?,
+    })
+}
+
+/// How a due date is stored in the `due_date` column.
+fn due_date_to_sql(due_date: Option<NaiveDate>) -> Option<String> {
+    due_date.map(|d| d.format(DUE_DATE_FORMAT).to_string())
+}
+
 /// Simple one field struct to wrap a  [`rusqlite::Connection`] so we
 /// can assign our own methods.
 pub struct DBConn(
@@ -30,50 +107,601 @@ impl DBConn {
         DBConn(conn)
     }
 
-    /// Query tasks in a [`Column`] by using the column's [`Column::id`].
+    /// Query tasks in a [`Column`] by using the column's [`Column::id`],
+    /// leaving out the archived ones. If the column sorts by
+    /// [`Priority`] they come in that order first, tasks without one
+    /// going last.
     ///
     /// # Errors
     ///
     /// Returns an error if something is wrong with the SQL.
     pub fn get_tasks_by_column(&self, column_id: i64) -> Result<Vec<Task>> {
-        let mut stmt = self.prepare(
-            r#"
-            select task.id, title, description from task
-            where column_id = ?1
-            order by sort_order
-        "#,
+        let mut stmt = self.prepare(&format!(
+            "select {TASK_COLUMNS} from task where column_id = ?1
+               and archived_at is null and deleted_at is null
+             order by case when (select sort_by_priority from kb_column where id = ?1)
+                      then coalesce(priority, 4) else 0 end,
+                      sort_order"
+        ))?;
+        let mut tasks = Vec::new();
+        let rows = stmt.query_map([column_id], task_from_row)?;
+        for row in rows {
+            let mut task = row?;
+            task.checklist = self.get_checklist(task.id)?;
+            tasks.push(task);
+        }
+        Ok(tasks)
+    }
+
+    /// Query a single [`Task`] by its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the task doesn't exist or something is
+    /// wrong with the SQL.
+    pub fn get_task(&self, task_id: i64) -> Result<Task> {
+        let mut task = self.query_row(
+            &format!("select {TASK_COLUMNS} from task where id = ?1"),
+            [task_id],
+            task_from_row,
         )?;
+        task.checklist = self.get_checklist(task_id)?;
+        Ok(task)
+    }
+
+    /// Query the archived tasks of a [`Board`], the most recently
+    /// archived first. Deleted tasks are in the trash instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_archived_tasks(&self, board_id: i64) -> Result<Vec<OffBoardTask>> {
+        self.get_off_board_tasks(board_id, "archived_at", "deleted_at is null")
+    }
+
+    /// Query the deleted tasks of a [`Board`] that are still in the
+    /// trash, the most recently deleted first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_trashed_tasks(&self, board_id: i64) -> Result<Vec<OffBoardTask>> {
+        self.get_off_board_tasks(board_id, "deleted_at", "true")
+    }
+
+    /// Query the tasks of a [`Board`] where the `removed_at` timestamp
+    /// column is set and `filter` holds, the most recent first.
+    fn get_off_board_tasks(
+        &self,
+        board_id: i64,
+        removed_at: &str,
+        filter: &str,
+    ) -> Result<Vec<OffBoardTask>> {
+        let mut stmt = self.prepare(&format!(
+            "select {TASK_COLUMNS}, kb_column.id, kb_column.name, {removed_at} from task
+             join kb_column on kb_column.id = task.column_id
+             where board_id = ?1 and {removed_at} is not null and {filter}
+             order by {removed_at} desc, task.id desc"
+        ))?;
         let mut tasks = Vec::new();
-        let rows = stmt.query_map([column_id], |row| {
-            Ok(Task {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
+        let rows = stmt.query_map([board_id], |row| {
+            Ok(OffBoardTask {
+                task: task_from_row(row)?,
+                column_id: row.get(12)?,
+                column_name: row.get(13)?,
+                removed_at: row.get(14)?,
             })
         })?;
         for row in rows {
-            tasks.push(row?);
+            let mut entry = row?;
+            entry.task.checklist = self.get_checklist(entry.task.id)?;
+            tasks.push(entry);
         }
         Ok(tasks)
     }
 
+    /// Archives a [`Task`], which takes it off the board without
+    /// losing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn archive_task(&self, task_id: i64) -> Result<()> {
+        let now = now();
+        self.execute(
+            "update task set archived_at = ?2 where id = ?1",
+            (task_id, now),
+        )?;
+        let column_id = self.get_task_column_id(task_id)?;
+        self.add_task_event(task_id, TaskEventKind::Archived, column_id, now)
+    }
+
+    /// Archives every [`Task`] of a [`Board`] that sits in a
+    /// [`ColumnRole::Done`] column and was completed before
+    /// `completed_before`. Returns the IDs of the archived tasks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn archive_done_tasks(&self, board_id: i64, completed_before: i64) -> Result<Vec<i64>> {
+        let tx = self.unchecked_transaction()?;
+        let now = now();
+        let done_tasks = "select task.id from task
+                          join kb_column on kb_column.id = task.column_id
+                          where board_id = ?1 and role = 'done'
+                            and archived_at is null and deleted_at is null
+                            and completed_at < ?2";
+        let ids = self
+            .prepare(&format!("{done_tasks} order by task.id"))?
+            .query_map((board_id, completed_before), |row| row.get(0))?
+            .collect::<Result<Vec<i64>>>()?;
+        self.execute(
+            &format!(
+                "insert into task_event(task_id, kind, column_id, created_at)
+                 select id, ?3, column_id, ?4 from task where id in ({done_tasks})"
+            ),
+            (
+                board_id,
+                completed_before,
+                TaskEventKind::Archived.as_str(),
+                now,
+            ),
+        )?;
+        self.execute(
+            &format!("update task set archived_at = ?3 where id in ({done_tasks})"),
+            (board_id, completed_before, now),
+        )?;
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    /// Puts an archived [`Task`] back at the end of its [`Column`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn unarchive_task(&self, task_id: i64) -> Result<()> {
+        self.execute(
+            "update task
+             set
+               archived_at = null,
+               sort_order = coalesce(1 +
+                 (select max(sort_order) from task t where t.column_id = task.column_id),
+                 0)
+             where id = ?1",
+            [task_id],
+        )?;
+        let column_id = self.get_task_column_id(task_id)?;
+        self.add_task_event(task_id, TaskEventKind::Unarchived, column_id, now())
+    }
+
+    /// Query the [`ChecklistItem`]s of a [`Task`], in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_checklist(&self, task_id: i64) -> Result<Vec<ChecklistItem>> {
+        let mut stmt = self.prepare(
+            "select text, done from checklist_item where task_id = ?1 order by sort_order",
+        )?;
+        let items = stmt
+            .query_map([task_id], |row| {
+                Ok(ChecklistItem {
+                    text: row.get(0)?,
+                    done: row.get(1)?,
+                })
+            })?
+            .collect();
+        items
+    }
+
+    /// Returns `true` if `blocker_id` blocking the [`Task`] `task_id`
+    /// would make a task wait on itself, directly or through other
+    /// links.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn would_create_cycle(&self, blocker_id: i64, task_id: i64) -> Result<bool> {
+        self.query_row(
+            "with recursive blocked_chain(id) as (
+                 select ?2
+                 union
+                 select task_link.blocked_id from task_link
+                 join blocked_chain on task_link.blocker_id = blocked_chain.id
+             )
+             select exists(select 1 from blocked_chain where id = ?1)",
+            (blocker_id, task_id),
+            |row| row.get(0),
+        )
+    }
+
+    /// Marks the [`Task`] `task_id` as blocked by `blocker_id` until
+    /// that one is done. Does nothing if the link already exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either task doesn't exist, if they are the
+    /// same task or if something is wrong with the SQL.
+    pub fn add_task_link(&self, blocker_id: i64, task_id: i64) -> Result<()> {
+        self.execute(
+            "insert or ignore into task_link(blocker_id, blocked_id) values (?1, ?2)",
+            (blocker_id, task_id),
+        )?;
+        Ok(())
+    }
+
+    /// Removes the link added by
+    /// [`add_task_link`][`DBConn::add_task_link`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn remove_task_link(&self, blocker_id: i64, task_id: i64) -> Result<()> {
+        self.execute(
+            "delete from task_link where blocker_id = ?1 and blocked_id = ?2",
+            (blocker_id, task_id),
+        )?;
+        Ok(())
+    }
+
+    /// Replaces the tasks blocking a [`Task`], skipping the ones that
+    /// don't exist anymore.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_task_blockers(&self, task_id: i64, blocker_ids: &[i64]) -> Result<()> {
+        self.execute("delete from task_link where blocked_id = ?1", [task_id])?;
+        for blocker_id in blocker_ids {
+            self.execute(
+                "insert or ignore into task_link(blocker_id, blocked_id)
+                 select id, ?2 from task where id = ?1 and id != ?2",
+                (blocker_id, task_id),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The first task still blocking the [`Task`] `task_id` as
+    /// `(id, title)`, on any board. Blockers in a done column, archived
+    /// or in the trash don't count.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_unfinished_blocker(&self, task_id: i64) -> Result<Option<(i64, String)>> {
+        self.query_row(
+            "select blocker.id, blocker.title from task_link
+             join task blocker on blocker.id = task_link.blocker_id
+             join kb_column on kb_column.id = blocker.column_id
+             where task_link.blocked_id = ?1
+               and blocker.deleted_at is null and blocker.archived_at is null
+               and kb_column.role != 'done'
+             order by blocker.id
+             limit 1",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+    }
+
+    /// Every link between tasks as `(blocker_id, blocked_id)`, leaving
+    /// out the ones to tasks in the trash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_task_links(&self) -> Result<Vec<(i64, i64)>> {
+        let mut stmt = self.prepare(
+            "select blocker_id, blocked_id from task_link
+             join task blocker on blocker.id = blocker_id
+             join task blocked on blocked.id = blocked_id
+             where blocker.deleted_at is null and blocked.deleted_at is null",
+        )?;
+        let links = stmt
+            .query_map((), |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect();
+        links
+    }
+
+    /// Replaces the [`ChecklistItem`]s of a [`Task`], keeping the
+    /// order of `items`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_checklist(&self, task_id: i64, items: &[ChecklistItem]) -> Result<()> {
+        self.execute("delete from checklist_item where task_id = ?1", [task_id])?;
+        let mut stmt = self.prepare(
+            "insert into checklist_item(task_id, text, done, sort_order) values (?1, ?2, ?3, ?4)",
+        )?;
+        for (order, item) in items.iter().enumerate() {
+            stmt.execute(params![task_id, item.text, item.done, order])?;
+        }
+        Ok(())
+    }
+
+    /// Query the ID of the [`Column`] a [`Task`] is in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the task doesn't exist or something is
+    /// wrong with the SQL.
+    pub fn get_task_column_id(&self, task_id: i64) -> Result<Option<i64>> {
+        self.query_row("select column_id from task where id = ?1", [task_id], |row| {
+            row.get(0)
+        })
+    }
+
+    /// Returns the IDs of the tasks whose title or description has a
+    /// word starting with each of the words in `query`, using the FTS5
+    /// index. A query without words matches nothing. Tasks in the
+    /// trash are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn search_tasks(&self, query: &str) -> Result<HashSet<i64>> {
+        self.search(query, false)
+    }
+
+    /// Same as [`DBConn::search_tasks`] but only for the tasks in the
+    /// trash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn search_trashed_tasks(&self, query: &str) -> Result<HashSet<i64>> {
+        self.search(query, true)
+    }
+
+    fn search(&self, query: &str, in_trash: bool) -> Result<HashSet<i64>> {
+        // Quote every word so FTS5 operators and punctuation typed by
+        // the user are matched literally, then make them prefixes
+        let fts_query = query
+            .split_whitespace()
+            .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if fts_query.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let mut stmt = self.prepare(
+            "select task.id from task_fts join task on task.id = task_fts.rowid
+             where task_fts match ?1 and (deleted_at is not null) = ?2",
+        )?;
+        let ids = stmt
+            .query_map((fts_query, in_trash), |row| row.get(0))?
+            .collect::<Result<HashSet<i64>>>()?;
+        Ok(ids)
+    }
+
+    /// Query every [`Label`], sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_all_labels(&self) -> Result<Vec<Label>> {
+        let mut stmt =
+            self.prepare("select id, name, color from label order by name collate nocase")?;
+        let labels = stmt
+            .query_map((), |row| {
+                let color: String = row.get(2)?;
+                Ok(Label {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    color: LabelColor::from_name(&color).unwrap_or(LabelColor::Blue),
+                })
+            })?
+            .collect::<Result<Vec<Label>>>()?;
+        Ok(labels)
+    }
+
+    /// Creates a new [`Label`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a label with the same name exists or
+    /// something is wrong with the SQL.
+    pub fn create_label(&self, name: &str, color: LabelColor) -> Result<Label> {
+        self.execute(
+            "insert into label(name, color) values (?1, ?2)",
+            (name, color.as_str()),
+        )?;
+        Ok(Label {
+            id: self.last_insert_rowid(),
+            name: name.to_string(),
+            color,
+        })
+    }
+
+    /// Renames a [`Label`], which renames it on every [`Task`] too.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a label with the same name exists or
+    /// something is wrong with the SQL.
+    pub fn rename_label(&self, label_id: i64, name: &str) -> Result<()> {
+        self.execute("update label set name = ?2 where id = ?1", (label_id, name))?;
+        Ok(())
+    }
+
+    /// Changes the [`LabelColor`] of a [`Label`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_label_color(&self, label_id: i64, color: LabelColor) -> Result<()> {
+        self.execute(
+            "update label set color = ?2 where id = ?1",
+            (label_id, color.as_str()),
+        )?;
+        Ok(())
+    }
+
+    /// Deletes a [`Label`] and takes it off every [`Task`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn delete_label(&self, label_id: i64) -> Result<()> {
+        self.execute("delete from label where id = ?1", [label_id])?;
+        Ok(())
+    }
+
+    /// Replaces the [`Label`]s of a [`Task`]. Labels are matched by
+    /// name ignoring case, and the missing ones are created with the
+    /// next color of [`LabelColor::ALL`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_task_labels(&self, task_id: i64, names: &[String]) -> Result<()> {
+        self.execute("delete from task_label where task_id = ?1", [task_id])?;
+        for name in names {
+            let existing = self
+                .query_row("select id from label where name = ?1", [name], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+            let label_id = if let Some(id) = existing {
+                id
+            } else {
+                let count: usize =
+                    self.query_row("select count(*) from label", (), |row| row.get(0))?;
+                let color = LabelColor::ALL[count % LabelColor::ALL.len()];
+                self.create_label(name, color)?.id
+            };
+            self.execute(
+                "insert or ignore into task_label(task_id, label_id) values (?1, ?2)",
+                (task_id, label_id),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Query every [`Board`], in the order they were created.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_all_boards(&self) -> Result<Vec<Board>> {
+        let mut stmt = self.prepare("select id, name, selected_column from board order by id")?;
+        let boards = stmt.query_map((), board_from_row)?.collect();
+        boards
+    }
+
+    /// Query a single [`Board`] by its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the board doesn't exist or something is
+    /// wrong with the SQL.
+    pub fn get_board(&self, board_id: i64) -> Result<Board> {
+        self.query_row(
+            "select id, name, selected_column from board where id = ?1",
+            [board_id],
+            board_from_row,
+        )
+    }
+
+    /// Insert a new [`Board`] with the same columns a new database
+    /// starts with, and return it with the ID provided by the DB.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a board with the same name exists or
+    /// something is wrong with the SQL.
+    pub fn create_board(&self, name: &str) -> Result<Board> {
+        let tx = self.unchecked_transaction()?;
+        self.execute("insert into board(name) values (?1)", [name])?;
+        let board_id = self.last_insert_rowid();
+        for (column, role) in [
+            ("Todo", ColumnRole::Backlog),
+            ("InProgress", ColumnRole::Active),
+            ("Done", ColumnRole::Done),
+            ("Ideas", ColumnRole::Parked),
+        ] {
+            self.create_column(board_id, column.to_string(), role)?;
+        }
+        tx.commit()?;
+        Ok(Board {
+            id: board_id,
+            name: name.to_string(),
+            selected_column_idx: 0,
+        })
+    }
+
+    /// Updates the name of a [`Board`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a board with the same name exists or
+    /// something is wrong with the SQL.
+    pub fn rename_board(&self, board_id: i64, name: &str) -> Result<()> {
+        self.execute("update board set name = ?2 where id = ?1", (board_id, name))?;
+        Ok(())
+    }
+
+    /// Deletes a [`Board`] along with its [`Column`]s. Their tasks go
+    /// to the trash of the first remaining board, on its first column,
+    /// which is where restoring them puts them back. Archived tasks
+    /// stay archived. There has to be another board.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn delete_board(&self, board_id: i64) -> Result<()> {
+        let tx = self.unchecked_transaction()?;
+        let now = now();
+        self.execute(
+            "insert into task_event(task_id, kind, column_id, created_at)
+             select task.id, ?2, column_id, ?3 from task
+             join kb_column on kb_column.id = task.column_id
+             where board_id = ?1 and deleted_at is null and archived_at is null",
+            (board_id, TaskEventKind::Deleted.as_str(), now),
+        )?;
+        self.execute(
+            "update task
+             set
+               deleted_at = coalesce(deleted_at, case when archived_at is null then ?2 end),
+               column_id =
+                 (select id from kb_column
+                  where board_id = (select id from board where id != ?1 order by id limit 1)
+                  order by sort_order limit 1)
+             where column_id in (select id from kb_column where board_id = ?1)",
+            (board_id, now),
+        )?;
+        self.execute("delete from kb_column where board_id = ?1", [board_id])?;
+        self.execute("delete from board where id = ?1", [board_id])?;
+        tx.commit()
+    }
+
     /// Uses [`get_tasks_by_column`][`DBConn::get_tasks_by_column`] over
-    /// a loop to get all [`Column`] populated with the vec of.
-    /// [`Task`]
+    /// a loop to get all [`Column`] of a [`Board`] populated with the
+    /// vec of [`Task`]
     ///
     /// # Errors
     ///
     /// Returns an error if something is wrong with the SQL.
-    pub fn get_all_columns(&self) -> Result<Vec<Column>> {
-        let mut stmt = self.prepare("select id, name, selected_task from kb_column")?;
+    pub fn get_all_columns(&self, board_id: i64) -> Result<Vec<Column>> {
+        let mut stmt = self.prepare(
+            "select id, name, selected_task, role, sort_by_priority, wip_limit
+             from kb_column where board_id = ?1 order by sort_order",
+        )?;
         let columns = stmt
-            .query_map((), |row| {
+            .query_map([board_id], |row| {
                 let id = row.get(0)?;
+                let role: String = row.get(3)?;
                 Ok(Column {
                     id,
                     tasks: self.get_tasks_by_column(id)?,
                     name: row.get(1)?,
+                    role: ColumnRole::from_name(&role).unwrap_or(ColumnRole::Backlog),
                     selected_task_idx: row.get(2)?,
+                    sort_by_priority: row.get(4)?,
+                    wip_limit: row.get(5)?,
                 })
             })?
             .filter_map(Result::ok)
@@ -81,6 +709,187 @@ impl DBConn {
         Ok(columns)
     }
 
+    /// Insert a new [`Column`] at the end of a [`Board`] and return
+    /// it with the ID provided by the DB.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn create_column(&self, board_id: i64, name: String, role: ColumnRole) -> Result<Column> {
+        let mut stmt = self.prepare(
+            "insert into kb_column(board_id, name, role, sort_order)
+             values (?1, ?2, ?3,
+               coalesce(1 + (select max(sort_order) from kb_column where board_id = ?1), 0))",
+        )?;
+        stmt.execute((board_id, &name, role.as_str()))?;
+        Ok(Column {
+            id: self.last_insert_rowid(),
+            name,
+            role,
+            selected_task_idx: 0,
+            tasks: Vec::new(),
+            sort_by_priority: false,
+            wip_limit: None,
+        })
+    }
+
+    /// Updates the name of a [`Column`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn rename_column(&self, column_id: i64, name: &str) -> Result<()> {
+        let mut stmt = self.prepare("update kb_column set name = ?2 where id = ?1")?;
+        stmt.execute((column_id, name))?;
+        Ok(())
+    }
+
+    /// Updates the [`ColumnRole`] of a [`Column`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_column_role(&self, column_id: i64, role: ColumnRole) -> Result<()> {
+        let mut stmt = self.prepare("update kb_column set role = ?2 where id = ?1")?;
+        stmt.execute((column_id, role.as_str()))?;
+        Ok(())
+    }
+
+    /// Switches a [`Column`] between sorting its tasks by
+    /// [`Priority`] first and only by their manual order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_column_sort_by_priority(&self, column_id: i64, enabled: bool) -> Result<()> {
+        self.execute(
+            "update kb_column set sort_by_priority = ?2 where id = ?1",
+            (column_id, enabled),
+        )?;
+        Ok(())
+    }
+
+    /// Sets or clears the WIP limit of a [`Column`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_column_wip_limit(&self, column_id: i64, wip_limit: Option<usize>) -> Result<()> {
+        self.execute(
+            "update kb_column set wip_limit = ?2 where id = ?1",
+            (column_id, wip_limit),
+        )?;
+        Ok(())
+    }
+
+    /// Swaps the `sort_order` of two [`Column`]s, which is how the
+    /// columns are laid out left to right.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn swap_column_order(&mut self, column1_id: i64, column2_id: i64) -> Result<()> {
+        let tx = self.transaction()?;
+
+        let get_order = |id: i64| -> Result<i64> {
+            tx.query_row("select sort_order from kb_column where id = ?1", [id], |row| {
+                row.get(0)
+            })
+        };
+        let order1 = get_order(column1_id)?;
+        let order2 = get_order(column2_id)?;
+
+        tx.execute(
+            "update kb_column set sort_order = ?2 where id = ?1",
+            (column1_id, order2),
+        )?;
+        tx.execute(
+            "update kb_column set sort_order = ?2 where id = ?1",
+            (column2_id, order1),
+        )?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Deletes a [`Column`]. If `move_tasks_to` has the ID of another
+    /// column, its tasks get appended to the end of that column keeping
+    /// their order, otherwise they go to the trash. Since their column
+    /// is gone they are kept on the first remaining column of the
+    /// board, which is where restoring them puts them back.
+    /// Archived tasks stay archived either way.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn delete_column(&mut self, column_id: i64, move_tasks_to: Option<i64>) -> Result<()> {
+        let tx = self.transaction()?;
+
+        let now = now();
+        if let Some(target_id) = move_tasks_to {
+            // Query the offset up front, otherwise the subquery would
+            // see the tasks that were already moved over
+            let offset: i64 = tx.query_row(
+                "select coalesce(1 + max(sort_order), 0) from task where column_id = ?1",
+                [target_id],
+                |row| row.get(0),
+            )?;
+            // Archived and deleted tasks follow along to keep a column,
+            // but only the ones on the board actually move
+            tx.execute(
+                "insert into task_event(task_id, kind, column_id, created_at)
+                 select id, ?3, ?2, ?4 from task
+                 where column_id = ?1 and deleted_at is null and archived_at is null",
+                (column_id, target_id, TaskEventKind::Moved.as_str(), now),
+            )?;
+            tx.execute(
+                "update task
+                 set
+                   column_id = ?2,
+                   sort_order = sort_order + ?3,
+                   updated_at =
+                     case when deleted_at is null and archived_at is null
+                     then ?4 else updated_at end,
+                   completed_at =
+                     case
+                       when deleted_at is not null or archived_at is not null
+                         or (select role from kb_column where id = ?2)
+                          = (select role from kb_column where id = ?1)
+                       then completed_at
+                       when (select role from kb_column where id = ?2) = 'done' then ?4
+                       else null
+                     end
+                 where column_id = ?1",
+                (column_id, target_id, offset, now),
+            )?;
+        } else {
+            tx.execute(
+                "insert into task_event(task_id, kind, column_id, created_at)
+                 select id, ?2, column_id, ?3 from task
+                 where column_id = ?1 and deleted_at is null and archived_at is null",
+                (column_id, TaskEventKind::Deleted.as_str(), now),
+            )?;
+            tx.execute(
+                "update task
+                 set
+                   deleted_at = coalesce(deleted_at, case when archived_at is null then ?2 end),
+                   column_id =
+                     (select id from kb_column
+                      where board_id = (select board_id from kb_column where id = ?1)
+                        and id != ?1
+                      order by sort_order limit 1)
+                 where column_id = ?1",
+                (column_id, now),
+            )?;
+        }
+        tx.execute("delete from kb_column where id = ?1", [column_id])?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
     /// Insert a new task into the DB given a title and description,
     /// then return the [`Task`] with the ID provided by the DB.
     ///
@@ -93,52 +902,253 @@ impl DBConn {
         description: String,
         column_id: i64,
     ) -> Result<Task> {
+        let now = now();
         let mut stmt =
             self.prepare(
-                "insert into task(title, description, column_id, sort_order)
+                "insert into task(title, description, column_id, sort_order, created_at, updated_at)
                 values (?1, ?2, ?3,
                   (coalesce(1 +
                     (select sort_order from task
                      where column_id = ?3 order by sort_order desc limit 1),
-                  0)))")?;
-        stmt.execute(params![title, description, column_id])?;
+                  0)), ?4, ?4)")?;
+        stmt.execute(params![title, description, column_id, now])?;
         let id = self.last_insert_rowid();
+        self.add_task_event(id, TaskEventKind::Created, Some(column_id), now)?;
         Ok(Task {
             id,
             title,
             description,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+            labels: Vec::new(),
+            priority: None,
+            due_date: None,
+            checklist: Vec::new(),
+            blocked_by: Vec::new(),
+            blocks: Vec::new(),
+            archived_at: None,
         })
     }
 
-    /// Deletes a [`Task`] given it's ID.
+    /// Insert an existing [`Task`], like one read from an export, at
+    /// the end of a [`Column`] keeping its text and timestamps. Its
+    /// history starts with a creation event at the original time.
+    /// Returns the ID provided by the DB.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn import_task(&self, task: &Task, column_id: i64) -> Result<i64> {
+        let mut stmt = self.prepare(
+            "insert into task(title, description, column_id, sort_order,
+                              created_at, updated_at, completed_at, priority, due_date,
+                              archived_at)
+             values (?1, ?2, ?3,
+               coalesce(1 + (select max(sort_order) from task where column_id = ?3), 0),
+               ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        stmt.execute(params![
+            task.title,
+            task.description,
+            column_id,
+            task.created_at,
+            task.updated_at,
+            task.completed_at,
+            task.priority.map(Priority::level),
+            due_date_to_sql(task.due_date),
+            task.archived_at
+        ])?;
+        let id = self.last_insert_rowid();
+        self.set_task_labels(id, &task.labels)?;
+        self.set_checklist(id, &task.checklist)?;
+        self.add_task_event(id, TaskEventKind::Created, Some(column_id), task.created_at)?;
+        Ok(id)
+    }
+
+    /// Deletes every [`Task`] of a [`Board`], their history and every
+    /// [`Column`] of the board, leaving it empty. The labels are
+    /// shared with the other boards so they stay.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn clear_board(&self, board_id: i64) -> Result<()> {
+        let tasks = "select task.id from task
+                     join kb_column on kb_column.id = task.column_id
+                     where board_id = ?1";
+        self.execute(
+            &format!("delete from task_event where task_id in ({tasks})"),
+            [board_id],
+        )?;
+        // The checklists, labels and links of the tasks cascade
+        self.execute(
+            &format!("delete from task where id in ({tasks})"),
+            [board_id],
+        )?;
+        self.execute("delete from kb_column where board_id = ?1", [board_id])?;
+        self.execute(
+            "update board set selected_column = 0 where id = ?1",
+            [board_id],
+        )?;
+        Ok(())
+    }
+
+    /// Moves a [`Task`] to the trash given it's ID. It stays there
+    /// until it's restored or purged by [`DBConn::purge_trash`].
     ///
     /// # Errors
     ///
     /// Returns an error if something is wrong with the SQL.
     pub fn delete_task(&self, task_id: i64) -> Result<()> {
-        let mut stmt = self.prepare("delete from task where id = ?1")?;
-        stmt.execute([task_id])?;
+        let now = now();
+        let column_id = self.get_task_column_id(task_id)?;
+        self.execute(
+            "update task set deleted_at = ?2 where id = ?1",
+            (task_id, now),
+        )?;
+        self.add_task_event(task_id, TaskEventKind::Deleted, column_id, now)?;
+        Ok(())
+    }
+
+    /// Deletes the tasks that have been in the trash for more than
+    /// `older_than_days` days for good. Returns how many were purged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn purge_trash(&self, older_than_days: u32) -> Result<usize> {
+        let deleted_before = now() - i64::from(older_than_days) * 24 * 60 * 60;
+        self.execute("delete from task where deleted_at < ?1", [deleted_before])
+    }
+
+    /// Puts back a deleted [`Task`] with its original ID and
+    /// timestamps, at the end of the [`Column`]. Takes it out of the
+    /// trash if it's still there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn restore_task(&self, task: &Task, column_id: i64) -> Result<()> {
+        let mut stmt = self.prepare(
+            "insert into task(id, title, description, column_id, sort_order,
+                              created_at, updated_at, completed_at, priority, due_date)
+             values (?1, ?2, ?3, ?4,
+               coalesce(1 + (select max(sort_order) from task where column_id = ?4), 0),
+               ?5, ?6, ?7, ?8, ?9)
+             on conflict(id) do update set
+               column_id = excluded.column_id,
+               sort_order = excluded.sort_order,
+               deleted_at = null",
+        )?;
+        stmt.execute(params![
+            task.id,
+            task.title,
+            task.description,
+            column_id,
+            task.created_at,
+            task.updated_at,
+            task.completed_at,
+            task.priority.map(Priority::level),
+            due_date_to_sql(task.due_date)
+        ])?;
+        self.set_task_labels(task.id, &task.labels)?;
+        self.set_checklist(task.id, &task.checklist)?;
+        self.set_task_blockers(task.id, &task.blocked_by)?;
+        for blocked_id in &task.blocks {
+            self.execute(
+                "insert or ignore into task_link(blocker_id, blocked_id)
+                 select ?1, id from task where id = ?2",
+                (task.id, blocked_id),
+            )?;
+        }
+        self.add_task_event(task.id, TaskEventKind::Restored, Some(column_id), now())?;
         Ok(())
     }
 
+    /// Rewrites the `sort_order` of the tasks of a [`Column`] so they
+    /// follow the order of `task_ids`. Uses a savepoint so it can run
+    /// inside a bigger transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_task_order(&mut self, column_id: i64, task_ids: &[i64]) -> Result<()> {
+        let tx = self.savepoint()?;
+        let mut stmt =
+            tx.prepare("update task set sort_order = ?3 where id = ?1 and column_id = ?2")?;
+        for (order, id) in task_ids.iter().enumerate() {
+            stmt.execute(params![id, column_id, order])?;
+        }
+        drop(stmt);
+        tx.commit()
+    }
+
     /// Updates an existing [`Task`]'s `title` and `description`.
     ///
     /// # Errors
     ///
     /// Returns an error if something is wrong with the SQL.
     pub fn update_task_text(&self, task: &Task) -> Result<()> {
-        let mut stmt =
-            self.prepare("update task set title = ?2, description = ?3 where id = ?1")?;
-        stmt.execute((&task.id, &task.title, &task.description))?;
+        let now = now();
+        let mut stmt = self.prepare(
+            "update task set title = ?2, description = ?3, updated_at = ?4 where id = ?1",
+        )?;
+        stmt.execute((&task.id, &task.title, &task.description, now))?;
+        let column_id = self.get_task_column_id(task.id)?;
+        self.add_task_event(task.id, TaskEventKind::Edited, column_id, now)?;
+        Ok(())
+    }
+
+    /// Puts back the `title` and `description` of a [`Task`] without
+    /// touching its history, for undo and redo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_task_text(&self, task: &Task) -> Result<()> {
+        self.execute(
+            "update task set title = ?2, description = ?3 where id = ?1",
+            (&task.id, &task.title, &task.description),
+        )?;
+        Ok(())
+    }
+
+    /// Updates the [`Priority`] of a [`Task`], `None` removes it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_task_priority(&self, task_id: i64, priority: Option<Priority>) -> Result<()> {
+        self.execute(
+            "update task set priority = ?2, updated_at = ?3 where id = ?1",
+            params![task_id, priority.map(Priority::level), now()],
+        )?;
         Ok(())
     }
 
-    /// Moves a [`Task`] to the target [`Column`] and updates the sorting order.
+    /// Updates the due date of a [`Task`], `None` removes it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_task_due_date(&self, task_id: i64, due_date: Option<NaiveDate>) -> Result<()> {
+        self.execute(
+            "update task set due_date = ?2, updated_at = ?3 where id = ?1",
+            params![task_id, due_date_to_sql(due_date), now()],
+        )?;
+        Ok(())
+    }
+
+    /// Moves a [`Task`] to the target [`Column`] and updates the sorting
+    /// order. Entering a [`ColumnRole::Done`] column marks the task as
+    /// completed, leaving it clears that mark.
     ///
     /// # Errors
     ///
     /// Returns an error if something is wrong with the SQL.
     pub fn move_task_to_column(&self, task: &Task, target_column: &Column) -> Result<()> {
+        let now = now();
         let mut stmt = self
             .prepare(
                 "update task
@@ -147,14 +1157,90 @@ impl DBConn {
                sort_order = coalesce(1 +
                  (select sort_order from task
                   where column_id = ?2 order by sort_order desc limit 1),
-                  0)
+                  0),
+               updated_at = ?3,
+               completed_at =
+                 case when (select role from kb_column where id = ?2) = 'done'
+                 then ?3 else null end
              where task.id = ?1",
             )?;
-        stmt.execute((&task.id, &target_column.id))?;
+        stmt.execute((&task.id, &target_column.id, now))?;
+        self.add_task_event(task.id, TaskEventKind::Moved, Some(target_column.id), now)?;
         self.set_selected_task_for_column(target_column.selected_task_idx, target_column.id)?;
         Ok(())
     }
 
+    /// Records an entry in a [`Task`]'s activity history.
+    fn add_task_event(
+        &self,
+        task_id: i64,
+        kind: TaskEventKind,
+        column_id: Option<i64>,
+        created_at: i64,
+    ) -> Result<()> {
+        let mut stmt = self.prepare(
+            "insert into task_event(task_id, kind, column_id, created_at)
+             values (?1, ?2, ?3, ?4)",
+        )?;
+        stmt.execute((task_id, kind.as_str(), column_id, created_at))?;
+        Ok(())
+    }
+
+    /// Query the activity history of a [`Task`], oldest first. The
+    /// history outlives the task, so this also works for deleted ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_task_events(&self, task_id: i64) -> Result<Vec<TaskEvent>> {
+        self.query_task_events("where task_id = ?1", [task_id])
+    }
+
+    /// Query the activity history of every [`Task`] that has been on
+    /// a [`Board`], sorted by task and then oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_board_task_events(&self, board_id: i64) -> Result<Vec<TaskEvent>> {
+        self.query_task_events(
+            "where task_id in (
+               select e.task_id from task_event e
+               join kb_column c on c.id = e.column_id
+               where c.board_id = ?1
+             )",
+            [board_id],
+        )
+    }
+
+    fn query_task_events<P: rusqlite::Params>(
+        &self,
+        filter: &str,
+        params: P,
+    ) -> Result<Vec<TaskEvent>> {
+        let mut stmt = self.prepare(&format!(
+            "select task_id, kind, column_id, kb_column.name, task_event.created_at
+             from task_event
+             left join kb_column on kb_column.id = task_event.column_id
+             {filter}
+             order by task_id, task_event.created_at, task_event.id"
+        ))?;
+        let events = stmt
+            .query_map(params, |row| {
+                let kind: String = row.get(1)?;
+                Ok(TaskEvent {
+                    task_id: row.get(0)?,
+                    kind: TaskEventKind::from_name(&kind)
+                        .ok_or(rusqlite::Error::InvalidQuery)?,
+                    column_id: row.get(2)?,
+                    column_name: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
     /// This is a helper function in case we need to debug `sort_order`, because I ran into
     /// a bug when I forgot to insert the `sort_order` when creating a task.
     #[allow(dead_code)]
@@ -179,7 +1265,7 @@ impl DBConn {
     ///
     /// Returns an error if something is wrong with the SQL.
     pub fn swap_task_order(&mut self, task1_id: i64, task2_id: i64) -> Result<()> {
-        let tx = self.transaction()?;
+        let tx = self.savepoint()?;
 
         tx.execute(
             "create temp table temp_order as select sort_order from task where id = ?1",
@@ -205,32 +1291,73 @@ impl DBConn {
         Ok(())
     }
 
-    /// Saves the currently selected column's index to `app_state` so
-    /// when the user reloads the project, they start on the
+    /// Saves the currently selected column's index in its [`Board`]
+    /// so when the user comes back to the board, they start on the
     /// [`Column`] they were last on.
     ///
     /// # Errors
     ///
     /// Returns an error if something is wrong with the SQL.
-    pub fn set_selected_column(&self, column_id: usize) -> Result<(), Error> {
-        let mut stmt =
-            self.prepare("insert or replace into app_state(key, value) values (?1, ?2)")?;
-        stmt.execute((&"selected_column", column_id.to_string()))?;
+    pub fn set_selected_column(&self, board_id: i64, column_idx: usize) -> Result<(), Error> {
+        let mut stmt = self.prepare("update board set selected_column = ?2 where id = ?1")?;
+        stmt.execute((board_id, column_idx))?;
         Ok(())
     }
 
-    /// Get's the user's last selected [`Column`] before exiting.
+    /// Saves the ID of the [`Board`] the user is on to `app_state` so
+    /// the app opens it again next time.
     ///
     /// # Errors
     ///
     /// Returns an error if something is wrong with the SQL.
-    pub fn get_selected_column(&self) -> Result<usize> {
-        let mut stmt = self.prepare("select value from app_state where key = ?1")?;
-        stmt.query_row(["selected_column"], |row| {
-            let value: String = row.get::<usize, String>(0)?;
-            value.parse::<usize>()
-                .map_err(|_| rusqlite::Error::InvalidQuery)
-        })
+    pub fn set_selected_board(&self, board_id: i64) -> Result<()> {
+        self.set_app_state("selected_board", &board_id.to_string(), true)
+    }
+
+    /// Get's the ID of the user's last selected [`Board`] before
+    /// exiting, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_selected_board(&self) -> Result<Option<i64>> {
+        let value: Option<String> = self
+            .query_row(
+                "select value from app_state where key = 'selected_board'",
+                (),
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value.and_then(|v| v.parse().ok()))
+    }
+
+    /// Get's every key and value saved in `app_state`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn get_app_state(&self) -> Result<BTreeMap<String, String>> {
+        let mut stmt = self.prepare("select key, value from app_state")?;
+        let values = stmt
+            .query_map((), |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect();
+        values
+    }
+
+    /// Saves a value in `app_state`. Only replaces an existing value
+    /// when `overwrite` is true.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if something is wrong with the SQL.
+    pub fn set_app_state(&self, key: &str, value: &str, overwrite: bool) -> Result<()> {
+        let sql = if overwrite {
+            "insert or replace into app_state(key, value) values (?1, ?2)"
+        } else {
+            "insert or ignore into app_state(key, value) values (?1, ?2)"
+        };
+        self.execute(sql, (key, value))?;
+        Ok(())
     }
 
     /// Saves the index currently selected [`Task`] in a [`Column`] so
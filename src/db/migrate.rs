@@ -0,0 +1,103 @@
+//! Versioned schema migrations.
+//!
+//! The schema version of a database is kept in `SQLite`'s
+//! `PRAGMA user_version`. Each entry in [`MIGRATIONS`] brings the
+//! schema from one version to the next, so the version of a database
+//! is simply the number of steps that have been applied to it.
+
+use anyhow::{bail, Error};
+use rusqlite::{Connection, Transaction};
+
+/// The ordered list of migration steps embedded in the binary. Steps
+/// must never be edited or removed once released, only appended.
+const MIGRATIONS: &[&str] = &[
+    include_str!("../../sql/migrations/0001_initial.sql"),
+    include_str!("../../sql/migrations/0002_column_order.sql"),
+    include_str!("../../sql/migrations/0003_task_history.sql"),
+    include_str!("../../sql/migrations/0004_column_roles.sql"),
+    include_str!("../../sql/migrations/0005_task_search.sql"),
+    include_str!("../../sql/migrations/0006_labels.sql"),
+    include_str!("../../sql/migrations/0007_priority.sql"),
+    include_str!("../../sql/migrations/0008_due_date.sql"),
+    include_str!("../../sql/migrations/0009_checklist.sql"),
+    include_str!("../../sql/migrations/0010_task_links.sql"),
+    include_str!("../../sql/migrations/0011_boards.sql"),
+    include_str!("../../sql/migrations/0012_archive.sql"),
+    include_str!("../../sql/migrations/0013_trash.sql"),
+    include_str!("../../sql/migrations/0014_wip_limit.sql"),
+];
+
+/// The schema version this binary knows how to work with.
+#[must_use]
+pub fn latest_version() -> usize {
+    MIGRATIONS.len()
+}
+
+/// Brings the database up to [`latest_version`] by applying the
+/// missing migration steps inside a single transaction. Also turns on
+/// foreign key enforcement for the connection, since `SQLite` has it
+/// off by default.
+///
+/// # Errors
+///
+/// Returns an error if the database schema is newer than the
+/// binary, or if something is wrong with the SQL, in which case none
+/// of the steps are applied.
+pub fn run(conn: &mut Connection) -> Result<(), Error> {
+    // This is a no-op inside a transaction, so do it first
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+
+    let tx = conn.transaction()?;
+    let mut version = current_version(&tx)?;
+
+    if version > latest_version() {
+        bail!(
+            "The database schema is at version {version} but this kanban-tui only \
+             supports up to version {}, please upgrade kanban-tui",
+            latest_version()
+        );
+    }
+
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        tx.execute_batch(migration)?;
+        version = i + 1;
+    }
+
+    tx.pragma_update(None, "user_version", version)?;
+    tx.commit()?;
+
+    Ok(())
+}
+
+/// Reads `user_version`, taking into account the databases created
+/// before versioned migrations existed. Those always have a version
+/// of 0, so we look at the schema to find out which steps they have.
+fn current_version(tx: &Transaction<'_>) -> Result<usize, Error> {
+    let version: usize = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version != 0 || !has_table(tx, "kb_column")? {
+        return Ok(version);
+    }
+    if has_column(tx, "kb_column", "sort_order")? {
+        Ok(2)
+    } else {
+        Ok(1)
+    }
+}
+
+fn has_table(tx: &Transaction<'_>, table: &str) -> Result<bool, Error> {
+    let count: i64 = tx.query_row(
+        "select count(*) from sqlite_master where type = 'table' and name = ?1",
+        [table],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn has_column(tx: &Transaction<'_>, table: &str, column: &str) -> Result<bool, Error> {
+    let count: i64 = tx.query_row(
+        "select count(*) from pragma_table_info(?1) where name = ?2",
+        [table, column],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
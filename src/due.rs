@@ -0,0 +1,114 @@
+//! Due dates, from what the user types to how close they are.
+
+use anyhow::{anyhow, Error};
+use chrono::{Datelike, Days, Local, Months, NaiveDate, Weekday};
+
+/// The format due dates are stored and shown in.
+pub const DUE_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// How a due date compares to today.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DueStatus {
+    /// The date has passed
+    Overdue,
+    /// Due by the end of the day
+    Today,
+    /// Still some time left
+    Upcoming,
+}
+
+impl DueStatus {
+    /// Where `due` stands compared to `today`.
+    #[must_use]
+    pub fn of(due: NaiveDate, today: NaiveDate) -> Self {
+        match due.cmp(&today) {
+            std::cmp::Ordering::Less => DueStatus::Overdue,
+            std::cmp::Ordering::Equal => DueStatus::Today,
+            std::cmp::Ordering::Greater => DueStatus::Upcoming,
+        }
+    }
+}
+
+/// Today in the local timezone, which relative dates are based on.
+#[must_use]
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Adds a signed amount of a unit to `date`, the units being `d`ays,
+/// `w`eeks, `m`onths and `y`ears.
+fn add_offset(date: NaiveDate, amount: i64, unit: &str) -> Option<NaiveDate> {
+    let magnitude = amount.unsigned_abs();
+    let (days, months) = match unit {
+        "d" => (magnitude, 0),
+        "w" => (magnitude.checked_mul(7)?, 0),
+        "m" => (0, u32::try_from(magnitude).ok()?),
+        "y" => (0, u32::try_from(magnitude).ok()?.checked_mul(12)?),
+        _ => return None,
+    };
+    if amount < 0 {
+        date.checked_sub_days(Days::new(days))?
+            .checked_sub_months(Months::new(months))
+    } else {
+        date.checked_add_days(Days::new(days))?
+            .checked_add_months(Months::new(months))
+    }
+}
+
+/// Parses a due date typed by the user. Besides `YYYY-MM-DD` it
+/// understands `today`, `tomorrow`, `yesterday`, offsets like `+3d`,
+/// `+2w`, `+1m` or `-1y`, and weekday names like `fri` or `monday`
+/// which mean the next one after `today`. Returns `None` for an empty
+/// input, which means no due date.
+///
+/// # Errors
+///
+/// Returns an error if the input isn't any of the above.
+pub fn parse_due_date(input: &str, today: NaiveDate) -> Result<Option<NaiveDate>, Error> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    let date = match input.as_str() {
+        "today" | "tod" => Some(today),
+        "tomorrow" | "tom" => today.succ_opt(),
+        "yesterday" => today.pred_opt(),
+        _ => {
+            if let Ok(date) = NaiveDate::parse_from_str(&input, DUE_DATE_FORMAT) {
+                Some(date)
+            } else if let Ok(weekday) = input.parse::<Weekday>() {
+                let ahead = (weekday.num_days_from_monday() + 7
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                let ahead = if ahead == 0 { 7 } else { ahead };
+                today.checked_add_days(Days::new(ahead.into()))
+            } else {
+                let unit_start = input.len() - input.chars().last().map_or(0, char::len_utf8);
+                let (amount, unit) = input.split_at(unit_start);
+                amount
+                    .strip_prefix('+')
+                    .unwrap_or(amount)
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(|amount| add_offset(today, amount, unit))
+            }
+        }
+    };
+    date.map(Some).ok_or_else(|| {
+        anyhow!("Can't read \"{input}\" as a date, try YYYY-MM-DD, tomorrow, +3d, +2w or fri")
+    })
+}
+
+/// Describes a due date relative to `today`, like `in 3 days` or
+/// `2 days overdue`.
+#[must_use]
+pub fn describe_due_date(due: NaiveDate, today: NaiveDate) -> String {
+    let days = (due - today).num_days();
+    match days {
+        0 => String::from("today"),
+        1 => String::from("tomorrow"),
+        -1 => String::from("1 day overdue"),
+        d if d < 0 => format!("{} days overdue", -d),
+        d => format!("in {d} days"),
+    }
+}
@@ -0,0 +1,100 @@
+//! Editing a task in the user's own editor, with the title on the
+//! first line and the description below it.
+
+use anyhow::{bail, Context, Error};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Writes a task the way it's shown in the editor, the title on the
+/// first line, then an empty line and the description.
+#[must_use]
+pub fn format_task_for_editor(title: &str, description: &str) -> String {
+    if description.is_empty() {
+        format!("{title}\n")
+    } else {
+        format!("{title}\n\n{description}\n")
+    }
+}
+
+/// Reads back a task written by [`format_task_for_editor`]. The empty
+/// line after the title is optional and trailing blank lines are
+/// dropped.
+///
+/// # Errors
+///
+/// Returns an error if the title is empty.
+pub fn parse_task_from_editor(text: &str) -> Result<(String, String), Error> {
+    let mut lines = text.lines();
+    let title = lines.next().unwrap_or_default().trim().to_string();
+    if title.is_empty() {
+        bail!("The title can't be empty, nothing was changed");
+    }
+    let mut lines = lines.peekable();
+    if lines.peek().is_some_and(|l| l.trim().is_empty()) {
+        lines.next();
+    }
+    let description = lines.collect::<Vec<_>>().join("\n").trim_end().to_string();
+    Ok((title, description))
+}
+
+/// The editor to use, `$VISUAL` or `$EDITOR`, falling back to `vi`.
+#[must_use]
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| String::from("vi"))
+}
+
+/// Writes `text` to a new file in the temp dir that only the user can
+/// read. The file must not exist yet, so a file or symlink planted
+/// under the same name makes this fail instead of being written to.
+fn create_temp_file(text: &str) -> Result<PathBuf, Error> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let path = std::env::temp_dir().join(format!("kanban-tui-{}-{nanos}.md", std::process::id()));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(&path)
+        .with_context(|| format!("Couldn't create {}", path.display()))?;
+    if let Err(e) = file.write_all(text.as_bytes()) {
+        let _ = std::fs::remove_file(&path);
+        return Err(Error::new(e).context(format!("Couldn't write {}", path.display())));
+    }
+    Ok(path)
+}
+
+/// Opens `text` in `editor` through a temporary file and returns what
+/// the file holds once the editor exits. `editor` can have arguments,
+/// like `code --wait`.
+///
+/// # Errors
+///
+/// Returns an error if the temporary file can't be written or read,
+/// or if the editor can't be started or exits with an error.
+pub fn run_editor(editor: &str, text: &str) -> Result<String, Error> {
+    let path = create_temp_file(text)?;
+
+    let mut args = editor.split_whitespace();
+    let program = args.next().unwrap_or("vi");
+    let status = Command::new(program).args(args).arg(&path).status();
+    let result = match status {
+        Ok(status) if status.success() => std::fs::read_to_string(&path)
+            .with_context(|| format!("Couldn't read {}", path.display())),
+        Ok(status) => Err(Error::msg(format!(
+            "{editor} exited with {status}, nothing was changed"
+        ))),
+        Err(e) => Err(Error::new(e).context(format!("Couldn't start {editor}"))),
+    };
+    // Failing to clean up the temp dir isn't worth an error
+    let _ = std::fs::remove_file(&path);
+    result
+}
@@ -0,0 +1,411 @@
+//! Exporting the board to other formats and importing it back.
+
+use anyhow::{bail, Error};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use crate::app::{ColumnRole, Label, Priority, Task};
+use crate::db::DBConn;
+use crate::due::DUE_DATE_FORMAT;
+
+/// Version of the JSON export format, bumped on incompatible changes.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// The formats the board can be exported to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Lossless format that can be imported back
+    Json,
+    /// A heading per column and a checkbox per task
+    Markdown,
+    /// A heading per column and a TODO heading per task
+    Org,
+}
+
+impl ExportFormat {
+    /// File extension used when exporting to a file.
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Org => "org",
+        }
+    }
+}
+
+/// A [`Column`][`crate::Column`] as written to a JSON export.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnExport {
+    /// Name of the column
+    pub name: String,
+    /// What the column means in the workflow
+    pub role: ColumnRole,
+    /// Index of the last selected [`Task`]
+    #[serde(default)]
+    pub selected_task: usize,
+    /// The tasks of the column, in order
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    /// See [`Column::sort_by_priority`][`crate::Column::sort_by_priority`]
+    #[serde(default)]
+    pub sort_by_priority: bool,
+    /// See [`Column::wip_limit`][`crate::Column::wip_limit`]
+    #[serde(default)]
+    pub wip_limit: Option<usize>,
+}
+
+/// A whole [`Board`][`crate::Board`] as written to a JSON export.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoardExport {
+    /// See [`EXPORT_VERSION`]
+    pub version: u32,
+    /// The columns, in order
+    pub columns: Vec<ColumnExport>,
+    /// Every [`Label`], the tasks refer to them by name
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    /// The saved state of the board, only `selected_column` for now
+    #[serde(default)]
+    pub app_state: BTreeMap<String, String>,
+}
+
+/// How an import treats the board that is already in the database.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Appends the tasks to the columns with the same name, creating
+    /// the missing columns, and keeps the selected column
+    Merge,
+    /// Deletes every column and task of the board first
+    Replace,
+}
+
+/// Reads a whole [`Board`][`crate::Board`] from the database.
+///
+/// # Errors
+///
+/// Returns an error if something is wrong with the SQL.
+pub fn export_board(db_conn: &DBConn, board_id: i64) -> Result<BoardExport, Error> {
+    let board = db_conn.get_board(board_id)?;
+    let archived = db_conn.get_archived_tasks(board_id)?;
+    let columns = db_conn
+        .get_all_columns(board_id)?
+        .into_iter()
+        .map(|column| {
+            // Archived tasks follow the ones on the board, oldest first
+            let mut tasks = column.tasks;
+            tasks.extend(
+                archived
+                    .iter()
+                    .rev()
+                    .filter(|a| a.column_id == column.id)
+                    .map(|a| a.task.clone()),
+            );
+            ColumnExport {
+                name: column.name,
+                role: column.role,
+                selected_task: column.selected_task_idx,
+                tasks,
+                sort_by_priority: column.sort_by_priority,
+                wip_limit: column.wip_limit,
+            }
+        })
+        .collect();
+    Ok(BoardExport {
+        version: EXPORT_VERSION,
+        columns,
+        labels: db_conn.get_all_labels()?,
+        app_state: BTreeMap::from([(
+            String::from("selected_column"),
+            board.selected_column_idx.to_string(),
+        )]),
+    })
+}
+
+/// Renders a whole [`Board`][`crate::Board`] in the given
+/// [`ExportFormat`].
+///
+/// # Errors
+///
+/// Returns an error if something is wrong with the SQL.
+pub fn export_to_string(
+    db_conn: &DBConn,
+    board_id: i64,
+    format: ExportFormat,
+) -> Result<String, Error> {
+    let board = export_board(db_conn, board_id)?;
+    match format {
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(&board)?),
+        ExportFormat::Markdown => Ok(render_markdown(&board)),
+        ExportFormat::Org => Ok(render_org(&board)),
+    }
+}
+
+/// Writes `text` with every line indented by `indent`, leaving blank
+/// lines empty so they don't carry trailing whitespace.
+fn push_indented(out: &mut String, text: &str, indent: &str) {
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            out.push('\n');
+        } else {
+            let _ = writeln!(out, "{indent}{line}");
+        }
+    }
+}
+
+/// Renders the board as Markdown, one `##` heading per column and a
+/// checkbox list item per task, with its priority in bold before the
+/// title and its due date and labels after it. Tasks in
+/// [`ColumnRole::Done`] columns are checked, checklists and
+/// descriptions are nested under their task. Archived tasks are left
+/// out.
+#[must_use]
+pub fn render_markdown(board: &BoardExport) -> String {
+    let mut out = String::new();
+    for (i, column) in board.columns.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let _ = writeln!(out, "## {}\n", column.name);
+        let mut tasks = column
+            .tasks
+            .iter()
+            .filter(|t| t.archived_at.is_none())
+            .peekable();
+        if tasks.peek().is_none() {
+            out.push_str("_No tasks_\n");
+        }
+        let check = if column.role == ColumnRole::Done {
+            'x'
+        } else {
+            ' '
+        };
+        for task in tasks {
+            let _ = write!(out, "- [{check}] ");
+            if let Some(priority) = task.priority {
+                let _ = write!(out, "**{}** ", priority.as_str());
+            }
+            out.push_str(&task.title);
+            if let Some(due_date) = task.due_date {
+                let _ = write!(out, " (due {})", due_date.format(DUE_DATE_FORMAT));
+            }
+            for label in &task.labels {
+                let _ = write!(out, " `{label}`");
+            }
+            out.push('\n');
+            for item in &task.checklist {
+                let check = if item.done { 'x' } else { ' ' };
+                let _ = writeln!(out, "  - [{check}] {}", item.text);
+            }
+            push_indented(&mut out, &task.description, "  ");
+        }
+    }
+    out
+}
+
+/// The Org-mode TODO keyword used for the tasks of a column.
+fn org_keyword(role: ColumnRole) -> &'static str {
+    match role {
+        ColumnRole::Backlog => "TODO",
+        ColumnRole::Active => "DOING",
+        ColumnRole::Parked => "SOMEDAY",
+        ColumnRole::Done => "DONE",
+    }
+}
+
+/// The Org-mode priority cookie of a [`Priority`], `[#A]` being the
+/// most urgent.
+fn org_priority(priority: Priority) -> &'static str {
+    match priority {
+        Priority::P0 => "[#A]",
+        Priority::P1 => "[#B]",
+        Priority::P2 => "[#C]",
+        Priority::P3 => "[#D]",
+    }
+}
+
+/// Org-mode tags can only hold letters, numbers, `_`, `@`, `#` and
+/// `%`, so everything else becomes `_`.
+fn org_tags(labels: &[String]) -> Vec<String> {
+    labels
+        .iter()
+        .map(|label| {
+            label
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || "_@#%".contains(c) {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Renders the board as an Org-mode file, one top level heading per
+/// column and a second level heading per task. The TODO keyword of a
+/// task comes from the role of its column, its priority becomes a
+/// priority cookie, its labels become tags, its due date becomes a
+/// `DEADLINE` and completed tasks get a `CLOSED` timestamp. Checklists
+/// become checkbox lists with a statistics cookie in the heading.
+/// Archived tasks are left out.
+#[must_use]
+pub fn render_org(board: &BoardExport) -> String {
+    let mut out = String::from("#+TODO: TODO DOING SOMEDAY | DONE\n#+PRIORITIES: A D D\n");
+    for column in &board.columns {
+        let _ = writeln!(out, "\n* {}", column.name);
+        let keyword = org_keyword(column.role);
+        for task in column.tasks.iter().filter(|t| t.archived_at.is_none()) {
+            let _ = write!(out, "** {keyword} ");
+            if let Some(priority) = task.priority {
+                let _ = write!(out, "{} ", org_priority(priority));
+            }
+            out.push_str(&task.title);
+            let (done, total) = task.checklist_progress();
+            if total > 0 {
+                let _ = write!(out, " [{done}/{total}]");
+            }
+            if !task.labels.is_empty() {
+                let _ = write!(out, " :{}:", org_tags(&task.labels).join(":"));
+            }
+            out.push('\n');
+            let mut planning = Vec::new();
+            if let Some(closed) = task
+                .completed_at
+                .filter(|_| column.role == ColumnRole::Done)
+                .and_then(|t| Local.timestamp_opt(t, 0).single())
+            {
+                planning.push(format!("CLOSED: [{}]", closed.format("%Y-%m-%d %a %H:%M")));
+            }
+            if let Some(due_date) = task.due_date {
+                planning.push(format!("DEADLINE: <{}>", due_date.format("%Y-%m-%d %a")));
+            }
+            if !planning.is_empty() {
+                let _ = writeln!(out, "   {}", planning.join(" "));
+            }
+            for item in &task.checklist {
+                let check = if item.done { 'X' } else { ' ' };
+                let _ = writeln!(out, "   - [{check}] {}", item.text);
+            }
+            // Indenting keeps lines starting with `*` from becoming headings
+            push_indented(&mut out, &task.description, "   ");
+        }
+    }
+    out
+}
+
+/// Writes a [`BoardExport`] into a [`Board`][`crate::Board`] of the
+/// database inside a single transaction. Task IDs are not kept, the
+/// DB provides new ones, and the links between the imported tasks
+/// follow them. The IDs in the file only serve to resolve those
+/// links, so they may be left out when there are none.
+/// Returns how many columns were created and how many tasks were
+/// imported.
+///
+/// # Errors
+///
+/// Returns an error if the export is from a newer version, if a task
+/// is blocked by an ID that several tasks of the file share, or if
+/// something is wrong with the SQL, in which case nothing is changed.
+pub fn import_board(
+    db_conn: &DBConn,
+    board_id: i64,
+    board: &BoardExport,
+    mode: ImportMode,
+) -> Result<(usize, usize), Error> {
+    if board.version > EXPORT_VERSION {
+        bail!(
+            "The export is at version {} but this kanban-tui only supports up to version {}",
+            board.version,
+            EXPORT_VERSION
+        );
+    }
+
+    // An unchecked transaction borrows the connection immutably, so
+    // the regular DBConn methods keep working inside of it
+    let tx = db_conn.unchecked_transaction()?;
+
+    if mode == ImportMode::Replace {
+        db_conn.clear_board(board_id)?;
+    }
+
+    // Create the labels first so they keep their colors, the ones
+    // that already exist keep theirs
+    let existing_labels = db_conn.get_all_labels()?;
+    for label in &board.labels {
+        if !existing_labels
+            .iter()
+            .any(|l| l.name.eq_ignore_ascii_case(&label.name))
+        {
+            db_conn.create_label(&label.name, label.color)?;
+        }
+    }
+
+    let mut existing = db_conn.get_all_columns(board_id)?;
+    let (mut columns_created, mut tasks_imported) = (0, 0);
+    // The new ID of every task in file order, and the new ID for each
+    // ID of the file, `None` when several tasks share it
+    let mut imported = Vec::new();
+    let mut new_ids: HashMap<i64, Option<i64>> = HashMap::new();
+    for column in &board.columns {
+        let column_id = if let Some(c) = existing
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(&column.name))
+        {
+            c.id
+        } else {
+            let created = db_conn.create_column(board_id, column.name.clone(), column.role)?;
+            db_conn.set_selected_task_for_column(column.selected_task, created.id)?;
+            db_conn.set_column_sort_by_priority(created.id, column.sort_by_priority)?;
+            db_conn.set_column_wip_limit(created.id, column.wip_limit)?;
+            columns_created += 1;
+            let id = created.id;
+            existing.push(created);
+            id
+        };
+        for task in &column.tasks {
+            let new_id = db_conn.import_task(task, column_id)?;
+            new_ids
+                .entry(task.id)
+                .and_modify(|id| *id = None)
+                .or_insert(Some(new_id));
+            imported.push((task, new_id));
+            tasks_imported += 1;
+        }
+    }
+    for (task, new_id) in imported {
+        if task.blocked_by.is_empty() {
+            continue;
+        }
+        let mut blockers = Vec::new();
+        for id in &task.blocked_by {
+            match new_ids.get(id) {
+                Some(Some(blocker)) => blockers.push(*blocker),
+                Some(None) => bail!(
+                    "Task \"{}\" is blocked by the ID {}, which several tasks share",
+                    task.title,
+                    id
+                ),
+                None => {}
+            }
+        }
+        db_conn.set_task_blockers(new_id, &blockers)?;
+    }
+
+    if mode == ImportMode::Replace {
+        let selected_column = board
+            .app_state
+            .get("selected_column")
+            .and_then(|idx| idx.parse().ok())
+            .unwrap_or_default();
+        db_conn.set_selected_column(board_id, selected_column)?;
+    }
+
+    tx.commit()?;
+
+    Ok((columns_created, tasks_imported))
+}
@@ -1,50 +1,159 @@
-use crate::app::{State, TaskEditFocus, TaskState, EDIT_WINDOW_FOCUS_STATES};
+use crate::app::{
+    parse_labels, AgendaState, BoardPickerMode, BoardPickerState, ChecklistItem, ColumnEditMode,
+    ColumnEditState, HelpState, LabelFilterState, LinkState, OffBoardKind, Priority, State,
+    TaskEditFocus, TaskState, EDIT_WINDOW_FOCUS_STATES,
+};
+use crate::due::{parse_due_date, today};
+use crate::export::ExportFormat;
+use crate::keymap::{Action, KeyContext};
+use crate::mouse::Button;
 use anyhow::Error;
 use crossterm::event;
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::{Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use int_enum::IntEnum;
+use tui_textarea::TextArea;
 
 pub fn cycle_focus(task: &mut TaskState<'_>, forward: bool) -> Result<(), Error> {
     let cycle = if forward {
-        (task.focus.int_value() + 1) % EDIT_WINDOW_FOCUS_STATES
+        (task.focus.int_value() + 1).rem_euclid(EDIT_WINDOW_FOCUS_STATES)
     } else {
-        (task.focus.int_value() - 1) % EDIT_WINDOW_FOCUS_STATES
+        (task.focus.int_value() - 1).rem_euclid(EDIT_WINDOW_FOCUS_STATES)
     };
     task.focus = TaskEditFocus::from_int(cycle)?;
     Ok(())
 }
 
+/// Keys of the checklist of the task form while an item is typed.
+fn handle_checklist_input(task: &mut TaskState<'_>, key: event::KeyEvent) {
+    let Some(input) = &mut task.checklist_input else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => task.checklist_input = None,
+        KeyCode::Enter => {
+            let text = input.lines().join(" ").trim().to_string();
+            if !text.is_empty() {
+                if task.is_checklist_edit {
+                    if let Some(item) = task.checklist.get_mut(task.checklist_idx) {
+                        item.text = text;
+                    }
+                } else {
+                    task.checklist.push(ChecklistItem { text, done: false });
+                    task.checklist_idx = task.checklist.len() - 1;
+                }
+            }
+            task.checklist_input = None;
+        }
+        _ => {
+            input.input(key);
+        }
+    }
+}
+
+/// Keys of the checklist of the task form: adding, editing, toggling,
+/// reordering and deleting items.
+fn handle_checklist(task: &mut TaskState<'_>, key: event::KeyEvent) {
+    let idx = task.checklist_idx;
+    let last_idx = task.checklist.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => task.checklist_idx = std::cmp::min(idx + 1, last_idx),
+        KeyCode::Char('k') | KeyCode::Up => task.checklist_idx = idx.saturating_sub(1),
+        KeyCode::Char('a' | 'n') => {
+            task.checklist_input = Some(TextArea::default());
+            task.is_checklist_edit = false;
+        }
+        KeyCode::Char('e') if idx < task.checklist.len() => {
+            task.checklist_input = Some(TextArea::from([task.checklist[idx].text.clone()]));
+            task.is_checklist_edit = true;
+        }
+        KeyCode::Char(' ') | KeyCode::Enter => {
+            if let Some(item) = task.checklist.get_mut(idx) {
+                item.done = !item.done;
+            }
+        }
+        KeyCode::Char('J') if idx < last_idx => {
+            task.checklist.swap(idx, idx + 1);
+            task.checklist_idx += 1;
+        }
+        KeyCode::Char('K') if idx > 0 && idx < task.checklist.len() => {
+            task.checklist.swap(idx, idx - 1);
+            task.checklist_idx -= 1;
+        }
+        KeyCode::Char('d' | 'D') | KeyCode::Delete if idx < task.checklist.len() => {
+            task.checklist.remove(idx);
+            task.checklist_idx = std::cmp::min(idx, task.checklist.len().saturating_sub(1));
+        }
+        _ => {}
+    }
+}
+
 pub fn handle_task_edit(state: &mut State<'_>, key: event::KeyEvent) -> Result<(), Error> {
     // .take() the option so we can avoid borrow checker issues when
     // we try to edit the task since that mutably borrows State, then
     // assign later to task_edit_state
     let updated_task = if let Some(mut task) = state.task_edit_state.take() {
         match (key.code, task.focus) {
-            (KeyCode::Tab, _) => {
+            // Typing an item takes every key, Tab included
+            (_, TaskEditFocus::Checklist) if task.checklist_input.is_some() => {
+                handle_checklist_input(&mut task, key);
+                Some(task)
+            }
+            _ if state.keymap.action(KeyContext::TaskEdit, key) == Some(Action::OpenEditor) => {
+                state.open_editor = true;
+                Some(task)
+            }
+            _ if state.keymap.action(KeyContext::TaskEdit, key) == Some(Action::NextField) => {
                 cycle_focus(&mut task, true)?;
                 Some(task)
             }
-            (KeyCode::BackTab, _) => {
+            _ if state.keymap.action(KeyContext::TaskEdit, key) == Some(Action::PreviousField) => {
                 cycle_focus(&mut task, false)?;
                 Some(task)
             }
+            // Keep the form open if the column can't take the task
+            (KeyCode::Enter, TaskEditFocus::ConfirmBtn)
+                if !task.is_edit && !state.check_wip_limit(state.selected_column_idx) =>
+            {
+                Some(task)
+            }
             (KeyCode::Enter, TaskEditFocus::ConfirmBtn) => {
+                let due_date = match parse_due_date(&task.due_date.lines().join(""), today()) {
+                    Ok(due_date) => due_date,
+                    Err(e) => {
+                        // Keep the form open so the date can be fixed
+                        state.status_message = Some(e.to_string());
+                        task.focus = TaskEditFocus::DueDate;
+                        state.task_edit_state = Some(task);
+                        return Ok(());
+                    }
+                };
                 // The structure of this function is so we avoid an
                 // unncessary clone() here. We can just transfer
                 // ownership of these strings right into the task
                 // that's going to be created/updated
                 let title = task.title.into_lines().join("\n");
                 let description = task.description.into_lines().join("\n");
+                let labels = parse_labels(&task.labels.into_lines().join(","));
+                // A single undo reverts everything the form changed
+                state.history.start_batch();
                 if task.is_edit {
                     state.edit_task(title, description)?;
                 } else {
                     state.add_new_task(title, description)?;
                 }
+                state.set_selected_task_labels(&labels)?;
+                state.set_selected_task_priority(task.priority)?;
+                state.set_selected_task_due_date(due_date)?;
+                state.set_selected_task_checklist(&task.checklist)?;
+                state.history.finish_batch();
                 None
             }
             (KeyCode::Enter, TaskEditFocus::CancelBtn) => None,
             // Ignore enter on the title bar to effectively make it single line
-            (KeyCode::Enter, TaskEditFocus::Title) => Some(task),
+            (
+                KeyCode::Enter,
+                TaskEditFocus::Title | TaskEditFocus::Labels | TaskEditFocus::DueDate,
+            ) => Some(task),
             (_, TaskEditFocus::Title) => {
                 task.title.input(key);
                 Some(task)
@@ -53,6 +162,34 @@ pub fn handle_task_edit(state: &mut State<'_>, key: event::KeyEvent) -> Result<(
                 task.description.input(key);
                 Some(task)
             }
+            (_, TaskEditFocus::Labels) => {
+                task.labels.input(key);
+                Some(task)
+            }
+            (_, TaskEditFocus::DueDate) => {
+                task.due_date.input(key);
+                Some(task)
+            }
+            (_, TaskEditFocus::Checklist) => {
+                handle_checklist(&mut task, key);
+                Some(task)
+            }
+            (KeyCode::Left | KeyCode::Char('h'), TaskEditFocus::Priority) => {
+                task.priority = Priority::cycle(task.priority, false);
+                Some(task)
+            }
+            (KeyCode::Right | KeyCode::Char('l'), TaskEditFocus::Priority) => {
+                task.priority = Priority::cycle(task.priority, true);
+                Some(task)
+            }
+            (KeyCode::Char(c @ '0'..='3'), TaskEditFocus::Priority) => {
+                task.priority = c.to_digit(10).and_then(|l| Priority::from_level(l.into()));
+                Some(task)
+            }
+            (KeyCode::Backspace | KeyCode::Delete, TaskEditFocus::Priority) => {
+                task.priority = None;
+                Some(task)
+            }
             _ => Some(task),
         }
     } else {
@@ -62,41 +199,558 @@ pub fn handle_task_edit(state: &mut State<'_>, key: event::KeyEvent) -> Result<(
     Ok(())
 }
 
+/// Reads the WIP limit typed in the column form, empty or 0 meaning
+/// no limit.
+fn parse_wip_limit(text: &str) -> Result<Option<usize>, String> {
+    match text.trim() {
+        "" => Ok(None),
+        limit => limit
+            .parse()
+            .map(|l: usize| Some(l).filter(|l| *l > 0))
+            .map_err(|_| format!("\"{limit}\" isn't a number of tasks")),
+    }
+}
+
+/// Answers the question asked before deleting a column. Returns
+/// `false` if the key isn't an answer, those are ignored until the
+/// user makes a choice.
+fn handle_column_delete(state: &mut State<'_>, code: KeyCode) -> Result<bool, Error> {
+    let has_tasks = !state.get_selected_column().tasks.is_empty();
+    match code {
+        KeyCode::Char('m') if has_tasks => state.delete_column(true)?,
+        KeyCode::Char('d') if has_tasks => state.delete_column(false)?,
+        KeyCode::Char('y') | KeyCode::Enter if !has_tasks => state.delete_column(false)?,
+        KeyCode::Char('n') | KeyCode::Esc => (),
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+pub fn handle_column_edit(state: &mut State<'_>, key: event::KeyEvent) -> Result<(), Error> {
+    // Same .take() trick as in handle_task_edit
+    let updated_column = if let Some(mut column) = state.column_edit_state.take() {
+        match (column.mode, key.code) {
+            (ColumnEditMode::Browse, KeyCode::Char('q') | KeyCode::Esc) => None,
+            (ColumnEditMode::Browse, KeyCode::Char('h') | KeyCode::Left) => {
+                state.select_column_left()?;
+                Some(column)
+            }
+            (ColumnEditMode::Browse, KeyCode::Char('l') | KeyCode::Right) => {
+                state.select_column_right()?;
+                Some(column)
+            }
+            (ColumnEditMode::Browse, KeyCode::Char('H')) => {
+                state.move_column_left()?;
+                Some(column)
+            }
+            (ColumnEditMode::Browse, KeyCode::Char('L')) => {
+                state.move_column_right()?;
+                Some(column)
+            }
+            (ColumnEditMode::Browse, KeyCode::Char('n')) => {
+                column.mode = ColumnEditMode::Create;
+                column.name = TextArea::default();
+                Some(column)
+            }
+            (ColumnEditMode::Browse, KeyCode::Char('r')) => {
+                column.mode = ColumnEditMode::Rename;
+                column.name = TextArea::from(state.get_selected_column().name.lines());
+                Some(column)
+            }
+            (ColumnEditMode::Browse, KeyCode::Char('w')) => {
+                column.mode = ColumnEditMode::WipLimit;
+                let limit = state.get_selected_column().wip_limit;
+                column.name = TextArea::from(limit.map(|l| l.to_string()));
+                Some(column)
+            }
+            (ColumnEditMode::Browse, KeyCode::Char('t')) => {
+                state.cycle_column_role()?;
+                Some(column)
+            }
+            (ColumnEditMode::Browse, KeyCode::Char('D')) => {
+                if state.get_column_delete_target().is_some() {
+                    column.mode = ColumnEditMode::ConfirmDelete;
+                }
+                Some(column)
+            }
+            (ColumnEditMode::Create | ColumnEditMode::Rename, KeyCode::Enter) => {
+                let name = column.name.lines().join(" ").trim().to_string();
+                if !name.is_empty() {
+                    if column.mode == ColumnEditMode::Create {
+                        state.add_column(name)?;
+                    } else {
+                        state.rename_column(name)?;
+                    }
+                }
+                column.mode = ColumnEditMode::Browse;
+                Some(column)
+            }
+            (ColumnEditMode::WipLimit, KeyCode::Enter) => {
+                match parse_wip_limit(&column.name.lines().join("")) {
+                    Ok(limit) => {
+                        state.set_column_wip_limit(limit)?;
+                        column.mode = ColumnEditMode::Browse;
+                    }
+                    Err(message) => state.status_message = Some(message),
+                }
+                Some(column)
+            }
+            (
+                ColumnEditMode::Create | ColumnEditMode::Rename | ColumnEditMode::WipLimit,
+                KeyCode::Esc,
+            ) => {
+                column.mode = ColumnEditMode::Browse;
+                Some(column)
+            }
+            (ColumnEditMode::Create | ColumnEditMode::Rename | ColumnEditMode::WipLimit, _) => {
+                column.name.input(key);
+                Some(column)
+            }
+            (ColumnEditMode::ConfirmDelete, code) => {
+                if handle_column_delete(state, code)? {
+                    column.mode = ColumnEditMode::Browse;
+                }
+                Some(column)
+            }
+            (ColumnEditMode::Browse, _) => Some(column),
+        }
+    } else {
+        None
+    };
+    state.column_edit_state = updated_column;
+    Ok(())
+}
+
+pub fn handle_label_filter(state: &mut State<'_>, key: event::KeyEvent) -> Result<(), Error> {
+    // Same .take() trick as in handle_task_edit
+    let updated_filter = if let Some(mut filter) = state.label_filter_state.take() {
+        match key.code {
+            KeyCode::Char('q' | 'f') | KeyCode::Esc => None,
+            KeyCode::Char('j') | KeyCode::Down => {
+                filter.selected_idx = std::cmp::min(
+                    filter.selected_idx + 1,
+                    state.labels.len().saturating_sub(1),
+                );
+                Some(filter)
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                filter.selected_idx = filter.selected_idx.saturating_sub(1);
+                Some(filter)
+            }
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                state.toggle_label_filter(filter.selected_idx)?;
+                Some(filter)
+            }
+            KeyCode::Char('x') => {
+                state.clear_label_filter();
+                Some(filter)
+            }
+            KeyCode::Char('c') => {
+                state.cycle_label_color(filter.selected_idx)?;
+                Some(filter)
+            }
+            KeyCode::Char('D') => {
+                state.ask_delete_label(filter.selected_idx);
+                Some(filter)
+            }
+            _ => Some(filter),
+        }
+    } else {
+        None
+    };
+    state.label_filter_state = updated_filter;
+    Ok(())
+}
+
+pub fn handle_search(state: &mut State<'_>, key: event::KeyEvent) -> Result<(), Error> {
+    let Some(search) = &mut state.search else {
+        return Ok(());
+    };
+    match key.code {
+        KeyCode::Esc => state.clear_search(),
+        KeyCode::Enter => {
+            if search.query.lines().join("").trim().is_empty() {
+                state.clear_search();
+            } else {
+                search.is_typing = false;
+            }
+        }
+        _ => {
+            search.query.input(key);
+            state.update_search()?;
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_agenda(state: &mut State<'_>, key: event::KeyEvent) -> Result<(), Error> {
+    let last_idx = state.get_agenda().len().saturating_sub(1);
+    // Same .take() trick as in handle_task_edit
+    let updated_agenda = if let Some(mut agenda) = state.agenda.take() {
+        match key.code {
+            KeyCode::Char('q' | 'A') | KeyCode::Esc => None,
+            KeyCode::Char('j') | KeyCode::Down => {
+                agenda.selected_idx = std::cmp::min(agenda.selected_idx + 1, last_idx);
+                Some(agenda)
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                agenda.selected_idx = agenda.selected_idx.saturating_sub(1);
+                Some(agenda)
+            }
+            KeyCode::Char('g') => {
+                agenda.selected_idx = 0;
+                Some(agenda)
+            }
+            KeyCode::Char('G') => {
+                agenda.selected_idx = last_idx;
+                Some(agenda)
+            }
+            KeyCode::Enter => {
+                state.open_agenda_entry(agenda.selected_idx)?;
+                None
+            }
+            _ => Some(agenda),
+        }
+    } else {
+        None
+    };
+    state.agenda = updated_agenda;
+    Ok(())
+}
+
+pub fn handle_link(state: &mut State<'_>, key: event::KeyEvent) -> Result<(), Error> {
+    let candidates = state.get_link_candidates();
+    // Same .take() trick as in handle_task_edit
+    let updated_link = if let Some(mut link) = state.link_state.take() {
+        match key.code {
+            KeyCode::Char('q' | 'b') | KeyCode::Esc => None,
+            KeyCode::Char('j') | KeyCode::Down => {
+                link.selected_idx =
+                    std::cmp::min(link.selected_idx + 1, candidates.len().saturating_sub(1));
+                Some(link)
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                link.selected_idx = link.selected_idx.saturating_sub(1);
+                Some(link)
+            }
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                if let Some(&(col_idx, task_idx)) = candidates.get(link.selected_idx) {
+                    let blocker_id = state.columns[col_idx].tasks[task_idx].id;
+                    state.toggle_selected_task_blocker(blocker_id)?;
+                }
+                Some(link)
+            }
+            _ => Some(link),
+        }
+    } else {
+        None
+    };
+    state.link_state = updated_link;
+    Ok(())
+}
+
+pub fn handle_board_picker(state: &mut State<'_>, key: event::KeyEvent) -> Result<(), Error> {
+    // Same .take() trick as in handle_task_edit
+    let updated_picker = if let Some(mut picker) = state.board_picker.take() {
+        match (picker.mode, key.code) {
+            (BoardPickerMode::Browse, KeyCode::Char('q' | 'B') | KeyCode::Esc) => None,
+            (BoardPickerMode::Browse, KeyCode::Char('j') | KeyCode::Down) => {
+                picker.selected_idx = std::cmp::min(
+                    picker.selected_idx + 1,
+                    state.boards.len().saturating_sub(1),
+                );
+                Some(picker)
+            }
+            (BoardPickerMode::Browse, KeyCode::Char('k') | KeyCode::Up) => {
+                picker.selected_idx = picker.selected_idx.saturating_sub(1);
+                Some(picker)
+            }
+            (BoardPickerMode::Browse, KeyCode::Enter) => {
+                state.switch_board(picker.selected_idx)?;
+                None
+            }
+            (BoardPickerMode::Browse, KeyCode::Char('n')) => {
+                picker.mode = BoardPickerMode::Create;
+                picker.name = TextArea::default();
+                Some(picker)
+            }
+            (BoardPickerMode::Browse, KeyCode::Char('r')) => {
+                picker.mode = BoardPickerMode::Rename;
+                picker.name = TextArea::from(state.boards[picker.selected_idx].name.lines());
+                Some(picker)
+            }
+            (BoardPickerMode::Browse, KeyCode::Char('D')) => {
+                state.ask_delete_board(picker.selected_idx);
+                Some(picker)
+            }
+            (BoardPickerMode::Create | BoardPickerMode::Rename, KeyCode::Enter) => {
+                let name = picker.name.lines().join(" ").trim().to_string();
+                if !name.is_empty() {
+                    if picker.mode == BoardPickerMode::Create {
+                        state.add_board(&name)?;
+                        picker.selected_idx = state.selected_board_idx;
+                    } else {
+                        state.rename_board(picker.selected_idx, &name)?;
+                    }
+                }
+                picker.mode = BoardPickerMode::Browse;
+                Some(picker)
+            }
+            (BoardPickerMode::Create | BoardPickerMode::Rename, KeyCode::Esc) => {
+                picker.mode = BoardPickerMode::Browse;
+                Some(picker)
+            }
+            (BoardPickerMode::Create | BoardPickerMode::Rename, _) => {
+                picker.name.input(key);
+                Some(picker)
+            }
+            (BoardPickerMode::Browse, _) => Some(picker),
+        }
+    } else {
+        None
+    };
+    state.board_picker = updated_picker;
+    Ok(())
+}
+
+pub fn handle_archive(state: &mut State<'_>, key: event::KeyEvent) -> Result<(), Error> {
+    let last_idx = state.get_archive_entries().len().saturating_sub(1);
+    let Some(archive) = &mut state.archive else {
+        return Ok(());
+    };
+    match key.code {
+        KeyCode::Esc => state.archive = None,
+        KeyCode::Down => archive.selected_idx = std::cmp::min(archive.selected_idx + 1, last_idx),
+        KeyCode::Up => archive.selected_idx = archive.selected_idx.saturating_sub(1),
+        KeyCode::Enter => {
+            state.restore_off_board_task()?;
+            state.refresh_search_matches()?;
+        }
+        _ => {
+            archive.query.input(key);
+            state.update_archive_search()?;
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_confirm(state: &mut State<'_>, key: event::KeyEvent) -> Result<(), Error> {
+    match key.code {
+        KeyCode::Char('y' | 'Y') | KeyCode::Enter => state.confirm_action(),
+        KeyCode::Char('n' | 'N' | 'q') | KeyCode::Esc => {
+            state.confirm = None;
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
 #[allow(clippy::unit_arg)]
 pub fn handle_main(state: &mut State<'_>, key: event::KeyEvent) -> Result<(), Error> {
+    // While searching, the search bindings win so n/N can jump
+    // between the matches instead of creating a task
+    let action = state
+        .search
+        .as_ref()
+        .and_then(|_| state.keymap.action(KeyContext::Search, key))
+        .or_else(|| state.keymap.action(KeyContext::Main, key));
+    let Some(action) = action else {
+        return Ok(());
+    };
+    match action {
+        Action::Quit => Ok(state.quit = true),
+        Action::SelectColumnLeft => state.select_column_left(),
+        Action::SelectTaskBelow => state.select_task_below(),
+        Action::SelectTaskAbove => state.select_task_above(),
+        Action::SelectColumnRight => state.select_column_right(),
+        Action::SelectFirstTask => state.select_first_task(),
+        Action::SelectLastTask => state.select_last_task(),
+        Action::MoveTaskLeft => state.move_task_column_left(),
+        Action::MoveTaskRight => state.move_task_column_right(),
+        Action::MoveTaskDown => state.move_task_down(),
+        Action::MoveTaskUp => state.move_task_up(),
+        Action::NextMatch => state.select_next_match(true),
+        Action::PreviousMatch => state.select_next_match(false),
+        Action::ClearSearch => Ok(state.clear_search()),
+        Action::Search => Ok(state.start_search()),
+        Action::FilterLabels => Ok(state.label_filter_state = Some(LabelFilterState::default())),
+        Action::NewTask => Ok(state.task_edit_state = Some(TaskState::default())),
+        Action::EditTask => Ok(state.task_edit_state = state.get_task_state_from_current()),
+        Action::EditInEditor => Ok(state.open_editor = state.get_selected_task().is_some()),
+        Action::DeleteTask => Ok(state.ask_delete_task()),
+        Action::ArchiveTask => state.archive_task(),
+        Action::OpenArchive => state.open_archive(OffBoardKind::Archived),
+        Action::OpenTrash => state.open_archive(OffBoardKind::Deleted),
+        Action::ManageColumns => Ok(state.column_edit_state = Some(ColumnEditState::default())),
+        Action::FlowMetrics => state.toggle_flow_metrics(),
+        Action::Agenda => Ok(state.agenda = Some(AgendaState::default())),
+        Action::Boards => {
+            let picker = BoardPickerState {
+                selected_idx: state.selected_board_idx,
+                ..BoardPickerState::default()
+            };
+            Ok(state.board_picker = Some(picker))
+        }
+        Action::ExportMarkdown => Ok(state.export_to_file(ExportFormat::Markdown)),
+        Action::ExportOrg => Ok(state.export_to_file(ExportFormat::Org)),
+        Action::SetPriority0 => state.toggle_selected_task_priority(Priority::P0),
+        Action::SetPriority1 => state.toggle_selected_task_priority(Priority::P1),
+        Action::SetPriority2 => state.toggle_selected_task_priority(Priority::P2),
+        Action::SetPriority3 => state.toggle_selected_task_priority(Priority::P3),
+        Action::SortByPriority => state.toggle_priority_sort(),
+        Action::Blockers if state.get_selected_task().is_some() => {
+            Ok(state.link_state = Some(LinkState::default()))
+        }
+        Action::Undo => state.undo(),
+        Action::Redo => state.redo(),
+        Action::TogglePanels => Ok(state.show_panels = !state.show_panels),
+        Action::ToggleCardView => Ok(state.task_view = state.task_view.toggled()),
+        Action::Help => Ok(state.help = Some(HelpState::default())),
+        Action::Blockers | Action::NextField | Action::PreviousField | Action::OpenEditor => Ok(()),
+    }
+}
+
+/// Keys of the help popup, j/k scroll and anything else closes it.
+fn handle_help(state: &mut State<'_>, key: event::KeyEvent) {
+    let Some(help) = &mut state.help else {
+        return;
+    };
     match key.code {
-        KeyCode::Char('q') => Ok(state.quit = true),
-        KeyCode::Char('h') | KeyCode::Left => state.select_column_left(),
-        KeyCode::Char('j') | KeyCode::Down => state.select_task_below(),
-        KeyCode::Char('k') | KeyCode::Up => state.select_task_above(),
-        KeyCode::Char('l') | KeyCode::Right => state.select_column_right(),
-        KeyCode::Char('g') => state.select_first_task(),
-        KeyCode::Char('G') => state.select_last_task(),
-        KeyCode::Char('H') => state.move_task_column_left(),
-        KeyCode::Char('L') => state.move_task_column_right(),
-        KeyCode::Char('J') => state.move_task_down(),
-        KeyCode::Char('K') => state.move_task_up(),
-        KeyCode::Char('n') => Ok(state.task_edit_state = Some(TaskState::default())),
-        KeyCode::Char('e') => Ok(state.task_edit_state = state.get_task_state_from_current()),
-        KeyCode::Char('D') => state.delete_task(),
-        _ => Ok(()),
+        KeyCode::Char('j') | KeyCode::Down => help.scroll = help.scroll.saturating_add(1),
+        KeyCode::Char('k') | KeyCode::Up => help.scroll = help.scroll.saturating_sub(1),
+        _ => state.help = None,
     }
 }
 
-/// Takes the app's [`State`] and uses [`event::read`] to get the current keypress.
+/// Whether a popup, the column manager or the search bar has the
+/// focus, in which case the board doesn't take clicks.
+fn is_board_covered(state: &State<'_>) -> bool {
+    state.confirm.is_some()
+        || state.task_edit_state.is_some()
+        || state.column_edit_state.is_some()
+        || state.help.is_some()
+        || state.flow_metrics.is_some()
+        || state.label_filter_state.is_some()
+        || state.agenda.is_some()
+        || state.link_state.is_some()
+        || state.board_picker.is_some()
+        || state.archive.is_some()
+        || state.search.as_ref().is_some_and(|s| s.is_typing)
+}
+
+/// Clicking a popup button does what its key does.
+fn click_button(state: &mut State<'_>, button: Button) -> Result<(), Error> {
+    match button {
+        Button::FormConfirm | Button::FormCancel => {
+            if let Some(task) = &mut state.task_edit_state {
+                task.focus = if button == Button::FormConfirm {
+                    TaskEditFocus::ConfirmBtn
+                } else {
+                    TaskEditFocus::CancelBtn
+                };
+                handle_task_edit(state, KeyEvent::from(KeyCode::Enter))?;
+            }
+        }
+        Button::ConfirmYes => handle_confirm(state, KeyEvent::from(KeyCode::Char('y')))?,
+        Button::ConfirmNo => handle_confirm(state, KeyEvent::from(KeyCode::Char('n')))?,
+    }
+    Ok(())
+}
+
+/// Clicking selects the task or column under the pointer and starts
+/// dragging the task, letting go of it somewhere else moves it there.
+/// The wheel goes through the columns. The places things were drawn
+/// come from [`State::click_areas`].
 ///
 /// # Errors
 ///
-/// Most of the applications errors will be bubbled up to this layer,
-/// including all database related ones
+/// SQL related errors get bubbled up to here.
+pub fn handle_mouse(state: &mut State<'_>, mouse: MouseEvent) -> Result<(), Error> {
+    let (x, y) = (mouse.column, mouse.row);
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            state.status_message = None;
+            state.dragged_task = None;
+            // Only the confirmation's answers work while it's open
+            let button = state.click_areas.button_at(x, y).filter(|b| {
+                state.confirm.is_none() || matches!(b, Button::ConfirmYes | Button::ConfirmNo)
+            });
+            if let Some(button) = button {
+                return click_button(state, button);
+            }
+            if is_board_covered(state) {
+                return Ok(());
+            }
+            if let Some((col_idx, task_idx)) = state.click_areas.task_at(x, y) {
+                let task_idx = task_idx.unwrap_or(state.columns[col_idx].selected_task_idx);
+                state.select_task_at(col_idx, task_idx)?;
+                state.dragged_task = state.get_selected_task().map(|t| t.id);
+            }
+        }
+        MouseEventKind::Up(MouseButton::Left) => {
+            let Some(task_id) = state.dragged_task.take() else {
+                return Ok(());
+            };
+            if is_board_covered(state) || state.get_selected_task().map(|t| t.id) != Some(task_id) {
+                return Ok(());
+            }
+            if let Some((col_idx, task_idx)) = state.click_areas.task_at(x, y) {
+                let task_idx = task_idx.unwrap_or(state.columns[col_idx].tasks.len());
+                let column = state.get_selected_column();
+                if col_idx != state.selected_column_idx || task_idx != column.selected_task_idx {
+                    state.move_task_to(col_idx, task_idx)?;
+                    state.refresh_search_matches()?;
+                }
+            }
+        }
+        MouseEventKind::ScrollDown if !is_board_covered(state) => state.select_column_right()?,
+        MouseEventKind::ScrollUp if !is_board_covered(state) => state.select_column_left()?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Waits for the next key press or mouse event and hands it to the
+/// handler of whatever has the focus.
+///
+/// # Errors
 ///
-/// Crossterm `event::read()` might return an error,
+/// Returns an error if reading the event fails, SQL related errors
+/// get bubbled up to here too.
 pub fn handle_user_keypress(state: &mut State<'_>) -> Result<(), Error> {
-    if let Event::Key(key) = event::read()? {
-        if state.task_edit_state.is_some() {
+    let event = event::read()?;
+    if let Event::Mouse(mouse) = event {
+        return handle_mouse(state, mouse);
+    }
+    if let Event::Key(key) = event {
+        state.status_message = None;
+        if state.confirm.is_some() {
+            handle_confirm(state, key)?;
+        } else if state.task_edit_state.is_some() {
             handle_task_edit(state, key)?;
+        } else if state.column_edit_state.is_some() {
+            handle_column_edit(state, key)?;
+        } else if state.help.is_some() {
+            handle_help(state, key);
+        } else if state.flow_metrics.is_some() {
+            // Any key closes the expanded stats view
+            state.flow_metrics = None;
+        } else if state.label_filter_state.is_some() {
+            handle_label_filter(state, key)?;
+        } else if state.agenda.is_some() {
+            handle_agenda(state, key)?;
+        } else if state.link_state.is_some() {
+            handle_link(state, key)?;
+        } else if state.board_picker.is_some() {
+            handle_board_picker(state, key)?;
+        } else if state.archive.is_some() {
+            handle_archive(state, key)?;
+        } else if state.search.as_ref().is_some_and(|s| s.is_typing) {
+            handle_search(state, key)?;
         } else {
             handle_main(state, key)?;
+            // The tasks might have changed so the matches are stale
+            state.refresh_search_matches()?;
         }
     }
     Ok(())
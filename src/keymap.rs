@@ -0,0 +1,663 @@
+//! Maps key presses to named actions, with defaults that can be
+//! overridden from the `[keys.*]` tables of the config file.
+
+use anyhow::{anyhow, bail, Error};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::config::KeysConfig;
+
+/// A key along with the modifiers held with it. Written like `q`,
+/// `G`, `C-r`, `M-x`, `Space`, `Enter`, `S-Tab` or `F1`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    /// The key itself
+    pub code: KeyCode,
+    /// `CONTROL` and/or `ALT`, `SHIFT` is part of the character
+    /// instead
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Parses a key written in the config file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key or one of its modifiers is unknown.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        while rest.len() > 2 {
+            // Not sliced directly, the key may start with a character
+            // longer than a byte
+            let modifier = match rest.get(..2) {
+                Some("C-") => KeyModifiers::CONTROL,
+                Some("M-") => KeyModifiers::ALT,
+                Some("S-") => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            modifiers |= modifier;
+            rest = &rest[2..];
+        }
+
+        let mut code = match rest.to_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "del" | "delete" => KeyCode::Delete,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    (Some('F' | 'f'), Some(_)) => rest[1..]
+                        .parse()
+                        .ok()
+                        .filter(|n| (1..=24).contains(n))
+                        .map(KeyCode::F)
+                        .ok_or_else(|| anyhow!("Unknown key \"{text}\""))?,
+                    _ => bail!("Unknown key \"{text}\""),
+                }
+            }
+        };
+        // Shifted characters are just other characters, and terminals
+        // report Shift-Tab as a key of its own
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            match code {
+                KeyCode::Char(c) => code = KeyCode::Char(c.to_ascii_uppercase()),
+                KeyCode::Tab => code = KeyCode::BackTab,
+                _ => (),
+            }
+            if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+                modifiers.remove(KeyModifiers::SHIFT);
+            }
+        }
+        Ok(KeyBinding { code, modifiers })
+    }
+
+    /// Returns `true` if `key` is this key with the same modifiers.
+    #[must_use]
+    pub fn matches(self, key: KeyEvent) -> bool {
+        let mut modifiers = key.modifiers;
+        if matches!(key.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        self.code == key.code && self.modifiers == modifiers
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "C-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "M-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "S-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::BackTab => write!(f, "S-Tab"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Where a key is pressed, each place having its own bindings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    /// The board
+    Main,
+    /// The board while a search is active, these bindings win over
+    /// the [`KeyContext::Main`] ones
+    Search,
+    /// The form creating or editing a task
+    TaskEdit,
+}
+
+impl KeyContext {
+    /// Every context, in the order they are shown in the help.
+    pub const ALL: [KeyContext; 3] = [KeyContext::Main, KeyContext::Search, KeyContext::TaskEdit];
+
+    /// Name of the `[keys.*]` table of the context.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyContext::Main => "main",
+            KeyContext::Search => "search",
+            KeyContext::TaskEdit => "task-edit",
+        }
+    }
+
+    /// Heading of the context in the help.
+    #[must_use]
+    pub fn title(self) -> &'static str {
+        match self {
+            KeyContext::Main => "Board",
+            KeyContext::Search => "While searching",
+            KeyContext::TaskEdit => "Task form",
+        }
+    }
+}
+
+/// Something a key can trigger.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    Quit,
+    SelectColumnLeft,
+    SelectTaskBelow,
+    SelectTaskAbove,
+    SelectColumnRight,
+    SelectFirstTask,
+    SelectLastTask,
+    MoveTaskLeft,
+    MoveTaskDown,
+    MoveTaskUp,
+    MoveTaskRight,
+    NewTask,
+    EditTask,
+    EditInEditor,
+    DeleteTask,
+    ArchiveTask,
+    OpenArchive,
+    OpenTrash,
+    SetPriority0,
+    SetPriority1,
+    SetPriority2,
+    SetPriority3,
+    SortByPriority,
+    Blockers,
+    Search,
+    FilterLabels,
+    ManageColumns,
+    Boards,
+    Agenda,
+    FlowMetrics,
+    ExportMarkdown,
+    ExportOrg,
+    Undo,
+    Redo,
+    TogglePanels,
+    ToggleCardView,
+    Help,
+    NextMatch,
+    PreviousMatch,
+    ClearSearch,
+    NextField,
+    PreviousField,
+    OpenEditor,
+}
+
+/// What the keymap knows about an [`Action`].
+struct ActionInfo {
+    action: Action,
+    /// Name used in the config file
+    name: &'static str,
+    context: KeyContext,
+    /// What it does, shown in the help
+    description: &'static str,
+    default_keys: &'static [&'static str],
+}
+
+macro_rules! actions {
+    ($(($action:ident, $name:literal, $context:ident, $description:literal, [$($key:literal),*])),* $(,)?) => {
+        &[$(ActionInfo {
+            action: Action::$action,
+            name: $name,
+            context: KeyContext::$context,
+            description: $description,
+            default_keys: &[$($key),*],
+        }),*]
+    };
+}
+
+/// Every [`Action`], in the order they are shown in the help.
+const ACTIONS: &[ActionInfo] = actions![
+    (Quit, "quit", Main, "Quit", ["q"]),
+    (
+        SelectColumnLeft,
+        "select-column-left",
+        Main,
+        "Select the column on the left",
+        ["h", "Left"]
+    ),
+    (
+        SelectTaskBelow,
+        "select-task-below",
+        Main,
+        "Select the task below",
+        ["j", "Down"]
+    ),
+    (
+        SelectTaskAbove,
+        "select-task-above",
+        Main,
+        "Select the task above",
+        ["k", "Up"]
+    ),
+    (
+        SelectColumnRight,
+        "select-column-right",
+        Main,
+        "Select the column on the right",
+        ["l", "Right"]
+    ),
+    (
+        SelectFirstTask,
+        "select-first-task",
+        Main,
+        "Select the first task of the column",
+        ["g"]
+    ),
+    (
+        SelectLastTask,
+        "select-last-task",
+        Main,
+        "Select the last task of the column",
+        ["G"]
+    ),
+    (
+        MoveTaskLeft,
+        "move-task-left",
+        Main,
+        "Move the task to the column on the left",
+        ["H"]
+    ),
+    (
+        MoveTaskDown,
+        "move-task-down",
+        Main,
+        "Move the task down",
+        ["J"]
+    ),
+    (MoveTaskUp, "move-task-up", Main, "Move the task up", ["K"]),
+    (
+        MoveTaskRight,
+        "move-task-right",
+        Main,
+        "Move the task to the column on the right",
+        ["L"]
+    ),
+    (NewTask, "new-task", Main, "Create a task", ["n"]),
+    (EditTask, "edit-task", Main, "Edit the task", ["e"]),
+    (
+        EditInEditor,
+        "edit-in-editor",
+        Main,
+        "Edit the title and description in $EDITOR",
+        ["E"]
+    ),
+    (
+        DeleteTask,
+        "delete-task",
+        Main,
+        "Move the task to the trash, after confirming",
+        ["D"]
+    ),
+    (ArchiveTask, "archive-task", Main, "Archive the task", ["a"]),
+    (
+        OpenArchive,
+        "open-archive",
+        Main,
+        "Browse and restore the archived tasks",
+        ["Z"]
+    ),
+    (
+        OpenTrash,
+        "open-trash",
+        Main,
+        "Browse and restore the deleted tasks",
+        ["T"]
+    ),
+    (
+        SetPriority0,
+        "set-priority-0",
+        Main,
+        "Set or clear priority P0, the most urgent",
+        ["0"]
+    ),
+    (
+        SetPriority1,
+        "set-priority-1",
+        Main,
+        "Set or clear priority P1",
+        ["1"]
+    ),
+    (
+        SetPriority2,
+        "set-priority-2",
+        Main,
+        "Set or clear priority P2",
+        ["2"]
+    ),
+    (
+        SetPriority3,
+        "set-priority-3",
+        Main,
+        "Set or clear priority P3",
+        ["3"]
+    ),
+    (
+        SortByPriority,
+        "sort-by-priority",
+        Main,
+        "Sort the column by priority",
+        ["s"]
+    ),
+    (
+        Blockers,
+        "blockers",
+        Main,
+        "Pick the tasks blocking the task",
+        ["b"]
+    ),
+    (
+        Search,
+        "search",
+        Main,
+        "Search the titles and descriptions",
+        ["/"]
+    ),
+    (
+        FilterLabels,
+        "filter-labels",
+        Main,
+        "Filter the board by label",
+        ["f"]
+    ),
+    (
+        ManageColumns,
+        "manage-columns",
+        Main,
+        "Add, rename, move and delete columns",
+        ["C"]
+    ),
+    (Boards, "boards", Main, "Switch boards", ["B"]),
+    (
+        Agenda,
+        "agenda",
+        Main,
+        "Show every task sorted by due date",
+        ["A"]
+    ),
+    (
+        FlowMetrics,
+        "flow-metrics",
+        Main,
+        "Show the flow metrics",
+        ["m"]
+    ),
+    (
+        ExportMarkdown,
+        "export-markdown",
+        Main,
+        "Export to Markdown next to the database",
+        ["x"]
+    ),
+    (
+        ExportOrg,
+        "export-org",
+        Main,
+        "Export to Org next to the database",
+        ["X"]
+    ),
+    (Undo, "undo", Main, "Undo the last change", ["u"]),
+    (Redo, "redo", Main, "Redo the last undone change", ["C-r"]),
+    (
+        TogglePanels,
+        "toggle-panels",
+        Main,
+        "Show or hide the info panels",
+        ["i"]
+    ),
+    (
+        ToggleCardView,
+        "toggle-card-view",
+        Main,
+        "Switch between a line per task and cards",
+        ["v"]
+    ),
+    (Help, "help", Main, "Show every keybinding", ["?"]),
+    (
+        NextMatch,
+        "next-match",
+        Search,
+        "Jump to the next match",
+        ["n"]
+    ),
+    (
+        PreviousMatch,
+        "previous-match",
+        Search,
+        "Jump to the previous match",
+        ["N"]
+    ),
+    (
+        ClearSearch,
+        "clear-search",
+        Search,
+        "Clear the search",
+        ["Esc"]
+    ),
+    (
+        NextField,
+        "next-field",
+        TaskEdit,
+        "Focus the next field",
+        ["Tab"]
+    ),
+    (
+        PreviousField,
+        "previous-field",
+        TaskEdit,
+        "Focus the previous field",
+        ["S-Tab"]
+    ),
+    (
+        OpenEditor,
+        "open-editor",
+        TaskEdit,
+        "Edit the title and description in $EDITOR",
+        ["C-o"]
+    ),
+];
+
+impl Action {
+    fn info(self) -> &'static ActionInfo {
+        ACTIONS
+            .iter()
+            .find(|info| info.action == self)
+            .expect("every action is in ACTIONS")
+    }
+
+    /// Every action, in the order they are shown in the help.
+    pub fn all() -> impl Iterator<Item = Action> {
+        ACTIONS.iter().map(|info| info.action)
+    }
+
+    /// Finds an action by its name in the config file.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTIONS
+            .iter()
+            .find(|info| info.name == name)
+            .map(|info| info.action)
+    }
+
+    /// Name of the action in the config file, like `new-task`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        self.info().name
+    }
+
+    /// Where the action's keys are pressed.
+    #[must_use]
+    pub fn context(self) -> KeyContext {
+        self.info().context
+    }
+
+    /// What the action does.
+    #[must_use]
+    pub fn description(self) -> &'static str {
+        self.info().description
+    }
+}
+
+/// The keys bound to every [`Action`]. The [`Default`] keymap is the
+/// one documented in the README, [`Keymap::new`] applies the
+/// overrides of the config file on top of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = ACTIONS
+            .iter()
+            .map(|info| {
+                let keys = info
+                    .default_keys
+                    .iter()
+                    .map(|key| KeyBinding::parse(key).expect("default keys are valid"))
+                    .collect();
+                (info.action, keys)
+            })
+            .collect();
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    /// The default keymap with the actions listed in `config` bound to
+    /// their new keys instead. An empty list unbinds an action.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing every unknown action, unknown key and
+    /// key bound to two actions of the same [`KeyContext`].
+    pub fn new(config: &KeysConfig) -> Result<Self, Error> {
+        let mut keymap = Keymap::default();
+        let mut problems = Vec::new();
+        let tables = [
+            (KeyContext::Main, &config.main),
+            (KeyContext::Search, &config.search),
+            (KeyContext::TaskEdit, &config.task_edit),
+        ];
+        for (context, table) in tables {
+            for (name, keys) in table {
+                let Some(action) = Action::from_name(name).filter(|a| a.context() == context)
+                else {
+                    problems.push(format!(
+                        "Unknown action \"{name}\" in [keys.{}]",
+                        context.name()
+                    ));
+                    continue;
+                };
+                let mut bindings = Vec::new();
+                for key in keys.as_slice() {
+                    match KeyBinding::parse(key) {
+                        Ok(binding) => bindings.push(binding),
+                        Err(e) => problems.push(format!("{e} for \"{name}\"")),
+                    }
+                }
+                keymap.bindings.insert(action, bindings);
+            }
+        }
+        problems.extend(keymap.conflicts());
+        if !problems.is_empty() {
+            bail!(problems.join("\n"));
+        }
+        Ok(keymap)
+    }
+
+    /// Describes every key bound to two actions of the same
+    /// [`KeyContext`].
+    fn conflicts(&self) -> Vec<String> {
+        let mut seen: HashMap<(KeyContext, KeyBinding), Action> = HashMap::new();
+        let mut conflicts = Vec::new();
+        for action in Action::all() {
+            for &key in self.keys(action) {
+                match seen.get(&(action.context(), key)) {
+                    Some(other) if *other != action => conflicts.push(format!(
+                        "\"{key}\" is bound to both \"{}\" and \"{}\" in [keys.{}]",
+                        other.name(),
+                        action.name(),
+                        action.context().name()
+                    )),
+                    Some(_) => (),
+                    None => {
+                        seen.insert((action.context(), key), action);
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// The [`Action`] of `context` bound to `key`, if any.
+    #[must_use]
+    pub fn action(&self, context: KeyContext, key: KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .filter(|(action, _)| action.context() == context)
+            .find(|(_, keys)| keys.iter().any(|k| k.matches(key)))
+            .map(|(action, _)| *action)
+    }
+
+    /// The keys bound to `action`, empty if it's unbound.
+    #[must_use]
+    pub fn keys(&self, action: Action) -> &[KeyBinding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every key bound to `action`, like `h, Left`.
+    #[must_use]
+    pub fn describe_keys(&self, action: Action) -> String {
+        let keys: Vec<String> = self.keys(action).iter().map(ToString::to_string).collect();
+        keys.join(", ")
+    }
+
+    /// Renders footer entries like `quit: q | navigation: hjkl`, each
+    /// entry showing the first key of its actions. Groups of single
+    /// characters are written together, other keys are separated with
+    /// `/`. Entries whose actions are all unbound are left out.
+    #[must_use]
+    pub fn footer(&self, entries: &[(&str, &[Action])]) -> String {
+        let entries: Vec<String> = entries
+            .iter()
+            .filter_map(|(label, actions)| {
+                let keys: Vec<String> = actions
+                    .iter()
+                    .filter_map(|action| self.keys(*action).first())
+                    .map(ToString::to_string)
+                    .collect();
+                if keys.is_empty() {
+                    return None;
+                }
+                let separator = if keys.len() > 2 && keys.iter().all(|k| k.chars().count() == 1) {
+                    ""
+                } else {
+                    "/"
+                };
+                Some(format!("{label}: {}", keys.join(separator)))
+            })
+            .collect();
+        entries.join(" | ")
+    }
+}
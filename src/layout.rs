@@ -0,0 +1,107 @@
+//! How the board adapts to the size of the terminal: how many columns
+//! fit side by side, which of them are shown, and when the compact
+//! mode kicks in.
+
+use anyhow::{bail, Error};
+use serde::Deserialize;
+use std::ops::Range;
+
+use crate::config::LayoutConfig;
+
+/// How the tasks of a column are drawn.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskView {
+    /// A line each with the title and a few badges, cut at the edge of
+    /// the column
+    #[default]
+    Lines,
+    /// A card each with the whole title, the first line of the
+    /// description, the id and the age
+    Cards,
+}
+
+impl TaskView {
+    /// The other view.
+    #[must_use]
+    pub fn toggled(self) -> Self {
+        match self {
+            TaskView::Lines => TaskView::Cards,
+            TaskView::Cards => TaskView::Lines,
+        }
+    }
+}
+
+/// Sizes the board is laid out with, from the `[layout]` table of the
+/// config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardLayout {
+    /// Columns never get narrower than this, the ones that don't fit
+    /// are scrolled to
+    pub min_column_width: u16,
+    /// Below this width the compact mode is used
+    pub compact_width: u16,
+    /// Below this height the compact mode is used
+    pub compact_height: u16,
+}
+
+impl Default for BoardLayout {
+    fn default() -> Self {
+        BoardLayout {
+            min_column_width: 24,
+            compact_width: 80,
+            compact_height: 24,
+        }
+    }
+}
+
+impl BoardLayout {
+    /// The default layout with the sizes given in `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the minimum column width is 0.
+    pub fn new(config: &LayoutConfig) -> Result<Self, Error> {
+        let default = BoardLayout::default();
+        let layout = BoardLayout {
+            min_column_width: config.min_column_width.unwrap_or(default.min_column_width),
+            compact_width: config.compact_width.unwrap_or(default.compact_width),
+            compact_height: config.compact_height.unwrap_or(default.compact_height),
+        };
+        if layout.min_column_width == 0 {
+            bail!("\"min-column-width\" has to be at least 1");
+        }
+        Ok(layout)
+    }
+
+    /// Whether a terminal of this size is too small for the full
+    /// layout. The compact one hides the info panels and shortens the
+    /// header and footer.
+    #[must_use]
+    pub fn is_compact(&self, width: u16, height: u16) -> bool {
+        width < self.compact_width || height < self.compact_height
+    }
+
+    /// The indices of the columns to draw in `width`, as many as fit
+    /// at [`BoardLayout::min_column_width`]. The range starts at
+    /// `offset`, the first column shown last time, and only scrolls as
+    /// far as needed to show the `selected` one.
+    #[must_use]
+    pub fn visible_columns(
+        &self,
+        count: usize,
+        selected: usize,
+        offset: usize,
+        width: u16,
+    ) -> Range<usize> {
+        if count == 0 {
+            return 0..0;
+        }
+        let fits = usize::from(width / self.min_column_width).clamp(1, count);
+        let selected = selected.min(count - 1);
+        let start = offset
+            .min(count - fits)
+            .clamp((selected + 1).saturating_sub(fits), selected);
+        start..start + fits
+    }
+}
@@ -14,11 +14,35 @@
 
 #![deny(rust_2018_idioms)]
 mod app;
+mod card;
+mod cli;
+mod config;
 mod db;
+mod due;
+mod editor;
+mod export;
 mod input;
+mod keymap;
+mod layout;
+mod mouse;
+mod stats;
+mod theme;
 mod ui;
+mod undo;
 
 pub use app::*;
+pub use card::*;
+pub use cli::{run_command, Command};
+pub use config::*;
 pub use db::*;
-pub use input::handle_user_keypress;
+pub use due::*;
+pub use editor::*;
+pub use export::*;
+pub use input::{handle_mouse, handle_user_keypress};
+pub use keymap::*;
+pub use layout::*;
+pub use mouse::*;
+pub use stats::*;
+pub use theme::*;
 pub use ui::draw_ui_from_state;
+pub use undo::*;
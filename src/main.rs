@@ -1,10 +1,11 @@
 #![deny(rust_2018_idioms)]
+use anyhow::Context;
 use clap::{Parser, ValueHint::FilePath};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use kanban_tui::State;
+use kanban_tui::{BoardLayout, Command, Config, DBConn, Keymap, State, Theme};
 use rusqlite::Connection;
 use std::{error::Error, io, path::PathBuf};
 use tui::backend::CrosstermBackend;
@@ -17,49 +18,130 @@ pub struct CliArgs {
     #[arg(value_name="DATABASE", value_hint=FilePath, index=1)]
     /// Path to the SQLite database
     pub filepath: Option<PathBuf>,
+    #[arg(long, global = true)]
+    /// Name of the board to open or to run the subcommand against,
+    /// defaults to the last one opened
+    pub board: Option<String>,
+    #[arg(long, global = true)]
+    /// Print the output of subcommands as JSON
+    pub json: bool,
+    #[arg(long, value_name = "DAYS")]
+    /// Deleted tasks older than this are purged from the trash when
+    /// the app starts, overrides `trash-days` from the config file
+    pub trash_days: Option<u32>,
+    #[command(subcommand)]
+    /// Run a command against the board instead of starting the TUI
+    pub command: Option<Command>,
 }
 
-fn main() -> anyhow::Result<(), Box<dyn Error>> {
-    let dbpath = CliArgs::parse()
-        .filepath
-        .unwrap_or(PathBuf::from("./kanban.db"));
+type Term = Terminal<CrosstermBackend<io::Stdout>>;
 
-    let migrate = !dbpath.exists();
+/// Hands the terminal over to `f`, like an editor, and takes it back
+/// afterwards even if `f` or leaving the terminal failed.
+fn suspend_terminal<T>(terminal: &mut Term, f: impl FnOnce() -> T) -> io::Result<T> {
+    disable_raw_mode()?;
+    let left = crossterm::execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )
+    .and_then(|()| terminal.show_cursor());
+    let result = left.map(|()| f());
+    enable_raw_mode()?;
+    crossterm::execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    result
+}
 
-    let mut conn = Connection::open(dbpath)?;
+/// Gives the terminal back to the shell the way it was found.
+fn restore_terminal(terminal: &mut Term) -> io::Result<()> {
+    disable_raw_mode()?;
+    crossterm::execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()
+}
 
-    if migrate {
-        let migrations = include_str!("../sql/migrations.sql");
-        let migrations: Vec<&str> = migrations.split(';').collect();
-        let tx = conn.transaction()?;
-        for m in migrations {
-            if !m.trim().is_empty() {
-                tx.execute(m, ())?;
+/// The main loop of the TUI, returning once the user quits or on the
+/// first error the UI can't recover from.
+fn run_app(terminal: &mut Term, state: &mut State<'_>) -> anyhow::Result<()> {
+    while !state.quit {
+        terminal.draw(|f| kanban_tui::draw_ui_from_state(f, state))?;
+        kanban_tui::handle_user_keypress(state)?;
+        if state.open_editor {
+            state.open_editor = false;
+            if let Some(text) = state.get_editor_text() {
+                let editor = kanban_tui::editor_command();
+                let edited = suspend_terminal(terminal, || kanban_tui::run_editor(&editor, &text))?;
+                // A failed edit is no reason to quit
+                if let Err(e) = edited.and_then(|text| state.apply_editor_text(&text)) {
+                    state.status_message = Some(format!("{e:#}"));
+                }
             }
         }
-        tx.commit()?;
     }
+    Ok(())
+}
+
+fn main() -> anyhow::Result<(), Box<dyn Error>> {
+    let args = CliArgs::parse();
+    let dbpath = args.filepath.unwrap_or(PathBuf::from("./kanban.db"));
+
+    let mut conn = Connection::open(dbpath)?;
+    kanban_tui::migrate::run(&mut conn)?;
+    let mut db_conn = DBConn::new(conn);
+    let config = Config::load()?;
+
+    if let Some(command) = args.command {
+        kanban_tui::run_command(
+            &mut db_conn,
+            args.board.as_deref(),
+            command,
+            args.json,
+            config.wip_limit,
+            &mut io::stdout(),
+        )?;
+        return Ok(());
+    }
+
+    // Config problems are reported before the terminal is taken over
+    let keymap = Keymap::new(&config.keys).context("Invalid keybindings in the config file")?;
+    let theme = Theme::new(&config.theme).context("Invalid theme in the config file")?;
+    let layout = BoardLayout::new(&config.layout).context("Invalid layout in the config file")?;
 
-    let mut state = State::new(conn)?;
+    // Only the app purges the trash, so the commands never lose a task
+    // they could restore
+    db_conn.purge_trash(args.trash_days.or(config.trash_days).unwrap_or(30))?;
+
+    let mut state = State::new(db_conn.0)?;
+    state.keymap = keymap;
+    state.theme = theme;
+    state.wip_limit_mode = config.wip_limit;
+    state.layout = layout;
+    state.show_panels = config.layout.panels.unwrap_or(true);
+    state.task_view = config.layout.task_view;
+    if let Some(board) = &args.board {
+        state.switch_board_by_name(board)?;
+    }
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    crossterm::execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    if let Err(e) = crossterm::execute!(stdout, EnterAlternateScreen, EnableMouseCapture) {
+        disable_raw_mode()?;
+        return Err(e.into());
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    while !state.quit {
-        terminal.draw(|f| kanban_tui::draw_ui_from_state(f, &mut state))?;
-        kanban_tui::handle_user_keypress(&mut state)?;
-    }
-
-    // restore terminal
-    disable_raw_mode()?;
-    crossterm::execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    let result = run_app(&mut terminal, &mut state);
+    // Restore the terminal before reporting any error so it's readable
+    restore_terminal(&mut terminal)?;
+    result?;
     Ok(())
 }
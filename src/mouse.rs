@@ -0,0 +1,71 @@
+//! Where things were drawn in the last frame, so mouse events can be
+//! matched to the tasks and buttons under the pointer.
+
+use tui::layout::Rect;
+
+/// Where a [`Column`][`crate::Column`] and its visible tasks were
+/// drawn.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnArea {
+    /// Index of the column in [`State::columns`][`crate::State::columns`]
+    pub col_idx: usize,
+    /// The whole column, borders included
+    pub area: Rect,
+    /// The part of the column holding the task list
+    pub list: Rect,
+    /// Index in [`Column::tasks`][`crate::Column::tasks`] of the task
+    /// drawn on each row of [`ColumnArea::list`], top to bottom
+    pub rows: Vec<usize>,
+}
+
+/// A popup button that can be clicked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Button {
+    /// Saves the task form
+    FormConfirm,
+    /// Closes the task form without saving
+    FormCancel,
+    /// Answers yes to the confirmation popup
+    ConfirmYes,
+    /// Answers no to the confirmation popup
+    ConfirmNo,
+}
+
+/// Everything clickable of the last frame, filled in while drawing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClickAreas {
+    /// The columns of the board, left to right
+    pub columns: Vec<ColumnArea>,
+    /// The buttons of the open popups
+    pub buttons: Vec<(Button, Rect)>,
+}
+
+fn contains(area: Rect, x: u16, y: u16) -> bool {
+    x >= area.x && x < area.right() && y >= area.y && y < area.bottom()
+}
+
+impl ClickAreas {
+    /// The column under the pointer along with the task under it,
+    /// `None` for the task when it's over the empty part or the border
+    /// of the column.
+    #[must_use]
+    pub fn task_at(&self, x: u16, y: u16) -> Option<(usize, Option<usize>)> {
+        let column = self.columns.iter().find(|c| contains(c.area, x, y))?;
+        let task_idx = contains(column.list, x, y)
+            .then(|| column.rows.get(usize::from(y - column.list.y)))
+            .flatten()
+            .copied();
+        Some((column.col_idx, task_idx))
+    }
+
+    /// The button under the pointer, if any, the last one drawn when
+    /// popups overlap.
+    #[must_use]
+    pub fn button_at(&self, x: u16, y: u16) -> Option<Button> {
+        self.buttons
+            .iter()
+            .rev()
+            .find(|(_, area)| contains(*area, x, y))
+            .map(|(button, _)| *button)
+    }
+}
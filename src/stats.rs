@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use crate::app::{Column, TaskEvent, TaskEventKind};
+
+/// Number of seconds in a week, used to bucket the throughput.
+const WEEK: i64 = 7 * 24 * 60 * 60;
+
+/// How many weeks of throughput are reported, the current one included.
+pub const THROUGHPUT_WEEKS: usize = 4;
+
+/// How long tasks stayed in a [`Column`] before leaving it. Durations
+/// are in seconds and are `None` when no task has left the column yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnCycleTime {
+    /// Id of the [`Column`]
+    pub column_id: i64,
+    /// Name of the [`Column`]
+    pub name: String,
+    /// Average time spent in the column
+    pub average: Option<i64>,
+    /// Median time spent in the column
+    pub median: Option<i64>,
+    /// How many stays in the column were measured
+    pub samples: usize,
+}
+
+/// Kanban flow metrics computed from the recorded column transitions
+/// of every [`Task`][`crate::Task`]. Durations are in seconds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlowMetrics {
+    /// Time spent in each [`Column`], in board order
+    pub cycle_times: Vec<ColumnCycleTime>,
+    /// Average time from creation to Done
+    pub lead_time_average: Option<i64>,
+    /// Median time from creation to Done
+    pub lead_time_median: Option<i64>,
+    /// Tasks completed per week, newest first. The first entry is the
+    /// last 7 days
+    pub weekly_throughput: Vec<usize>,
+}
+
+impl FlowMetrics {
+    /// Replays the events of each task to work out how long it stayed
+    /// in each column and when it got completed. A task counts as
+    /// completed when its last transition put it in one of the
+    /// `done_column_ids`, deleting it afterwards doesn't undo that.
+    ///
+    /// `events` must be sorted by task and then by time.
+    #[must_use]
+    pub fn from_events(
+        events: &[TaskEvent],
+        columns: &[Column],
+        done_column_ids: &[i64],
+        now: i64,
+    ) -> Self {
+        let mut durations: HashMap<i64, Vec<i64>> = HashMap::new();
+        let mut lead_times = Vec::new();
+        let mut weekly_throughput = vec![0; THROUGHPUT_WEEKS];
+
+        // One task at a time, its events being next to each other
+        let mut rest = events;
+        while let Some(first) = rest.first() {
+            let len = rest
+                .iter()
+                .position(|e| e.task_id != first.task_id)
+                .unwrap_or(rest.len());
+            let (task_events, tail) = rest.split_at(len);
+            rest = tail;
+            let mut created_at = None;
+            let mut current: Option<(i64, i64)> = None;
+            let mut completed_at = None;
+
+            for event in task_events {
+                if event.kind == TaskEventKind::Created {
+                    created_at = Some(event.created_at);
+                }
+                let enters_column = matches!(
+                    event.kind,
+                    TaskEventKind::Created | TaskEventKind::Moved | TaskEventKind::Restored
+                );
+                if !enters_column && event.kind != TaskEventKind::Deleted {
+                    continue;
+                }
+
+                if let Some((column_id, entered_at)) = current.take() {
+                    durations
+                        .entry(column_id)
+                        .or_default()
+                        .push(event.created_at - entered_at);
+                }
+                if let (true, Some(column_id)) = (enters_column, event.column_id) {
+                    current = Some((column_id, event.created_at));
+                    completed_at = done_column_ids
+                        .contains(&column_id)
+                        .then_some(event.created_at);
+                }
+            }
+
+            if let Some(completed_at) = completed_at {
+                if let Some(created_at) = created_at {
+                    lead_times.push(completed_at - created_at);
+                }
+                if let Ok(week) = usize::try_from((now - completed_at).div_euclid(WEEK)) {
+                    if let Some(count) = weekly_throughput.get_mut(week) {
+                        *count += 1;
+                    }
+                }
+            }
+        }
+
+        let cycle_times = columns
+            .iter()
+            .map(|column| {
+                let mut samples = durations.remove(&column.id).unwrap_or_default();
+                ColumnCycleTime {
+                    column_id: column.id,
+                    name: column.name.clone(),
+                    average: average(&samples),
+                    median: median(&mut samples),
+                    samples: samples.len(),
+                }
+            })
+            .collect();
+
+        FlowMetrics {
+            cycle_times,
+            lead_time_average: average(&lead_times),
+            lead_time_median: median(&mut lead_times),
+            weekly_throughput,
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn average(values: &[i64]) -> Option<i64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<i64>() / values.len() as i64)
+    }
+}
+
+fn median(values: &mut [i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        // Sorted, so this can't overflow
+        Some(values[mid - 1] + (values[mid] - values[mid - 1]) / 2)
+    } else {
+        Some(values[mid])
+    }
+}
+
+/// Formats a duration in seconds with its two most significant units,
+/// like `3d 4h` or `12m`.
+#[must_use]
+pub fn format_duration(seconds: i64) -> String {
+    let minutes = seconds / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+    if days > 0 {
+        format!("{days}d {}h", hours % 24)
+    } else if hours > 0 {
+        format!("{hours}h {}m", minutes % 60)
+    } else {
+        format!("{minutes}m")
+    }
+}
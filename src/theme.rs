@@ -0,0 +1,245 @@
+//! Colors and markers of the UI, picked from a few built-in themes
+//! and tweaked from the `[theme]` table of the config file.
+
+use anyhow::{anyhow, bail, Context, Error};
+use tui::style::{Color, Modifier, Style};
+
+use crate::config::{StyleConfig, ThemeConfig};
+
+/// The styles the board is drawn with. Colors are written like
+/// `red`, `light-blue`, `dark-gray`, `#ff8800` or a 256-color index
+/// like `208`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Borders of the board's panes
+    pub border: Style,
+    /// The selected column
+    pub selected_column: Style,
+    /// The selected task
+    pub selected_task: Style,
+    /// Background of the popups
+    pub popup: Style,
+    /// Text of the label chips, their background is the label's color
+    pub label: Style,
+    /// Tasks past their due date
+    pub overdue: Style,
+    /// Tasks due today
+    pub due_today: Style,
+    /// Tasks waiting on an unfinished one
+    pub blocked: Style,
+    /// Border of the columns holding more tasks than their WIP limit
+    pub wip_exceeded: Style,
+    /// If `false` then plain text replaces the emoji markers
+    pub emoji: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            border: Style::default(),
+            selected_column: Style::default().add_modifier(Modifier::REVERSED),
+            selected_task: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            popup: Style::default(),
+            label: Style::default().fg(Color::Black),
+            overdue: Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            due_today: Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            blocked: Style::default().fg(Color::Red),
+            wip_exceeded: Style::default().fg(Color::Red),
+            emoji: true,
+        }
+    }
+}
+
+impl Theme {
+    /// Names of the built-in themes, see [`Theme::builtin`].
+    pub const BUILTIN_NAMES: [&'static str; 3] = ["default", "high-contrast", "light"];
+
+    /// The built-in theme called `name`, if there's one.
+    #[must_use]
+    pub fn builtin(name: &str) -> Option<Self> {
+        let bold = Modifier::BOLD;
+        match name {
+            "default" => Some(Theme::default()),
+            // Solid backgrounds instead of modifiers, which some
+            // terminals barely show
+            "high-contrast" => Some(Theme {
+                border: Style::default().fg(Color::White).add_modifier(bold),
+                selected_column: Style::default().fg(Color::White).bg(Color::Black),
+                selected_task: Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(bold),
+                popup: Style::default().fg(Color::White).bg(Color::Black),
+                label: Style::default().fg(Color::Black).add_modifier(bold),
+                overdue: Style::default()
+                    .fg(Color::White)
+                    .bg(Color::Red)
+                    .add_modifier(bold),
+                due_today: Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(bold),
+                blocked: Style::default().fg(Color::LightRed).add_modifier(bold),
+                wip_exceeded: Style::default().fg(Color::LightRed).add_modifier(bold),
+                emoji: true,
+            }),
+            // Yellow text is unreadable on a light background
+            "light" => Some(Theme {
+                border: Style::default().fg(Color::DarkGray),
+                label: Style::default().fg(Color::White),
+                due_today: Style::default().fg(Color::Magenta).add_modifier(bold),
+                ..Theme::default()
+            }),
+            _ => None,
+        }
+    }
+
+    /// The built-in theme named in `config` with its overrides
+    /// applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the theme isn't a built-in one or one of the
+    /// styles has an unknown color or modifier.
+    pub fn new(config: &ThemeConfig) -> Result<Self, Error> {
+        let name = config.name.as_deref().unwrap_or("default");
+        let mut theme = Theme::builtin(name).ok_or_else(|| {
+            anyhow!(
+                "Unknown theme \"{name}\", the built-in ones are {}",
+                Theme::BUILTIN_NAMES.join(", ")
+            )
+        })?;
+        if let Some(emoji) = config.emoji {
+            theme.emoji = emoji;
+        }
+        let overrides = [
+            ("border", &config.border, &mut theme.border),
+            (
+                "selected-column",
+                &config.selected_column,
+                &mut theme.selected_column,
+            ),
+            (
+                "selected-task",
+                &config.selected_task,
+                &mut theme.selected_task,
+            ),
+            ("popup", &config.popup, &mut theme.popup),
+            ("label", &config.label, &mut theme.label),
+            ("overdue", &config.overdue, &mut theme.overdue),
+            ("due-today", &config.due_today, &mut theme.due_today),
+            ("blocked", &config.blocked, &mut theme.blocked),
+            (
+                "wip-exceeded",
+                &config.wip_exceeded,
+                &mut theme.wip_exceeded,
+            ),
+        ];
+        for (name, style, target) in overrides {
+            if let Some(style) = style {
+                *target =
+                    parse_style(style).with_context(|| format!("Invalid style for \"{name}\""))?;
+            }
+        }
+        Ok(theme)
+    }
+
+    /// Follows the selected task on the board, nothing without emoji
+    /// since the task is highlighted anyway.
+    #[must_use]
+    pub fn selected_marker(&self) -> &'static str {
+        if self.emoji {
+            " 👈"
+        } else {
+            ""
+        }
+    }
+
+    /// Goes before the tasks waiting on an unfinished one.
+    #[must_use]
+    pub fn blocked_marker(&self) -> &'static str {
+        if self.emoji {
+            "🚫 "
+        } else {
+            "[blocked] "
+        }
+    }
+}
+
+/// Parses a color of the config file.
+///
+/// # Errors
+///
+/// Returns an error if it's neither a known name, a `#rrggbb` hex code
+/// nor a 256-color index.
+pub fn parse_color(text: &str) -> Result<Color, Error> {
+    let color = match text.to_lowercase().as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "dark-gray" => Color::DarkGray,
+        "light-red" => Color::LightRed,
+        "light-green" => Color::LightGreen,
+        "light-yellow" => Color::LightYellow,
+        "light-blue" => Color::LightBlue,
+        "light-magenta" => Color::LightMagenta,
+        "light-cyan" => Color::LightCyan,
+        "white" => Color::White,
+        hex if hex.len() == 7 && hex.is_ascii() && hex.starts_with('#') => {
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+            match (channel(1), channel(3), channel(5)) {
+                (Ok(r), Ok(g), Ok(b)) => Color::Rgb(r, g, b),
+                _ => bail!("Unknown color \"{text}\""),
+            }
+        }
+        index => Color::Indexed(
+            index
+                .parse()
+                .map_err(|_| anyhow!("Unknown color \"{text}\""))?,
+        ),
+    };
+    Ok(color)
+}
+
+fn parse_modifier(text: &str) -> Result<Modifier, Error> {
+    let modifier = match text.to_lowercase().as_str() {
+        "bold" => Modifier::BOLD,
+        "dim" => Modifier::DIM,
+        "italic" => Modifier::ITALIC,
+        "underlined" => Modifier::UNDERLINED,
+        "slow-blink" => Modifier::SLOW_BLINK,
+        "rapid-blink" => Modifier::RAPID_BLINK,
+        "reversed" => Modifier::REVERSED,
+        "hidden" => Modifier::HIDDEN,
+        "crossed-out" => Modifier::CROSSED_OUT,
+        _ => bail!("Unknown modifier \"{text}\""),
+    };
+    Ok(modifier)
+}
+
+/// Turns a [`StyleConfig`] into a [`Style`].
+///
+/// # Errors
+///
+/// Returns an error if a color or modifier is unknown.
+pub fn parse_style(config: &StyleConfig) -> Result<Style, Error> {
+    let mut style = Style::default();
+    if let Some(fg) = &config.fg {
+        style = style.fg(parse_color(fg)?);
+    }
+    if let Some(bg) = &config.bg {
+        style = style.bg(parse_color(bg)?);
+    }
+    for modifier in &config.modifiers {
+        style = style.add_modifier(parse_modifier(modifier)?);
+    }
+    Ok(style)
+}
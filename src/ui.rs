@@ -1,70 +1,404 @@
-use crate::app::{State, TaskEditFocus};
+use crate::app::{
+    BoardPickerMode, ChecklistItem, Column, ColumnEditMode, ColumnRole, LabelColor, OffBoardKind,
+    Priority, SearchState, State, Task, TaskEditFocus, TaskState,
+};
+use crate::card::{description_preview, wrap_text};
+use crate::config::Config;
+use crate::due::{describe_due_date, today, DueStatus, DUE_DATE_FORMAT};
+use crate::keymap::{Action, KeyContext, Keymap};
+use crate::layout::TaskView;
+use crate::mouse::{Button, ColumnArea};
+use crate::stats::format_duration;
+use crate::theme::Theme;
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+use std::cmp::min;
+use std::collections::HashSet;
+use std::ops::Range;
 use tui::backend::Backend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use tui::style::{Modifier, Style};
+use tui::style::{Color, Modifier, Style};
 use tui::text::{Span, Spans};
 use tui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
 use tui::Frame;
+use tui_textarea::TextArea;
 
-fn draw_tasks<B: Backend>(f: &mut Frame<'_, B>, area: Rect, state: &State<'_>) {
+fn label_color(color: LabelColor) -> Color {
+    match color {
+        LabelColor::Red => Color::Red,
+        LabelColor::Green => Color::Green,
+        LabelColor::Yellow => Color::Yellow,
+        LabelColor::Blue => Color::Blue,
+        LabelColor::Magenta => Color::Magenta,
+        LabelColor::Cyan => Color::Cyan,
+    }
+}
+
+fn priority_color(priority: Priority) -> Color {
+    match priority {
+        Priority::P0 => Color::Red,
+        Priority::P1 => Color::Yellow,
+        Priority::P2 => Color::Blue,
+        Priority::P3 => Color::Gray,
+    }
+}
+
+/// The theme's overdue style once a due date has passed and its due
+/// today style on the day itself.
+fn due_date_style(theme: &Theme, due_date: NaiveDate, today: NaiveDate) -> Style {
+    match DueStatus::of(due_date, today) {
+        DueStatus::Overdue => theme.overdue,
+        DueStatus::Today => theme.due_today,
+        DueStatus::Upcoming => Style::default(),
+    }
+}
+
+/// The checklist progress of a [`Task`] like `[3/5]`, green once
+/// every item is done. `None` if the task has no checklist.
+fn checklist_progress(task: &Task) -> Option<Span<'static>> {
+    let (done, total) = task.checklist_progress();
+    if total == 0 {
+        return None;
+    }
+    let mut style = Style::default().add_modifier(Modifier::DIM);
+    if done == total {
+        style = Style::default().fg(Color::Green);
+    }
+    Some(Span::styled(format!(" [{done}/{total}]"), style))
+}
+
+/// Draws a [`Label`][`crate::Label`] name as a colored chip.
+fn label_chip<'a>(state: &State<'_>, name: &'a str) -> Span<'a> {
+    let color = state
+        .labels
+        .iter()
+        .find(|l| l.name == name)
+        .map_or(Color::Gray, |l| label_color(l.color));
+    Span::styled(format!(" {name} "), state.theme.label.bg(color))
+}
+
+/// The chips of every label of a [`Task`], each preceded by a space.
+fn label_chips<'a>(state: &State<'_>, task: &'a Task) -> Vec<Span<'a>> {
+    task.labels
+        .iter()
+        .flat_map(|name| [Span::raw(" "), label_chip(state, name)])
+        .collect()
+}
+
+/// The title of a [`Column`][`crate::Column`] on the board, like
+/// `InProgress 3/4 ↓priority`, `visible` being how many of its tasks
+/// pass the search and the label filter.
+fn column_title(state: &State<'_>, column: &Column, visible: usize) -> String {
+    let sorting = if column.sort_by_priority {
+        " ↓priority"
+    } else {
+        ""
+    };
+    let wip = column.wip_limit.map_or(String::new(), |limit| {
+        format!(" {}/{limit}", column.tasks.len())
+    });
+    // Show the roles while managing the columns since that's
+    // where they get changed
+    if state.column_edit_state.is_some() {
+        format!("{}{wip} [{}]", column.name, column.role.as_str())
+    } else if state.is_filtering() {
+        format!(
+            "{}{wip} ({visible}/{}){sorting}",
+            column.name,
+            column.tasks.len()
+        )
+    } else {
+        format!("{}{wip}{sorting}", column.name)
+    }
+}
+
+/// Index in [`Column::tasks`] of the task on each row of a list
+/// `height` rows tall, `tasks` being the visible ones and `heights`
+/// how many rows each takes. The list is scrolled the way [`List`]
+/// scrolls to keep `selected_row` in view.
+fn visible_rows(
+    tasks: &[usize],
+    heights: &[usize],
+    selected_row: usize,
+    height: u16,
+) -> Vec<usize> {
+    if tasks.is_empty() {
+        return Vec::new();
+    }
+    let max_height = usize::from(height);
+    let (mut start, mut end, mut used) = (0, 0, 0);
+    while end < heights.len() && used + heights[end] <= max_height {
+        used += heights[end];
+        end += 1;
+    }
+    let selected_row = min(selected_row, tasks.len() - 1);
+    while selected_row >= end {
+        used += heights[end];
+        end += 1;
+        while used > max_height {
+            used -= heights[start];
+            start += 1;
+        }
+    }
+    (start..end)
+        .flat_map(|idx| (0..heights[idx]).map(move |_| tasks[idx]))
+        .collect()
+}
+
+/// The blocked marker and the priority, which go before the title of
+/// a task.
+fn task_prefix(state: &State<'_>, task: &Task) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    if state.is_task_blocked(task) {
+        spans.push(Span::raw(state.theme.blocked_marker()));
+    }
+    if let Some(priority) = task.priority {
+        spans.push(Span::styled(
+            format!("[{}] ", priority.as_str()),
+            Style::default()
+                .fg(priority_color(priority))
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    spans
+}
+
+/// The style of the title of a task, highlighted when it's selected
+/// and colored when it's due.
+fn task_title_style(state: &State<'_>, selected: bool, due_date: Option<NaiveDate>) -> Style {
+    let mut style = Style::default();
+    if selected {
+        style = style.patch(state.theme.selected_task);
+    }
+    if let Some(due_date) = due_date {
+        style = style.patch(due_date_style(&state.theme, due_date, today()));
+    }
+    style
+}
+
+/// How far off the due date is, like ` (in 3 days)`.
+fn due_date_badge(state: &State<'_>, due_date: NaiveDate) -> Span<'static> {
+    let today = today();
+    Span::styled(
+        format!(" ({})", describe_due_date(due_date, today)),
+        due_date_style(&state.theme, due_date, today),
+    )
+}
+
+/// A task on a single line, its title followed by its checklist
+/// progress, due date and labels. `due_date` is `None` for finished
+/// tasks so they don't show up as late.
+fn task_line<'a>(
+    state: &State<'_>,
+    task: &'a Task,
+    selected: bool,
+    due_date: Option<NaiveDate>,
+) -> ListItem<'a> {
+    let style = task_title_style(state, selected, due_date);
+    let mut spans = task_prefix(state, task);
+    if selected {
+        let title = format!("{}{}", task.title, state.theme.selected_marker());
+        spans.push(Span::styled(title, style));
+    } else {
+        spans.push(Span::styled(task.title.as_str(), style));
+    }
+    spans.extend(checklist_progress(task));
+    spans.extend(due_date.map(|due_date| due_date_badge(state, due_date)));
+    spans.extend(label_chips(state, task));
+    ListItem::new(vec![Spans::from(spans)])
+}
+
+/// A task as a card `width` cells wide: its whole title wrapped over
+/// as many lines as it takes, the first line of its description, then
+/// its id, age, checklist progress, due date and labels. An empty line
+/// separates it from the next card.
+fn task_card<'a>(
+    state: &State<'_>,
+    task: &'a Task,
+    selected: bool,
+    due_date: Option<NaiveDate>,
+    width: usize,
+) -> ListItem<'a> {
+    let style = task_title_style(state, selected, due_date);
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let prefix = task_prefix(state, task);
+    let prefix_width: usize = prefix.iter().map(Span::width).sum();
+    let mut title = task.title.clone();
+    if selected {
+        title.push_str(state.theme.selected_marker());
+    }
+
+    let mut wrapped = wrap_text(&title, width.saturating_sub(prefix_width), width).into_iter();
+    let mut first_line = prefix;
+    first_line.push(Span::styled(wrapped.next().unwrap_or_default(), style));
+    let mut lines = vec![Spans::from(first_line)];
+    lines.extend(wrapped.map(|line| Spans::from(Span::styled(line, style))));
+    if let Some(preview) = description_preview(&task.description, width) {
+        lines.push(Spans::from(Span::styled(preview, dim)));
+    }
+
+    let age = format_duration(Utc::now().timestamp() - task.created_at);
+    let mut badges = vec![Span::styled(format!("#{} · {age}", task.id), dim)];
+    badges.extend(checklist_progress(task));
+    badges.extend(due_date.map(|due_date| due_date_badge(state, due_date)));
+    badges.extend(label_chips(state, task));
+    lines.push(Spans::from(badges));
+    lines.push(Spans::from(""));
+    ListItem::new(lines)
+}
+
+/// Points out the columns scrolled out of view with an arrow on the
+/// title of the column next to them.
+fn scroll_hints(title: &str, col_idx: usize, shown: &Range<usize>, count: usize) -> String {
+    let left = if col_idx == shown.start && col_idx > 0 {
+        "‹ "
+    } else {
+        ""
+    };
+    let right = if col_idx + 1 == shown.end && shown.end < count {
+        " ›"
+    } else {
+        ""
+    };
+    format!("{left}{title}{right}")
+}
+
+/// Draws the `shown` columns of the board side by side and returns
+/// where each one ended up so clicks can be matched to its tasks.
+fn draw_tasks<B: Backend>(
+    f: &mut Frame<'_, B>,
+    area: Rect,
+    state: &State<'_>,
+    shown: Range<usize>,
+) -> Vec<ColumnArea> {
+    let count = u32::try_from(shown.len()).unwrap_or(1);
     let columns = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints(
-            vec![
-                Constraint::Percentage(100 / u16::try_from(state.columns.len()).unwrap_or(4));
-                state.columns.len()
-            ]
-            .as_ref(),
-        )
+        .constraints(vec![Constraint::Ratio(1, count); shown.len()].as_ref())
         .split(area);
+    let mut column_areas = Vec::new();
 
-    for (i, column) in state.columns.iter().enumerate() {
-        let items: Vec<ListItem<'_>> = column
+    for (i, column) in state
+        .columns
+        .iter()
+        .enumerate()
+        .skip(shown.start)
+        .take(shown.len())
+    {
+        let column_area = columns[i - shown.start];
+        let inner_width = usize::from(column_area.width.saturating_sub(2));
+        let visible: Vec<(usize, &Task)> = column
             .tasks
             .iter()
             .enumerate()
-            .map(|(j, task)| {
-                let mut style = Style::default();
-                let col_idx = state.selected_column_idx;
-                let task_idx = state.get_selected_column().selected_task_idx;
-                let mut span;
-                if i == col_idx && j == task_idx {
-                    style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
-                    span = Span::raw(format!("{} 👈", task.title));
+            .filter(|(_, task)| state.is_task_visible(task))
+            .collect();
+        let items: Vec<ListItem<'_>> = visible
+            .iter()
+            .map(|&(j, task)| {
+                let selected = i == state.selected_column_idx && j == column.selected_task_idx;
+                // Finished tasks can't be late anymore
+                let due_date = task.due_date.filter(|_| column.role != ColumnRole::Done);
+                if state.task_view == TaskView::Cards {
+                    task_card(state, task, selected, due_date, inner_width)
                 } else {
-                    span = Span::raw(&task.title);
+                    task_line(state, task, selected, due_date)
                 }
-                span.style = style;
-                ListItem::new(vec![Spans::from(span)])
             })
             .collect();
+        let heights: Vec<usize> = items.iter().map(ListItem::height).collect();
+        let tasks: Vec<usize> = visible.iter().map(|&(j, _)| j).collect();
 
         let mut style = Style::default();
         if i == state.selected_column_idx {
-            style = style.add_modifier(Modifier::REVERSED);
+            style = state.theme.selected_column;
         };
-        let mut s = Span::raw(column.name.as_str());
-        let mods = Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED;
+        let title = column_title(state, column, items.len());
+        let mut s = Span::raw(scroll_hints(&title, i, &shown, state.columns.len()));
+        let mut mods = Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED;
+        // Make it obvious which column is going to be edited
+        if state.column_edit_state.is_some() && i == state.selected_column_idx {
+            mods |= Modifier::REVERSED;
+        }
         s.style = Style::default().add_modifier(mods);
-        let block = Block::default().title(s).borders(Borders::ALL);
-        let inner_area = block.inner(columns[i]);
+        let border_style = if column.is_over_wip_limit() {
+            state.theme.wip_exceeded
+        } else {
+            state.theme.border
+        };
+        let block = Block::default()
+            .title(s)
+            .borders(Borders::ALL)
+            .border_style(border_style);
+        let inner_area = block.inner(column_area);
         let inner_block = Block::default().style(style);
         let list = List::new(items).block(inner_block);
 
+        // Hidden tasks don't take up rows, so count only the visible
+        // ones to know where the selected task is
+        let selected_row = column.tasks[..column.selected_task_idx.min(column.tasks.len())]
+            .iter()
+            .filter(|task| state.is_task_visible(task))
+            .count();
         let mut list_state = ListState::default();
-        list_state.select(Some(column.selected_task_idx + 1));
+        list_state.select(Some(selected_row + 1));
+
+        column_areas.push(ColumnArea {
+            col_idx: i,
+            area: column_area,
+            list: inner_area,
+            rows: visible_rows(&tasks, &heights, selected_row + 1, inner_area.height),
+        });
 
-        f.render_widget(block, columns[i]);
+        f.render_widget(block, column_area);
         f.render_stateful_widget(list, inner_area, &mut list_state);
     }
+    column_areas
 }
 
 fn draw_task_info<B: Backend>(f: &mut Frame<'_, B>, area: Rect, state: &State<'_>) {
-    let block = Block::default().title("TASK INFO").borders(Borders::ALL);
+    let block = Block::default()
+        .title("TASK INFO")
+        .borders(Borders::ALL)
+        .border_style(state.theme.border);
     if let Some(task) = state.get_selected_task() {
-        let p = Paragraph::new(task.description.as_str())
-            .block(block)
-            .wrap(Wrap { trim: true });
+        let mut lines = Vec::new();
+        if !task.labels.is_empty() {
+            let mut chips = label_chips(state, task);
+            chips.remove(0);
+            lines.push(Spans::from(chips));
+        }
+        if let Some(due_date) = task.due_date {
+            let today = today();
+            lines.push(Spans::from(Span::styled(
+                format!(
+                    "Due {} ({})",
+                    due_date.format(DUE_DATE_FORMAT),
+                    describe_due_date(due_date, today)
+                ),
+                due_date_style(&state.theme, due_date, today),
+            )));
+        }
+        for (title, ids) in [("Blocked by", &task.blocked_by), ("Blocks", &task.blocks)] {
+            for (column, linked) in ids.iter().filter_map(|id| state.get_task_by_id(*id)) {
+                let mut style = Style::default();
+                if title == "Blocked by" && column.role != ColumnRole::Done {
+                    style = state.theme.blocked;
+                }
+                lines.push(Spans::from(Span::styled(
+                    format!("{title} #{} {} ({})", linked.id, linked.title, column.name),
+                    style,
+                )));
+            }
+        }
+        if let Some(progress) = checklist_progress(task) {
+            lines.push(Spans::from(vec![Span::raw("Checklist"), progress]));
+            for item in &task.checklist {
+                let check = if item.done { "[x]" } else { "[ ]" };
+                lines.push(Spans::from(format!("  {check} {}", item.text)));
+            }
+        }
+        lines.extend(task.description.lines().map(Spans::from));
+        let p = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
         f.render_widget(p, area);
     } else {
         let p = Paragraph::new("No tasks for this column").block(block);
@@ -72,6 +406,54 @@ fn draw_task_info<B: Backend>(f: &mut Frame<'_, B>, area: Rect, state: &State<'_
     }
 }
 
+/// Formats a Unix timestamp in the user's local time.
+pub(crate) fn format_timestamp(timestamp: i64) -> String {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map_or_else(String::new, |t| t.format("%Y-%m-%d %H:%M").to_string())
+}
+
+fn draw_task_history<B: Backend>(f: &mut Frame<'_, B>, area: Rect, state: &State<'_>) {
+    let block = Block::default()
+        .title("TASK HISTORY")
+        .borders(Borders::ALL)
+        .border_style(state.theme.border);
+    let Some(task) = state.get_selected_task() else {
+        f.render_widget(Paragraph::new("").block(block), area);
+        return;
+    };
+
+    let mut lines = vec![Spans::from(format!(
+        "Created:   {}",
+        format_timestamp(task.created_at)
+    ))];
+    lines.push(Spans::from(format!(
+        "Updated:   {}",
+        format_timestamp(task.updated_at)
+    )));
+    if let Some(completed_at) = task.completed_at {
+        lines.push(Spans::from(format!(
+            "Completed: {}",
+            format_timestamp(completed_at)
+        )));
+    }
+    lines.push(Spans::from(""));
+
+    // Newest first so the latest column change is always visible
+    let events = state.get_selected_task_history().unwrap_or_default();
+    for event in events.iter().rev() {
+        lines.push(Spans::from(format!(
+            "{} {}",
+            format_timestamp(event.created_at),
+            event.describe()
+        )));
+    }
+
+    let p = Paragraph::new(lines).block(block);
+    f.render_widget(p, area);
+}
+
 fn centered_rect_for_popup(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -98,11 +480,170 @@ fn centered_rect_for_popup(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
+/// The priority picker of the task form, which isn't a text input.
+fn draw_priority_field<B: Backend>(
+    f: &mut Frame<'_, B>,
+    area: Rect,
+    priority: Option<Priority>,
+    focus: TaskEditFocus,
+) {
+    let block = Block::default()
+        .title("Priority (Left/Right or 0-3, Backspace to clear)")
+        .borders(Borders::ALL);
+    let text = match priority {
+        Some(priority) => Span::styled(
+            format!("< {} >", priority.as_str()),
+            Style::default().fg(priority_color(priority)),
+        ),
+        None => Span::raw("< None >"),
+    };
+    let mut style = Style::default();
+    if let TaskEditFocus::Priority = focus {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    let p = Paragraph::new(Spans::from(text)).style(style).block(block);
+    f.render_widget(p, area);
+}
+
+/// The checklist of the task form, drawn on the right of `area` with
+/// the input of the item being typed at the bottom. Returns what's
+/// left of `area` for the description.
+fn draw_checklist_field<B: Backend>(
+    f: &mut Frame<'_, B>,
+    area: Rect,
+    task: &mut TaskState<'_>,
+) -> Rect {
+    let areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(area);
+    let area = areas[1];
+    let is_focused = matches!(task.focus, TaskEditFocus::Checklist);
+    let (done, total) = (
+        task.checklist.iter().filter(|item| item.done).count(),
+        task.checklist.len(),
+    );
+    let mut block = Block::default()
+        .title(format!("Checklist {done}/{total}"))
+        .borders(Borders::ALL);
+    if is_focused {
+        block = block.title(format!(
+            "Checklist {done}/{total} (a/e/d add/edit/delete | Space toggle | J/K move)"
+        ));
+    }
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    let mut list_area = inner_area;
+    if let Some(input) = &mut task.checklist_input {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(2)].as_ref())
+            .split(inner_area);
+        list_area = rows[0];
+        let title = if task.is_checklist_edit {
+            "Edit item (Enter/Esc)"
+        } else {
+            "New item (Enter/Esc)"
+        };
+        input.set_block(Block::default().title(title).borders(Borders::TOP));
+        input.set_cursor_line_style(Style::default());
+        input.set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_widget(input.widget(), rows[1]);
+    }
+
+    let items: Vec<ListItem<'_>> = task
+        .checklist
+        .iter()
+        .map(|ChecklistItem { text, done }| {
+            let check = if *done { "[x]" } else { "[ ]" };
+            ListItem::new(format!("{check} {text}"))
+        })
+        .collect();
+    let mut list = List::new(items);
+    let mut list_state = ListState::default();
+    if is_focused {
+        list = list
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+        list_state.select(Some(task.checklist_idx));
+    }
+    f.render_stateful_widget(list, list_area, &mut list_state);
+    areas[0]
+}
+
+/// The due date input of the task form.
+fn draw_due_date_field<B: Backend>(
+    f: &mut Frame<'_, B>,
+    area: Rect,
+    due_date: &mut TextArea<'_>,
+    focus: TaskEditFocus,
+) {
+    let block = Block::default()
+        .title("Due date (YYYY-MM-DD, today, tomorrow, +3d, +2w, fri)")
+        .borders(Borders::ALL);
+    due_date.set_block(block);
+    due_date.set_cursor_line_style(Style::default());
+    if let TaskEditFocus::DueDate = focus {
+        due_date.set_style(Style::default().add_modifier(Modifier::BOLD));
+        due_date.set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+    } else {
+        due_date.set_style(Style::default());
+        due_date.set_cursor_style(Style::default());
+    }
+    f.render_widget(due_date.widget(), area);
+}
+
+/// The Confirm and Cancel buttons of the task form, returned along
+/// with where they were drawn.
+fn draw_form_buttons<B: Backend>(
+    f: &mut Frame<'_, B>,
+    area: Rect,
+    focus: TaskEditFocus,
+) -> [(Button, Rect); 2] {
+    let buttons = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(80),
+                Constraint::Min(10),
+                Constraint::Min(10),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    let (create_style, cancel_style, create_txt, cancel_txt) = match focus {
+        TaskEditFocus::ConfirmBtn => (
+            Style::default().add_modifier(Modifier::BOLD),
+            Style::default(),
+            "[Confirm]",
+            " Cancel ",
+        ),
+        TaskEditFocus::CancelBtn => (
+            Style::default(),
+            Style::default().add_modifier(Modifier::BOLD),
+            " Confirm ",
+            "[Cancel]",
+        ),
+        _ => (Style::default(), Style::default(), " Confirm ", " Cancel "),
+    };
+
+    let create_btn = Paragraph::new(create_txt).style(create_style);
+    let cancel_btn = Paragraph::new(cancel_txt).style(cancel_style);
+    f.render_widget(create_btn, buttons[1]);
+    f.render_widget(cancel_btn, buttons[2]);
+    [
+        (Button::FormConfirm, buttons[1]),
+        (Button::FormCancel, buttons[2]),
+    ]
+}
+
 fn draw_task_popup<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>, popup_title: &str) {
     let area = centered_rect_for_popup(45, 60, f.size());
     let block = Block::default()
         .title(popup_title)
         .title_alignment(Alignment::Center)
+        .style(state.theme.popup)
         .borders(Borders::ALL);
     let block_inner = block.inner(area);
     f.render_widget(Clear, area);
@@ -114,6 +655,9 @@ fn draw_task_popup<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>, popu
                 [
                     Constraint::Length(3),
                     Constraint::Max(100),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
                     Constraint::Length(1),
                     Constraint::Length(2),
                 ]
@@ -121,44 +665,18 @@ fn draw_task_popup<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>, popu
             )
             .split(block_inner);
 
-        let buttons = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(
-                [
-                    Constraint::Percentage(80),
-                    Constraint::Min(10),
-                    Constraint::Min(10),
-                ]
-                .as_ref(),
-            )
-            .split(layout[2]);
-
-        let (create_style, cancel_style, create_txt, cancel_txt) = match task.focus {
-            TaskEditFocus::ConfirmBtn => (
-                Style::default().add_modifier(Modifier::BOLD),
-                Style::default(),
-                "[Confirm]",
-                " Cancel ",
-            ),
-            TaskEditFocus::CancelBtn => (
-                Style::default(),
-                Style::default().add_modifier(Modifier::BOLD),
-                " Confirm ",
-                "[Cancel]",
-            ),
-            _ => (Style::default(), Style::default(), " Confirm ", " Cancel "),
-        };
-
-        let create_btn = Paragraph::new(create_txt).style(create_style);
-        let cancel_btn = Paragraph::new(cancel_txt).style(cancel_style);
-        f.render_widget(create_btn, buttons[1]);
-        f.render_widget(cancel_btn, buttons[2]);
+        let buttons = draw_form_buttons(f, layout[5], task.focus);
+        state.click_areas.buttons.extend(buttons);
 
         let b1 = Block::default().title("Title").borders(Borders::ALL);
         let b2 = Block::default().title("Description").borders(Borders::ALL);
         let b3 = Block::default().title("Keys").borders(Borders::TOP);
+        let b4 = Block::default()
+            .title("Labels (comma separated)")
+            .borders(Borders::ALL);
         task.title.set_cursor_line_style(Style::default());
         task.description.set_cursor_line_style(Style::default());
+        task.labels.set_cursor_line_style(Style::default());
 
         task.title.set_block(b1);
         if let TaskEditFocus::Title = task.focus {
@@ -182,10 +700,100 @@ fn draw_task_popup<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>, popu
             task.description.set_style(Style::default());
             task.description.set_cursor_style(Style::default());
         }
-        f.render_widget(task.description.widget(), layout[1]);
+        let description_area = draw_checklist_field(f, layout[1], task);
+        f.render_widget(task.description.widget(), description_area);
 
-        let footer = Paragraph::new("Tab/Backtab : Cycle").block(b3);
-        f.render_widget(footer, layout[3]);
+        task.labels.set_block(b4);
+        if let TaskEditFocus::Labels = task.focus {
+            task.labels
+                .set_style(Style::default().add_modifier(Modifier::BOLD));
+            task.labels
+                .set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+        } else {
+            task.labels.set_style(Style::default());
+            task.labels.set_cursor_style(Style::default());
+        }
+        f.render_widget(task.labels.widget(), layout[2]);
+
+        draw_priority_field(f, layout[3], task.priority, task.focus);
+        draw_due_date_field(f, layout[4], &mut task.due_date, task.focus);
+
+        let keys = state.keymap.footer(&[
+            ("cycle fields", &[Action::NextField, Action::PreviousField]),
+            ("edit in $EDITOR", &[Action::OpenEditor]),
+        ]);
+        let footer = Paragraph::new(keys).block(b3);
+        f.render_widget(footer, layout[6]);
+    }
+}
+
+fn draw_column_popup<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>) {
+    let target = state
+        .get_column_delete_target()
+        .map_or("", |idx| state.columns[idx].name.as_str())
+        .to_string();
+    let Some(column_edit) = &mut state.column_edit_state else {
+        return;
+    };
+    let column = &state.columns[state.selected_column_idx];
+    match column_edit.mode {
+        ColumnEditMode::Browse => {}
+        ColumnEditMode::Create | ColumnEditMode::Rename | ColumnEditMode::WipLimit => {
+            let (title, input_title) = match column_edit.mode {
+                ColumnEditMode::Create => ("New Column", "Name"),
+                ColumnEditMode::WipLimit => ("WIP Limit", "Most tasks, empty for none"),
+                _ => ("Rename Column", "Name"),
+            };
+            let area = centered_rect_for_popup(40, 20, f.size());
+            let block = Block::default()
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .style(state.theme.popup)
+                .borders(Borders::ALL);
+            let block_inner = block.inner(area);
+            f.render_widget(Clear, area);
+            f.render_widget(block, area);
+
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Length(1)].as_ref())
+                .split(block_inner);
+
+            let name = &mut column_edit.name;
+            name.set_block(Block::default().title(input_title).borders(Borders::ALL));
+            name.set_cursor_line_style(Style::default());
+            name.set_style(Style::default().add_modifier(Modifier::BOLD));
+            name.set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+            f.render_widget(name.widget(), layout[0]);
+            f.render_widget(Paragraph::new("Enter : Confirm | Esc : Cancel"), layout[1]);
+        }
+        ColumnEditMode::ConfirmDelete => {
+            let area = centered_rect_for_popup(40, 20, f.size());
+            let block = Block::default()
+                .title("Delete Column")
+                .title_alignment(Alignment::Center)
+                .style(state.theme.popup)
+                .borders(Borders::ALL);
+            let mut lines = vec![Spans::from(format!("Delete column \"{}\"?", column.name))];
+            if column.tasks.is_empty() {
+                lines.push(Spans::from(""));
+                lines.push(Spans::from("y : Delete | n : Cancel"));
+            } else {
+                lines.push(Spans::from(format!(
+                    "It has {} task(s), what should happen to them?",
+                    column.tasks.len()
+                )));
+                lines.push(Spans::from(""));
+                lines.push(Spans::from(format!(
+                    "m : Move them to \"{target}\" | d : Trash them | n : Cancel"
+                )));
+            }
+            let p = Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: true });
+            f.render_widget(Clear, area);
+            f.render_widget(p, area);
+        }
     }
 }
 
@@ -193,27 +801,112 @@ fn draw_task_popup<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>, popu
 fn draw_project_stats<B: Backend>(f: &mut Frame<'_, B>, area: Rect, state: &mut State<'_>) {
     let block = Block::default()
         .title("PROJECT STATS")
-        .borders(Borders::ALL);
+        .borders(Borders::ALL)
+        .border_style(state.theme.border);
 
-    let c1_len = state.columns[0].tasks.len();
-    let c2_len = state.columns[1].tasks.len();
-    let c3_len = state.columns[2].tasks.len();
-    let c4_len = state.columns[3].tasks.len();
-    let tocomplete_total = c1_len + c2_len + c3_len;
-    let percentage = (c3_len as f32 / tocomplete_total as f32 * 100.0) as i8;
-    let list = List::new(vec![ListItem::new(vec![
-        Spans::from("Tasks per Column:"),
-        Spans::from(format!("  Todo ({c1_len})")),
-        Spans::from(format!("  In Progress ({c2_len})")),
-        Spans::from(format!("  Done ({c3_len})")),
-        Spans::from(format!("  Ideas ({c4_len})")),
-        Spans::from(format!("Progress: {c3_len} / {tocomplete_total} - {percentage}%")),
-    ])])
-    .block(block);
+    let (done, tocomplete_total) = state.get_progress();
+    let percentage = if tocomplete_total == 0 {
+        0
+    } else {
+        (done as f32 / tocomplete_total as f32 * 100.0) as i8
+    };
+    let mut lines = vec![Spans::from("Tasks per Column:")];
+    lines.extend(
+        state
+            .columns
+            .iter()
+            .map(|c| Spans::from(format!("  {} ({})", c.name, c.tasks.len()))),
+    );
+    lines.push(Spans::from(format!(
+        "Progress: {done} / {tocomplete_total} - {percentage}%"
+    )));
+    lines.push(Spans::from("Flow metrics: m"));
+    let list = List::new(vec![ListItem::new(lines)]).block(block);
 
     f.render_widget(list, area);
 }
 
+fn draw_flow_metrics<B: Backend>(f: &mut Frame<'_, B>, state: &State<'_>) {
+    let Some(metrics) = &state.flow_metrics else {
+        return;
+    };
+    let duration = |d: Option<i64>| d.map_or_else(|| String::from("-"), format_duration);
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Spans::from(Span::styled("Time spent per column (average / median)", bold)),
+    ];
+    for column in &metrics.cycle_times {
+        lines.push(Spans::from(format!(
+            "  {:<20} {:>8} / {:<8} ({} moves)",
+            column.name,
+            duration(column.average),
+            duration(column.median),
+            column.samples
+        )));
+    }
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled("Lead time, created to done", bold)));
+    lines.push(Spans::from(format!(
+        "  average {} | median {}",
+        duration(metrics.lead_time_average),
+        duration(metrics.lead_time_median)
+    )));
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled("Tasks completed per week", bold)));
+    for (week, count) in metrics.weekly_throughput.iter().enumerate() {
+        let label = match week {
+            0 => String::from("last 7 days"),
+            1 => String::from("1 week ago"),
+            n => format!("{n} weeks ago"),
+        };
+        lines.push(Spans::from(format!("  {label:<12} {count}")));
+    }
+
+    let area = centered_rect_for_popup(60, 60, f.size());
+    let block = Block::default()
+        .title("FLOW METRICS")
+        .title_alignment(Alignment::Center)
+        .style(state.theme.popup)
+        .borders(Borders::ALL);
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Replaces the keybindings with the search input while typing, or
+/// with a summary of the search once the filter is applied.
+fn draw_search_bar<B: Backend>(
+    f: &mut Frame<'_, B>,
+    area: Rect,
+    search: &mut SearchState<'_>,
+    keymap: &Keymap,
+    theme: &Theme,
+) {
+    let block = Block::default()
+        .title("SEARCH")
+        .borders(Borders::TOP)
+        .border_style(theme.border);
+    if search.is_typing {
+        search.query.set_block(block);
+        f.render_widget(search.query.widget(), area);
+        return;
+    }
+    let matches = search.matches.as_ref().map_or(0, HashSet::len);
+    let keys = keymap.footer(&[
+        (
+            "next/prev match",
+            &[Action::NextMatch, Action::PreviousMatch],
+        ),
+        ("edit search", &[Action::Search]),
+        ("clear search", &[Action::ClearSearch]),
+    ]);
+    let text = format!(
+        "\"{}\" : {matches} match(es) | {keys}",
+        search.query.lines().join(" ")
+    );
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
 /// Macro to generate the app's keybindings string at compile time
 macro_rules! unroll {
     (($first_a:literal, $first_b:literal), $(($a:literal, $b:literal)),*) => {
@@ -221,55 +914,629 @@ macro_rules! unroll {
     };
 }
 
+/// Entries of the board's footer, generated from the active keymap.
+const MAIN_FOOTER: &[(&str, &[Action])] = &[
+    ("quit", &[Action::Quit]),
+    ("help", &[Action::Help]),
+    (
+        "navigation",
+        &[
+            Action::SelectColumnLeft,
+            Action::SelectTaskBelow,
+            Action::SelectTaskAbove,
+            Action::SelectColumnRight,
+        ],
+    ),
+    (
+        "move task",
+        &[
+            Action::MoveTaskLeft,
+            Action::MoveTaskDown,
+            Action::MoveTaskUp,
+            Action::MoveTaskRight,
+        ],
+    ),
+    ("new task", &[Action::NewTask]),
+    ("edit task", &[Action::EditTask]),
+    ("edit in $EDITOR", &[Action::EditInEditor]),
+    ("cycle edit fields", &[Action::NextField]),
+    ("column top", &[Action::SelectFirstTask]),
+    ("column bottom", &[Action::SelectLastTask]),
+    ("manage columns", &[Action::ManageColumns]),
+    ("boards", &[Action::Boards]),
+    ("undo/redo", &[Action::Undo, Action::Redo]),
+    ("search", &[Action::Search]),
+    ("filter labels", &[Action::FilterLabels]),
+    (
+        "priority",
+        &[
+            Action::SetPriority0,
+            Action::SetPriority1,
+            Action::SetPriority2,
+            Action::SetPriority3,
+        ],
+    ),
+    ("sort by priority", &[Action::SortByPriority]),
+    ("blockers", &[Action::Blockers]),
+    ("archive task", &[Action::ArchiveTask]),
+    ("archived tasks", &[Action::OpenArchive]),
+    ("trash", &[Action::OpenTrash]),
+    ("agenda", &[Action::Agenda]),
+    ("flow metrics", &[Action::FlowMetrics]),
+    ("toggle panels", &[Action::TogglePanels]),
+    ("card view", &[Action::ToggleCardView]),
+    (
+        "export md/org",
+        &[Action::ExportMarkdown, Action::ExportOrg],
+    ),
+];
+
+/// The footer of the compact mode, only what's needed to find the
+/// rest.
+const COMPACT_FOOTER: &[(&str, &[Action])] = &[
+    ("quit", &[Action::Quit]),
+    ("help", &[Action::Help]),
+    (
+        "move task",
+        &[
+            Action::MoveTaskLeft,
+            Action::MoveTaskDown,
+            Action::MoveTaskUp,
+            Action::MoveTaskRight,
+        ],
+    ),
+    ("new task", &[Action::NewTask]),
+];
+
+/// Lists every action with all of its keys, grouped by
+/// [`KeyContext`].
+fn draw_help<B: Backend>(f: &mut Frame<'_, B>, state: &State<'_>) {
+    let Some(help) = &state.help else {
+        return;
+    };
+    let mut lines = Vec::new();
+    for context in KeyContext::ALL {
+        if !lines.is_empty() {
+            lines.push(Spans::from(""));
+        }
+        lines.push(Spans::from(Span::styled(
+            context.title(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        for action in Action::all().filter(|a| a.context() == context) {
+            let keys = state.keymap.describe_keys(action);
+            let keys = if keys.is_empty() {
+                String::from("(unbound)")
+            } else {
+                keys
+            };
+            lines.push(Spans::from(format!(
+                "  {keys:<14} {}",
+                action.description()
+            )));
+        }
+    }
+    if let Some(path) = Config::path() {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(Span::styled(
+            format!("Keys can be changed in {}", path.display()),
+            Style::default().add_modifier(Modifier::ITALIC),
+        )));
+    }
+
+    let area = centered_rect_for_popup(70, 80, f.size());
+    let block = Block::default()
+        .title("KEYBINDINGS (scroll: j/k | close: any other key)")
+        .title_alignment(Alignment::Center)
+        .style(state.theme.popup)
+        .borders(Borders::ALL);
+    let p = Paragraph::new(lines).block(block).scroll((help.scroll, 0));
+    f.render_widget(Clear, area);
+    f.render_widget(p, area);
+}
+
+fn draw_agenda<B: Backend>(f: &mut Frame<'_, B>, state: &State<'_>) {
+    let Some(agenda) = &state.agenda else {
+        return;
+    };
+    let today = today();
+    let items: Vec<ListItem<'_>> = state
+        .get_agenda()
+        .into_iter()
+        .map(|(col_idx, task_idx)| {
+            let column = &state.columns[col_idx];
+            let task = &column.tasks[task_idx];
+            let (date, relative, style) = match task.due_date {
+                Some(due_date) if column.role == ColumnRole::Done => (
+                    due_date.format(DUE_DATE_FORMAT).to_string(),
+                    String::from("done"),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+                Some(due_date) => (
+                    due_date.format(DUE_DATE_FORMAT).to_string(),
+                    describe_due_date(due_date, today),
+                    due_date_style(&state.theme, due_date, today),
+                ),
+                None => (String::from("-"), String::new(), Style::default()),
+            };
+            let mut spans = vec![Span::styled(format!("{date:<11} {relative:<18}"), style)];
+            if let Some(priority) = task.priority {
+                spans.push(Span::styled(
+                    format!("[{}] ", priority.as_str()),
+                    Style::default().fg(priority_color(priority)),
+                ));
+            }
+            spans.push(Span::styled(task.title.as_str(), style));
+            spans.push(Span::styled(
+                format!("  {}", column.name),
+                Style::default().add_modifier(Modifier::ITALIC),
+            ));
+            ListItem::new(Spans::from(spans))
+        })
+        .collect();
+
+    let area = centered_rect_for_popup(70, 70, f.size());
+    let block = Block::default()
+        .title("AGENDA (open: Enter | close: q)")
+        .title_alignment(Alignment::Center)
+        .style(state.theme.popup)
+        .borders(Borders::ALL);
+    f.render_widget(Clear, area);
+    if items.is_empty() {
+        f.render_widget(Paragraph::new("No tasks yet").block(block), area);
+        return;
+    }
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut list_state = ListState::default();
+    list_state.select(Some(agenda.selected_idx));
+    f.render_stateful_widget(list, area, &mut list_state);
+}
+
+fn draw_label_filter<B: Backend>(f: &mut Frame<'_, B>, state: &State<'_>) {
+    let Some(filter) = &state.label_filter_state else {
+        return;
+    };
+    let mut lines: Vec<Spans<'_>> = state
+        .labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| {
+            let check = if state.label_filter.contains(&label.name) {
+                "[x] "
+            } else {
+                "[ ] "
+            };
+            let mut style = Style::default();
+            if i == filter.selected_idx {
+                style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+            }
+            Spans::from(vec![
+                Span::styled(check, style),
+                label_chip(state, &label.name),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Spans::from("No labels yet, add some from the task form"));
+    }
+
+    let area = centered_rect_for_popup(40, 50, f.size());
+    let block = Block::default()
+        .title("FILTER BY LABEL")
+        .title_alignment(Alignment::Center)
+        .style(state.theme.popup)
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(2)].as_ref())
+        .split(inner);
+    f.render_widget(Paragraph::new(lines), layout[0]);
+    let keys = unroll![
+        ("toggle", "Space"),
+        ("clear", "x"),
+        ("color", "c"),
+        ("delete", "D"),
+        ("close", "q")
+    ];
+    let footer = Paragraph::new(keys)
+        .block(Block::default().borders(Borders::TOP))
+        .wrap(Wrap { trim: true });
+    f.render_widget(footer, layout[1]);
+}
+
+/// The popup listing every other task, the checked ones being the
+/// tasks blocking the selected one.
+fn draw_link_popup<B: Backend>(f: &mut Frame<'_, B>, state: &State<'_>) {
+    let (Some(link), Some(selected)) = (&state.link_state, state.get_selected_task()) else {
+        return;
+    };
+    let items: Vec<ListItem<'_>> = state
+        .get_link_candidates()
+        .into_iter()
+        .map(|(col_idx, task_idx)| {
+            let column = &state.columns[col_idx];
+            let task = &column.tasks[task_idx];
+            let check = if selected.blocked_by.contains(&task.id) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            ListItem::new(format!(
+                "{check} #{} {} ({})",
+                task.id, task.title, column.name
+            ))
+        })
+        .collect();
+
+    let area = centered_rect_for_popup(50, 60, f.size());
+    let block = Block::default()
+        .title(format!("TASKS BLOCKING \"{}\"", selected.title))
+        .title_alignment(Alignment::Center)
+        .style(state.theme.popup)
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(2)].as_ref())
+        .split(inner);
+    if items.is_empty() {
+        f.render_widget(Paragraph::new("No other tasks to link"), layout[0]);
+    } else {
+        let list = List::new(items)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED));
+        let mut list_state = ListState::default();
+        list_state.select(Some(link.selected_idx));
+        f.render_stateful_widget(list, layout[0], &mut list_state);
+    }
+    let keys = unroll![("toggle blocker", "Space"), ("close", "q")];
+    let footer = Paragraph::new(keys)
+        .block(Block::default().borders(Borders::TOP))
+        .wrap(Wrap { trim: true });
+    f.render_widget(footer, layout[1]);
+}
+
+/// The popup listing the boards of the database, the one being shown
+/// marked with `*`.
+fn draw_board_picker<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>) {
+    let Some(picker) = &mut state.board_picker else {
+        return;
+    };
+    let items: Vec<ListItem<'_>> = state
+        .boards
+        .iter()
+        .enumerate()
+        .map(|(i, board)| {
+            let marker = if i == state.selected_board_idx {
+                "* "
+            } else {
+                "  "
+            };
+            ListItem::new(format!("{marker}{}", board.name))
+        })
+        .collect();
+
+    let area = centered_rect_for_popup(40, 50, f.size());
+    let block = Block::default()
+        .title("BOARDS")
+        .title_alignment(Alignment::Center)
+        .style(state.theme.popup)
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let is_typing = picker.mode != BoardPickerMode::Browse;
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(1),
+                Constraint::Length(if is_typing { 3 } else { 0 }),
+                Constraint::Length(2),
+            ]
+            .as_ref(),
+        )
+        .split(inner);
+    let list = List::new(items)
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED));
+    let mut list_state = ListState::default();
+    list_state.select(Some(picker.selected_idx));
+    f.render_stateful_widget(list, layout[0], &mut list_state);
+
+    let keys = if is_typing {
+        let title = if picker.mode == BoardPickerMode::Create {
+            "New Board"
+        } else {
+            "Rename Board"
+        };
+        let name = &mut picker.name;
+        name.set_block(Block::default().title(title).borders(Borders::ALL));
+        name.set_cursor_line_style(Style::default());
+        name.set_style(Style::default().add_modifier(Modifier::BOLD));
+        name.set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_widget(name.widget(), layout[1]);
+        "Enter : Confirm | Esc : Cancel"
+    } else {
+        unroll![
+            ("open", "Enter"),
+            ("new", "n"),
+            ("rename", "r"),
+            ("delete", "D"),
+            ("close", "q")
+        ]
+    };
+    let footer = Paragraph::new(keys)
+        .block(Block::default().borders(Borders::TOP))
+        .wrap(Wrap { trim: true });
+    f.render_widget(footer, layout[2]);
+}
+
+/// The archive screen, a search input over the list of archived
+/// tasks, or of the deleted ones when it shows the trash.
+fn draw_archive<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>) {
+    let items: Vec<ListItem<'_>> = state
+        .get_archive_entries()
+        .into_iter()
+        .map(|archived| {
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    format!("{:<18} ", format_timestamp(archived.removed_at)),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+                Span::raw(archived.task.title.clone()),
+                Span::styled(
+                    format!("  {}", archived.column_name),
+                    Style::default().add_modifier(Modifier::ITALIC),
+                ),
+            ]))
+        })
+        .collect();
+    let Some(archive) = &mut state.archive else {
+        return;
+    };
+
+    let area = centered_rect_for_popup(70, 70, f.size());
+    let title = match archive.kind {
+        OffBoardKind::Archived => "ARCHIVE",
+        OffBoardKind::Deleted => "TRASH",
+    };
+    let block = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .style(state.theme.popup)
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Min(1),
+                Constraint::Length(2),
+            ]
+            .as_ref(),
+        )
+        .split(inner);
+    let query = &mut archive.query;
+    query.set_block(Block::default().title("Search").borders(Borders::ALL));
+    query.set_cursor_line_style(Style::default());
+    query.set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+    f.render_widget(query.widget(), layout[0]);
+
+    if items.is_empty() {
+        let text = if !archive.tasks.is_empty() {
+            "No matching tasks"
+        } else if archive.kind == OffBoardKind::Archived {
+            "No archived tasks"
+        } else {
+            "The trash is empty"
+        };
+        f.render_widget(Paragraph::new(text), layout[1]);
+    } else {
+        let list = List::new(items)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED));
+        let mut list_state = ListState::default();
+        list_state.select(Some(archive.selected_idx));
+        f.render_stateful_widget(list, layout[1], &mut list_state);
+    }
+    let keys = unroll![
+        ("navigation", "Up/Down"),
+        ("restore", "Enter"),
+        ("close", "Esc")
+    ];
+    let footer = Paragraph::new(keys)
+        .block(Block::default().borders(Borders::TOP))
+        .wrap(Wrap { trim: true });
+    f.render_widget(footer, layout[2]);
+}
+
+fn draw_confirm_popup<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>) {
+    let Some(confirm) = &state.confirm else {
+        return;
+    };
+    let area = centered_rect_for_popup(40, 20, f.size());
+    let block = Block::default()
+        .title("Are you sure?")
+        .title_alignment(Alignment::Center)
+        .style(state.theme.popup)
+        .borders(Borders::ALL);
+    let p = Paragraph::new(confirm.message.as_str())
+        .block(block)
+        .wrap(Wrap { trim: true });
+    f.render_widget(Clear, area);
+    f.render_widget(p, area);
+
+    // The bottom row of the popup holds the clickable answers
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    let row = Rect {
+        y: inner.bottom().saturating_sub(1),
+        height: inner.height.min(1),
+        ..inner
+    };
+    let buttons = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Min(0),
+                Constraint::Length(9),
+                Constraint::Length(2),
+                Constraint::Length(8),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(row);
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    f.render_widget(Paragraph::new("[Yes (y)]").style(bold), buttons[1]);
+    f.render_widget(Paragraph::new("[No (n)]").style(bold), buttons[3]);
+    state.click_areas.buttons.extend([
+        (Button::ConfirmYes, buttons[1]),
+        (Button::ConfirmNo, buttons[3]),
+    ]);
+}
+
+/// Draws whichever popups are open on top of the board, the
+/// confirmation last since it has to be answered first.
+fn draw_popups<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>) {
+    if state.task_edit_state.is_some() {
+        draw_task_popup(f, state, "Create Task");
+    }
+    if state.column_edit_state.is_some() {
+        draw_column_popup(f, state);
+    }
+    if state.flow_metrics.is_some() {
+        draw_flow_metrics(f, state);
+    }
+    if state.label_filter_state.is_some() {
+        draw_label_filter(f, state);
+    }
+    if state.agenda.is_some() {
+        draw_agenda(f, state);
+    }
+    if state.link_state.is_some() {
+        draw_link_popup(f, state);
+    }
+    if state.board_picker.is_some() {
+        draw_board_picker(f, state);
+    }
+    if state.archive.is_some() {
+        draw_archive(f, state);
+    }
+    if state.help.is_some() {
+        draw_help(f, state);
+    }
+    if state.confirm.is_some() {
+        draw_confirm_popup(f, state);
+    }
+}
+
 /// Takes the app's [`State`] so [ratatui][`tui`] can render it to the
 /// terminal screen
 pub fn draw_ui_from_state<B: Backend>(f: &mut Frame<'_, B>, state: &mut State<'_>) {
+    let size = f.size();
+    // Small terminals give every row they can to the columns
+    let compact = state.layout.is_compact(size.width, size.height);
+    let show_panels = state.show_panels && !compact;
     let main_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Length(2),
-                Constraint::Min(10),
-                Constraint::Max(10),
+                Constraint::Length(if compact { 1 } else { 2 }),
+                Constraint::Min(3),
+                Constraint::Max(if show_panels { 10 } else { 0 }),
                 Constraint::Length(2),
             ]
             .as_ref(),
         )
-        .split(f.size());
+        .split(size);
 
     let block = Block::default()
-        .title(format!("⎸ {} ⎹", state.project_name))
+        .title(format!(
+            "⎸ {} · {} ⎹",
+            state.project_name,
+            state.get_selected_board().name
+        ))
         .title_alignment(Alignment::Center)
-        .borders(Borders::TOP);
+        .borders(Borders::TOP)
+        .border_style(state.theme.border);
     f.render_widget(block, main_layout[0]);
 
-    draw_tasks(f, main_layout[1], state);
+    let shown = state.layout.visible_columns(
+        state.columns.len(),
+        state.selected_column_idx,
+        state.column_offset,
+        main_layout[1].width,
+    );
+    state.column_offset = shown.start;
+    state.click_areas.buttons.clear();
+    state.click_areas.columns = draw_tasks(f, main_layout[1], state, shown);
 
-    let info_area = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![Constraint::Min(60), Constraint::Max(60)].as_ref())
-        .split(main_layout[2]);
-
-    draw_task_info(f, info_area[0], state);
-    draw_project_stats(f, info_area[1], state);
-
-    let block = Block::default().title("KEYBINDINGS").borders(Borders::TOP);
-
-    let foot_txt = unroll![
-        ("quit", "q"),
-        ("navigation", "hjkl"),
-        ("move task", "HJKL"),
-        ("new task", "n"),
-        ("edit task", "e"),
-        ("cycle edit fields", "Tab"),
-        ("column top", "g"),
-        ("column bottom", "G")
-    ];
+    if show_panels {
+        let info_area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Percentage(40),
+                    Constraint::Percentage(30),
+                    Constraint::Percentage(30),
+                ]
+                .as_ref(),
+            )
+            .split(main_layout[2]);
 
-    let footer = Paragraph::new(foot_txt).block(block);
-    f.render_widget(footer, main_layout[3]);
+        draw_task_info(f, info_area[0], state);
+        draw_task_history(f, info_area[1], state);
+        draw_project_stats(f, info_area[2], state);
+    }
 
-    if state.task_edit_state.is_some() {
-        draw_task_popup(f, state, "Create Task");
+    let block = Block::default()
+        .title("KEYBINDINGS")
+        .borders(Borders::TOP)
+        .border_style(state.theme.border);
+
+    let foot_txt = state
+        .keymap
+        .footer(if compact { COMPACT_FOOTER } else { MAIN_FOOTER });
+
+    let column_txt = unroll![
+        ("back", "q"),
+        ("navigation", "hl"),
+        ("move column", "HL"),
+        ("new column", "n"),
+        ("rename column", "r"),
+        ("cycle role", "t"),
+        ("wip limit", "w"),
+        ("delete column", "D")
+    ];
+
+    let foot_txt = if let Some(message) = &state.status_message {
+        message.as_str()
+    } else if state.column_edit_state.is_some() {
+        column_txt
+    } else {
+        &foot_txt
+    };
+    match &mut state.search {
+        Some(search) if state.status_message.is_none() => {
+            draw_search_bar(f, main_layout[3], search, &state.keymap, &state.theme);
+        }
+        _ => {
+            let footer = Paragraph::new(foot_txt).block(block);
+            f.render_widget(footer, main_layout[3]);
+        }
     }
+
+    draw_popups(f, state);
 }
@@ -0,0 +1,228 @@
+//! Undo and redo of the changes made to the board.
+
+use crate::app::Task;
+
+/// How many [`Operation`]s are kept, the oldest ones get dropped first.
+pub const UNDO_LIMIT: usize = 100;
+
+/// A change made to the tasks of the board, holding enough to both
+/// apply it and revert it. [`Column`][`crate::Column`]s are referred to
+/// by ID and tasks by their index in [`Column::tasks`][`crate::Column::tasks`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// `task` was inserted at `index`
+    AddTask {
+        column_id: i64,
+        index: usize,
+        task: Task,
+    },
+    /// `task` was removed from `index`
+    DeleteTask {
+        column_id: i64,
+        index: usize,
+        task: Task,
+    },
+    /// `task` was archived from `index`
+    ArchiveTask {
+        column_id: i64,
+        index: usize,
+        task: Task,
+    },
+    /// `task` came back from the archive at `index`
+    UnarchiveTask {
+        column_id: i64,
+        index: usize,
+        task: Task,
+    },
+    /// The text, labels and/or priority changed from `before` to
+    /// `after`
+    EditTask { before: Task, after: Task },
+    /// The tasks at `from` and `to` swapped places
+    ReorderTask {
+        column_id: i64,
+        from: usize,
+        to: usize,
+    },
+    /// A task left a column for another one
+    MoveTask {
+        task_id: i64,
+        from_column_id: i64,
+        from_index: usize,
+        to_column_id: i64,
+        to_index: usize,
+    },
+    /// Several operations done as a single change, in order
+    Batch(Vec<Operation>),
+}
+
+impl Operation {
+    /// The operation that reverts this one.
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        match self.clone() {
+            Operation::AddTask {
+                column_id,
+                index,
+                task,
+            } => Operation::DeleteTask {
+                column_id,
+                index,
+                task,
+            },
+            Operation::DeleteTask {
+                column_id,
+                index,
+                task,
+            } => Operation::AddTask {
+                column_id,
+                index,
+                task,
+            },
+            Operation::ArchiveTask {
+                column_id,
+                index,
+                task,
+            } => Operation::UnarchiveTask {
+                column_id,
+                index,
+                task,
+            },
+            Operation::UnarchiveTask {
+                column_id,
+                index,
+                task,
+            } => Operation::ArchiveTask {
+                column_id,
+                index,
+                task,
+            },
+            Operation::EditTask { before, after } => Operation::EditTask {
+                before: after,
+                after: before,
+            },
+            Operation::ReorderTask {
+                column_id,
+                from,
+                to,
+            } => Operation::ReorderTask {
+                column_id,
+                from: to,
+                to: from,
+            },
+            Operation::MoveTask {
+                task_id,
+                from_column_id,
+                from_index,
+                to_column_id,
+                to_index,
+            } => Operation::MoveTask {
+                task_id,
+                from_column_id: to_column_id,
+                from_index: to_index,
+                to_column_id: from_column_id,
+                to_index: from_index,
+            },
+            Operation::Batch(operations) => {
+                Operation::Batch(operations.iter().rev().map(Operation::inverse).collect())
+            }
+        }
+    }
+
+    /// Short human readable description, like `delete task`.
+    #[must_use]
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Operation::AddTask { .. } => "add task",
+            Operation::DeleteTask { .. } => "delete task",
+            Operation::ArchiveTask { .. } => "archive task",
+            Operation::UnarchiveTask { .. } => "unarchive task",
+            Operation::EditTask { .. } => "edit task",
+            Operation::ReorderTask { .. } => "reorder task",
+            Operation::MoveTask { .. } => "move task",
+            Operation::Batch(operations) => {
+                operations.first().map_or("change", Operation::describe)
+            }
+        }
+    }
+}
+
+/// The undo and redo stacks. Recording a new [`Operation`] clears the
+/// redo stack, like in any editor.
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+    /// The operations recorded since [`History::start_batch`]
+    batch: Option<Vec<Operation>>,
+}
+
+impl History {
+    /// Saves an [`Operation`] that was just applied to the board.
+    pub fn record(&mut self, operation: Operation) {
+        if let Some(batch) = &mut self.batch {
+            batch.push(operation);
+            return;
+        }
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(operation);
+        self.redo.clear();
+    }
+
+    /// Groups the operations recorded until
+    /// [`finish_batch`][`History::finish_batch`] so they are undone
+    /// and redone together.
+    pub fn start_batch(&mut self) {
+        self.batch = Some(Vec::new());
+    }
+
+    /// Records the operations grouped since
+    /// [`start_batch`][`History::start_batch`], as a single one if
+    /// there are several of them.
+    pub fn finish_batch(&mut self) {
+        let Some(mut operations) = self.batch.take() else {
+            return;
+        };
+        match operations.len() {
+            0 => {}
+            1 => self.record(operations.remove(0)),
+            _ => self.record(Operation::Batch(operations)),
+        }
+    }
+
+    /// Forgets everything, used when the board changes in a way the
+    /// recorded operations can't be replayed against anymore.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Takes the last applied [`Operation`], which then becomes
+    /// redoable.
+    pub fn pop_undo(&mut self) -> Option<Operation> {
+        let operation = self.undo.pop()?;
+        self.redo.push(operation.clone());
+        Some(operation)
+    }
+
+    /// Takes the last undone [`Operation`], which then becomes
+    /// undoable again.
+    pub fn pop_redo(&mut self) -> Option<Operation> {
+        let operation = self.redo.pop()?;
+        self.undo.push(operation.clone());
+        Some(operation)
+    }
+
+    /// Returns `true` if there is something to undo.
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Returns `true` if there is something to redo.
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
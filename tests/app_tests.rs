@@ -1,23 +1,10 @@
+mod common;
+
 #[cfg(test)]
 mod app_tests {
+    use super::common::create_connection;
     use anyhow::{Error};
-    use kanban_tui::State;
-    use rusqlite::Connection;
-
-    fn create_connection() -> Result<Connection, Error> {
-        let mut conn = Connection::open_in_memory()?;
-
-        let migrations = include_str!("../sql/migrations.sql");
-        let migrations: Vec<&str> = migrations.split(';').collect();
-        let tx = conn.transaction()?;
-        for m in migrations {
-            if !m.trim().is_empty() {
-                tx.execute(m, ())?;
-            }
-        }
-        tx.commit()?;
-        Ok(conn)
-    }
+    use kanban_tui::{ColumnRole, State, TaskEventKind};
 
     #[test]
     fn it_adds_tasks_to_different_columns() -> Result<(), Error> {
@@ -317,4 +304,215 @@ mod app_tests {
 
         Ok(())
     }
+
+    #[test]
+    fn it_adds_renames_moves_and_deletes_columns() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+
+        state.add_column(String::from("Review"))?;
+        assert_eq!(state.columns.len(), 5);
+        assert_eq!(state.get_selected_column().name, "Review");
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.move_column_left()?;
+        state.move_column_left()?;
+        state.move_column_left()?;
+        assert_eq!(state.columns[1].name, "Review");
+        assert_eq!(state.get_selected_column().name, "Review");
+        state.rename_column(String::from("Code Review"))?;
+        for _ in 0..5 {
+            state.move_column_left()?;
+        }
+        assert_eq!(state.columns[0].name, "Code Review");
+        state.move_column_right()?;
+        assert_eq!(state.columns[0].name, "Todo");
+        assert_eq!(state.columns[1].name, "Code Review");
+        assert_eq!(state.get_selected_task().unwrap().title, "T1");
+
+        // Reload the data from the database then rerun the asserts to
+        // make sure everything was saved correctly
+        let mut state = State::new(state.db_conn.0)?;
+
+        let names: Vec<&str> = state.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Todo", "Code Review", "InProgress", "Done", "Ideas"]);
+        assert_eq!(state.get_selected_column().name, "Code Review");
+        assert_eq!(state.get_selected_task().unwrap().title, "T1");
+
+        state.select_column_left()?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.select_column_right()?;
+        state.delete_column(true)?;
+        assert_eq!(state.columns.len(), 4);
+        assert_eq!(state.get_selected_column().name, "Todo");
+        assert_eq!(state.columns[0].tasks.len(), 2);
+        assert_eq!(&state.columns[0].tasks[1].title, "T1");
+
+        state.delete_column(false)?;
+        assert_eq!(state.columns.len(), 3);
+        assert_eq!(state.get_selected_column().name, "InProgress");
+        state.delete_column(false)?;
+        state.delete_column(false)?;
+        state.delete_column(false)?;
+        assert_eq!(state.columns.len(), 1);
+        assert_eq!(state.get_selected_column().name, "Ideas");
+
+        let state = State::new(state.db_conn.0)?;
+
+        assert_eq!(state.columns.len(), 1);
+        assert_eq!(state.get_selected_column().name, "Ideas");
+        assert!(state.get_selected_task().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_moves_only_the_live_tasks_of_a_deleted_column() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        // Ideas becomes a second done column next to Done
+        for _ in 0..3 {
+            state.select_column_right()?;
+        }
+        while state.get_selected_column().role != ColumnRole::Done {
+            state.cycle_column_role()?;
+        }
+        for title in ["T1", "T2"] {
+            state.select_column_left()?;
+            state.select_column_left()?;
+            state.select_column_left()?;
+            state.select_first_task()?;
+            assert_eq!(state.get_selected_task().unwrap().title, title);
+            for _ in 0..3 {
+                state.move_task_column_right()?;
+            }
+        }
+        let t2_id = state.get_selected_task().unwrap().id;
+        state.delete_task()?;
+        state
+            .db_conn
+            .execute("update task set completed_at = 1000", ())?;
+
+        state.delete_column(true)?;
+        let task = state.get_selected_column().tasks.last().unwrap();
+        assert_eq!(task.title, "T1");
+        // Done to done, so still completed when it was
+        assert_eq!(task.completed_at, Some(1000));
+        let history = state.db_conn.get_task_events(t2_id)?;
+        assert_eq!(history.last().unwrap().kind, TaskEventKind::Deleted);
+        let trashed = state.db_conn.get_trashed_tasks(1)?;
+        assert_eq!(trashed[0].task.completed_at, Some(1000));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_records_timestamps_and_task_history() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        let task = state.get_selected_task().unwrap();
+        let task_id = task.id;
+        assert!(task.created_at > 0);
+        assert_eq!(task.created_at, task.updated_at);
+        assert!(task.completed_at.is_none());
+
+        state.edit_task(String::from("T1"), String::from("D2"))?;
+        state.move_task_column_right()?;
+        state.move_task_column_right()?;
+        assert_eq!(state.get_selected_column().name, "Done");
+        assert!(state.get_selected_task().unwrap().completed_at.is_some());
+
+        let history = state.get_selected_task_history()?;
+        let kinds: Vec<TaskEventKind> = history.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TaskEventKind::Created,
+                TaskEventKind::Edited,
+                TaskEventKind::Moved,
+                TaskEventKind::Moved
+            ]
+        );
+        let columns: Vec<&str> = history
+            .iter()
+            .map(|e| e.column_name.as_deref().unwrap())
+            .collect();
+        assert_eq!(columns, ["Todo", "Todo", "InProgress", "Done"]);
+
+        // Reload the data from the database then rerun the asserts to
+        // make sure everything was saved correctly
+        let mut state = State::new(state.db_conn.0)?;
+
+        assert!(state.get_selected_task().unwrap().completed_at.is_some());
+        state.move_task_column_right()?;
+        assert!(state.get_selected_task().unwrap().completed_at.is_none());
+        state.delete_task()?;
+
+        let history = state.db_conn.get_task_events(task_id)?;
+        assert_eq!(history.len(), 6);
+        assert_eq!(history[5].kind, TaskEventKind::Deleted);
+        assert_eq!(history[5].column_name.as_deref(), Some("Ideas"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_uses_column_roles_for_progress() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+
+        let roles: Vec<ColumnRole> = state.columns.iter().map(|c| c.role).collect();
+        assert_eq!(
+            roles,
+            [
+                ColumnRole::Backlog,
+                ColumnRole::Active,
+                ColumnRole::Done,
+                ColumnRole::Parked
+            ]
+        );
+        assert_eq!(state.get_progress(), (0, 0));
+
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.move_task_column_right()?;
+        state.move_task_column_right()?;
+        state.select_column_right()?;
+        state.add_new_task(String::from("T3"), String::from("D3"))?;
+        assert_eq!(state.get_progress(), (1, 2));
+
+        // Renaming doesn't matter, only the role does
+        state.select_column_left()?;
+        state.rename_column(String::from("Shipped"))?;
+        assert!(state.get_selected_task().unwrap().completed_at.is_some());
+        state.select_column_right()?;
+        state.cycle_column_role()?;
+        assert_eq!(state.get_selected_column().role, ColumnRole::Backlog);
+        assert_eq!(state.get_progress(), (1, 3));
+
+        state.select_column_left()?;
+        state.delete_column(true)?;
+        state.add_column(String::from("Review"))?;
+        assert_eq!(state.get_selected_column().role, ColumnRole::Active);
+        assert_eq!(state.get_progress(), (0, 3));
+
+        // Reload the data from the database then rerun the asserts to
+        // make sure everything was saved correctly
+        let mut state = State::new(state.db_conn.0)?;
+
+        assert_eq!(state.columns.len(), 4);
+        assert_eq!(state.columns[2].role, ColumnRole::Backlog);
+        assert_eq!(state.columns[3].role, ColumnRole::Active);
+        assert_eq!(state.get_progress(), (0, 3));
+        state.cycle_column_role()?;
+        assert_eq!(state.get_progress(), (0, 3));
+        state.cycle_column_role()?;
+        assert_eq!(state.get_progress(), (0, 3));
+        state.select_column_left()?;
+        state.cycle_column_role()?;
+        state.cycle_column_role()?;
+        assert_eq!(state.get_selected_column().role, ColumnRole::Done);
+        assert_eq!(state.get_progress(), (1, 3));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,115 @@
+mod common;
+
+#[cfg(test)]
+mod archive_tests {
+    use super::common::{create_connection, run, titles};
+    use anyhow::Error;
+    use kanban_tui::{
+        export_board, import_board, render_markdown, Command, ImportMode, OffBoardKind, State,
+    };
+
+    #[test]
+    fn it_archives_and_restores_tasks() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.add_new_task(String::from("T3"), String::from("D3"))?;
+        state.select_task_above()?;
+
+        state.archive_task()?;
+        assert_eq!(titles(&state, 0), ["T1", "T3"]);
+        state.undo()?;
+        assert_eq!(titles(&state, 0), ["T1", "T2", "T3"]);
+        state.redo()?;
+        state.select_first_task()?;
+        state.archive_task()?;
+
+        // Archived tasks stay off the board after a reload
+        let mut state = State::new(state.db_conn.0)?;
+        assert_eq!(titles(&state, 0), ["T3"]);
+
+        state.open_archive(OffBoardKind::Archived)?;
+        // Both were archived within the same second so the order
+        // isn't tested here
+        let mut archived: Vec<&str> = state
+            .get_archive_entries()
+            .iter()
+            .map(|a| a.task.title.as_str())
+            .collect();
+        archived.sort_unstable();
+        assert_eq!(archived, ["T1", "T2"]);
+        assert_eq!(state.get_archive_entries()[0].column_name, "Todo");
+
+        state.archive.as_mut().unwrap().query.insert_str("D2");
+        state.update_archive_search()?;
+        assert_eq!(state.get_archive_entries().len(), 1);
+        state.restore_off_board_task()?;
+        assert_eq!(titles(&state, 0), ["T3", "T2"]);
+        assert_eq!(state.get_selected_task().unwrap().title, "T2");
+        assert!(state.get_archive_entries().is_empty());
+
+        // Undoing the restore puts it back in the archive
+        state.undo()?;
+        assert_eq!(titles(&state, 0), ["T3"]);
+        assert_eq!(state.db_conn.get_archived_tasks(1)?.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_archives_old_done_tasks_and_keeps_them_in_exports() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("Old"), String::new())?;
+        state.add_new_task(String::from("Recent"), String::new())?;
+        state.add_new_task(String::from("Todo"), String::new())?;
+        for _ in 0..2 {
+            state.select_first_task()?;
+            state.move_task_column_right()?;
+            state.move_task_column_right()?;
+            state.select_column_left()?;
+            state.select_column_left()?;
+        }
+        state.db_conn.execute(
+            "update task set completed_at = completed_at - 10 * 86400 where title = 'Old'",
+            (),
+        )?;
+
+        let archive = || Command::Archive {
+            id: None,
+            done_older_than: Some(7),
+        };
+        let archived: serde_json::Value =
+            serde_json::from_str(&run(&mut state.db_conn, archive(), true)?)?;
+        assert_eq!(archived, serde_json::json!({ "archived": 1, "ids": [1] }));
+        assert_eq!(
+            run(&mut state.db_conn, archive(), false)?,
+            "Archived 0 task(s)\n"
+        );
+
+        let board = export_board(&state.db_conn, 1)?;
+        let done: Vec<&str> = board.columns[2]
+            .tasks
+            .iter()
+            .map(|t| t.title.as_str())
+            .collect();
+        assert_eq!(done, ["Recent", "Old"]);
+        assert!(!render_markdown(&board).contains("Old"));
+
+        // The import keeps them archived
+        let db_conn = State::new(create_connection()?)?.db_conn;
+        import_board(&db_conn, 1, &board, ImportMode::Replace)?;
+        let state = State::new(db_conn.0)?;
+        assert_eq!(titles(&state, 2), ["Recent"]);
+        assert_eq!(state.db_conn.get_archived_tasks(1)?[0].task.title, "Old");
+
+        let mut db_conn = state.db_conn;
+        let unarchive = |id| Command::Unarchive { id };
+        let id = db_conn.get_archived_tasks(1)?[0].task.id;
+        run(&mut db_conn, unarchive(id), false)?;
+        assert!(run(&mut db_conn, unarchive(id), false).is_err());
+        let state = State::new(db_conn.0)?;
+        assert_eq!(titles(&state, 2), ["Recent", "Old"]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+mod common;
+
+#[cfg(test)]
+mod board_tests {
+    use super::common::{create_connection, titles};
+    use anyhow::Error;
+    use kanban_tui::{export_board, OffBoardKind, State};
+
+    fn board_names(state: &State<'_>) -> Vec<String> {
+        state.boards.iter().map(|b| b.name.clone()).collect()
+    }
+
+    #[test]
+    fn it_keeps_the_columns_and_the_selection_of_each_board() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        assert_eq!(board_names(&state), ["Main"]);
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.select_column_right()?;
+
+        state.add_board("Team")?;
+        assert_eq!(state.get_selected_board().name, "Team");
+        assert_eq!(state.columns.len(), 4);
+        assert!(state.columns.iter().all(|c| c.tasks.is_empty()));
+        assert_eq!(state.selected_column_idx, 0);
+        // The history of the other board can't be undone from here
+        state.undo()?;
+        assert_eq!(state.status_message.as_deref(), Some("Nothing to undo"));
+
+        state.add_board("team")?;
+        assert!(state.status_message.is_some());
+        assert_eq!(board_names(&state), ["Main", "Team"]);
+
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.select_column_right()?;
+        state.select_column_right()?;
+
+        // The app opens the last board on its last column
+        let mut state = State::new(state.db_conn.0)?;
+        assert_eq!(state.get_selected_board().name, "Team");
+        assert_eq!(state.selected_column_idx, 2);
+        assert_eq!(state.columns[0].tasks[0].title, "T2");
+        let team_id = state.get_selected_board().id;
+
+        state.switch_board_by_name("main")?;
+        assert_eq!(state.selected_column_idx, 1);
+        assert_eq!(state.columns[0].tasks[0].title, "T1");
+        assert!(state.switch_board_by_name("Nope").is_err());
+
+        let export = export_board(&state.db_conn, team_id)?;
+        assert_eq!(export.columns[0].tasks.len(), 1);
+        assert_eq!(export.columns[0].tasks[0].title, "T2");
+
+        state.rename_board(1, "Work")?;
+        state.rename_board(1, "main")?;
+        assert!(state.status_message.is_some());
+        assert_eq!(board_names(&State::new(state.db_conn.0)?), ["Main", "Work"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_deletes_a_board_after_confirming() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_board("Team")?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+
+        state.ask_delete_board(0);
+        state.confirm = None;
+        assert_eq!(state.boards.len(), 2);
+
+        // Deleting the board being shown goes back to the other one
+        state.ask_delete_board(1);
+        state.confirm_action()?;
+        assert_eq!(board_names(&state), ["Main"]);
+        assert_eq!(state.get_selected_board().name, "Main");
+        assert!(state.columns.iter().all(|c| c.tasks.is_empty()));
+
+        // Its tasks went to the trash of the remaining board
+        let trashed = state.db_conn.get_trashed_tasks(1)?;
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].task.title, "T1");
+        state.open_archive(OffBoardKind::Deleted)?;
+        state.restore_off_board_task()?;
+        assert_eq!(titles(&state, 0), ["T1"]);
+        assert!(state.db_conn.get_trashed_tasks(1)?.is_empty());
+
+        // Or get purged with the rest of the trash
+        state.add_board("Team")?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.ask_delete_board(1);
+        state.confirm_action()?;
+        state
+            .db_conn
+            .execute("update task set deleted_at = deleted_at - 31 * 86400", ())?;
+        assert_eq!(state.db_conn.purge_trash(30)?, 1);
+        assert_eq!(titles(&state, 0), ["T1"]);
+
+        state.ask_delete_board(0);
+        assert!(state.confirm.is_none());
+        assert!(state.status_message.is_some());
+
+        Ok(())
+    }
+}
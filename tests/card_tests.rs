@@ -0,0 +1,94 @@
+mod common;
+
+#[cfg(test)]
+mod card_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use kanban_tui::{
+        description_preview, draw_ui_from_state, truncate_text, wrap_text, Config, State, TaskView,
+    };
+    use tui::backend::TestBackend;
+    use tui::Terminal;
+
+    /// Draws a frame and returns the text of the column at `col_idx`,
+    /// a string per row.
+    fn draw_column(state: &mut State<'_>, col_idx: usize) -> Result<Vec<String>, Error> {
+        let mut terminal = Terminal::new(TestBackend::new(100, 40))?;
+        terminal.draw(|f| draw_ui_from_state(f, state))?;
+        let list = state.click_areas.columns[col_idx].list;
+        let buffer = terminal.backend().buffer();
+        let rows = (list.y..list.bottom())
+            .map(|y| {
+                (list.x..list.right())
+                    .map(|x| buffer.get(x, y).symbol.as_str())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+        Ok(rows)
+    }
+
+    #[test]
+    fn it_wraps_and_truncates_text() {
+        assert_eq!(
+            wrap_text("Write the release notes for v2", 10, 14),
+            ["Write the", "release notes", "for v2"]
+        );
+        // Words longer than a line are cut
+        assert_eq!(wrap_text("abcdefgh ij", 4, 5), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap_text("", 10, 10), [""]);
+
+        assert_eq!(truncate_text("Short", 10), "Short");
+        assert_eq!(truncate_text("Much too long", 8), "Much to…");
+        assert_eq!(
+            description_preview("\n  \nFirst line\nSecond", 20),
+            Some(String::from("First line"))
+        );
+        assert_eq!(description_preview("   ", 20), None);
+    }
+
+    #[test]
+    fn it_draws_tasks_as_cards() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(
+            String::from("Write the release notes for the next version"),
+            String::from("Collect the changes\nand group them"),
+        )?;
+        state.add_new_task(String::from("Tag it"), String::new())?;
+        state.select_task_at(0, 1)?;
+
+        let rows = draw_column(&mut state, 0)?;
+        assert!(rows[0].starts_with("Write the release notes"));
+        assert_eq!(state.click_areas.columns[0].rows, [0, 1]);
+
+        state.task_view = TaskView::Cards;
+        let rows = draw_column(&mut state, 0)?;
+        let width = usize::from(state.click_areas.columns[0].list.width);
+        let title = wrap_text("Write the release notes for the next version", width, width);
+        assert!(title.len() > 1);
+        let card_height = title.len() + 3;
+        assert_eq!(rows[..title.len()], title);
+        assert_eq!(rows[title.len()], "Collect the changes");
+        assert!(rows[title.len() + 1].starts_with("#1 · "));
+        assert_eq!(rows[card_height - 1], "");
+        assert!(rows[card_height].starts_with("Tag it"));
+        // Clicks on any row of a card land on its task
+        let mut expected = vec![0; card_height];
+        expected.extend([1, 1, 1]);
+        assert_eq!(state.click_areas.columns[0].rows, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_reads_the_task_view_from_the_config() -> Result<(), Error> {
+        let config = Config::from_toml("[layout]\ntask-view = \"cards\"\n")?;
+        assert_eq!(config.layout.task_view, TaskView::Cards);
+        assert_eq!(config.layout.task_view.toggled(), TaskView::Lines);
+        assert_eq!(Config::from_toml("")?.layout.task_view, TaskView::Lines);
+        assert!(Config::from_toml("[layout]\ntask-view = \"grid\"\n").is_err());
+
+        Ok(())
+    }
+}
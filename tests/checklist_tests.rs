@@ -0,0 +1,65 @@
+mod common;
+
+#[cfg(test)]
+mod checklist_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use kanban_tui::{export_board, render_markdown, render_org, ChecklistItem, State};
+
+    fn item(text: &str, done: bool) -> ChecklistItem {
+        ChecklistItem {
+            text: text.to_string(),
+            done,
+        }
+    }
+
+    #[test]
+    fn it_saves_checklists_and_undoes_their_changes() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        let checklist = [
+            item("Write", true),
+            item("Review", false),
+            item("Ship", false),
+        ];
+        state.set_selected_task_checklist(&checklist)?;
+        assert_eq!(
+            state.get_selected_task().unwrap().checklist_progress(),
+            (1, 3)
+        );
+
+        let reordered = [item("Review", true), item("Write", true)];
+        state.set_selected_task_checklist(&reordered)?;
+        let mut state = State::new(state.db_conn.0)?;
+        assert_eq!(state.get_selected_task().unwrap().checklist, reordered);
+
+        // Undoing the deletion brings the checklist back too
+        state.delete_task()?;
+        state.undo()?;
+        assert_eq!(state.get_selected_task().unwrap().checklist, reordered);
+
+        let mut state = State::new(state.db_conn.0)?;
+        state.set_selected_task_checklist(&checklist)?;
+        state.undo()?;
+        assert_eq!(state.get_selected_task().unwrap().checklist, reordered);
+        state.redo()?;
+        assert_eq!(state.get_selected_task().unwrap().checklist, checklist);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_exports_checklists_as_checkboxes() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.set_selected_task_checklist(&[item("Write", true), item("Ship", false)])?;
+
+        let board = export_board(&state.db_conn, 1)?;
+        let markdown = render_markdown(&board);
+        assert!(markdown.contains("- [ ] T1\n  - [x] Write\n  - [ ] Ship\n  D1\n"));
+        let org = render_org(&board);
+        assert!(org.contains("** TODO T1 [1/2]\n   - [X] Write\n   - [ ] Ship\n   D1\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+mod common;
+
+#[cfg(test)]
+mod cli_tests {
+    use super::common::{create_connection, run};
+    use anyhow::Error;
+    use kanban_tui::{run_command, Command, DBConn, Priority, State, WipLimitMode};
+
+    fn create_db_conn() -> Result<DBConn, Error> {
+        Ok(DBConn::new(create_connection()?))
+    }
+
+    #[test]
+    fn it_adds_lists_moves_edits_and_removes_tasks() -> Result<(), Error> {
+        let mut db_conn = create_db_conn()?;
+
+        let add = |title: &str, column: Option<&str>| Command::Add {
+            title: title.to_string(),
+            description: format!("{title} description"),
+            column: column.map(String::from),
+            labels: Vec::new(),
+            priority: None,
+            due: None,
+            force: false,
+        };
+        assert_eq!(run(&mut db_conn, add("T1", None), false)?, "1\n");
+        assert_eq!(run(&mut db_conn, add("T2", Some("done")), false)?, "2\n");
+        assert!(run(&mut db_conn, add("T3", Some("Nope")), false).is_err());
+
+        let list = run(&mut db_conn, Command::List { column: None }, false)?;
+        assert_eq!(list, "Todo\n  #1 T1\nInProgress\nDone\n  #2 T2\nIdeas\n");
+
+        let move_task = Command::Move {
+            id: 1,
+            column: String::from("InProgress"),
+            force: false,
+        };
+        assert_eq!(run(&mut db_conn, move_task, false)?, "#1 T1\n");
+        let edit = Command::Edit {
+            id: 1,
+            title: Some(String::from("T1 edited")),
+            description: None,
+            priority: None,
+            no_priority: false,
+            due: None,
+        };
+        run(&mut db_conn, edit, false)?;
+        // Changing only the priority leaves the text and its history alone
+        let edit = Command::Edit {
+            id: 1,
+            title: None,
+            description: None,
+            priority: Some(Priority::P1),
+            no_priority: false,
+            due: None,
+        };
+        let task: serde_json::Value = serde_json::from_str(&run(&mut db_conn, edit, true)?)?;
+        assert_eq!(task["priority"], "p1");
+        assert_eq!(task["column"], "InProgress");
+
+        let list = Command::List {
+            column: Some(String::from("inprogress")),
+        };
+        let list = run(&mut db_conn, list, true)?;
+        let tasks: serde_json::Value = serde_json::from_str(&list)?;
+        assert_eq!(tasks.as_array().unwrap().len(), 1);
+        assert_eq!(tasks[0]["id"], 1);
+        assert_eq!(tasks[0]["title"], "T1 edited");
+        assert_eq!(tasks[0]["description"], "T1 description");
+        assert_eq!(tasks[0]["column"], "InProgress");
+        assert_eq!(tasks[0]["priority"], "p1");
+
+        let show = run(&mut db_conn, Command::Show { id: 1 }, true)?;
+        let task: serde_json::Value = serde_json::from_str(&show)?;
+        let kinds: Vec<&str> = task["history"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, ["created", "moved", "edited"]);
+
+        let show = run(&mut db_conn, Command::Show { id: 2 }, false)?;
+        assert!(show.starts_with("#2 T2\nColumn:    Done\n"));
+
+        let link = |blocker, blocked| Command::Link { blocker, blocked };
+        assert_eq!(run(&mut db_conn, link(2, 1), false)?, "#1 [P1] T1 edited\n");
+        assert!(run(&mut db_conn, link(1, 2), false).is_err());
+        let show = run(&mut db_conn, Command::Show { id: 1 }, false)?;
+        assert!(show.contains("Blocked by: #2 T2 (Done)\n"));
+        let unlink = Command::Unlink {
+            blocker: 2,
+            blocked: 1,
+        };
+        run(&mut db_conn, unlink, false)?;
+        let show = run(&mut db_conn, Command::Show { id: 1 }, false)?;
+        assert!(!show.contains("Blocked by"));
+
+        assert_eq!(run(&mut db_conn, Command::Rm { id: 2 }, false)?, "#2 T2\n");
+        assert!(run(&mut db_conn, Command::Rm { id: 2 }, false).is_err());
+        assert!(run(&mut db_conn, Command::Show { id: 2 }, false).is_err());
+
+        // The TUI sees the same board
+        let state = State::new(db_conn.0)?;
+        assert_eq!(state.columns[0].tasks.len(), 0);
+        assert_eq!(&state.columns[1].tasks[0].title, "T1 edited");
+        assert_eq!(state.columns[2].tasks.len(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_refuses_what_the_board_would_refuse() -> Result<(), Error> {
+        let mut db_conn = create_db_conn()?;
+        for title in ["T1", "T2"] {
+            let add = Command::Add {
+                title: title.to_string(),
+                description: String::new(),
+                column: None,
+                labels: Vec::new(),
+                priority: None,
+                due: None,
+                force: false,
+            };
+            run(&mut db_conn, add, false)?;
+        }
+        let link = Command::Link {
+            blocker: 1,
+            blocked: 2,
+        };
+        run(&mut db_conn, link, false)?;
+        let move_task = |id: i64, column: &str, force: bool| Command::Move {
+            id,
+            column: column.to_string(),
+            force,
+        };
+
+        // T1 isn't done so T2 can't start
+        assert!(run(&mut db_conn, move_task(2, "InProgress", false), false).is_err());
+        run(&mut db_conn, move_task(2, "Ideas", false), false)?;
+
+        let in_progress = db_conn.get_all_columns(1)?[1].id;
+        db_conn.set_column_wip_limit(in_progress, Some(0))?;
+        let refusing = |db_conn: &mut DBConn, command| {
+            let mut out = Vec::new();
+            run_command(
+                db_conn,
+                None,
+                command,
+                false,
+                WipLimitMode::Refuse,
+                &mut out,
+            )
+        };
+        let add = |force| Command::Add {
+            title: String::from("T3"),
+            description: String::new(),
+            column: Some(String::from("InProgress")),
+            labels: Vec::new(),
+            priority: None,
+            due: None,
+            force,
+        };
+        assert!(refusing(&mut db_conn, move_task(1, "InProgress", false)).is_err());
+        assert!(refusing(&mut db_conn, add(false)).is_err());
+        refusing(&mut db_conn, move_task(1, "InProgress", true))?;
+        refusing(&mut db_conn, move_task(2, "InProgress", true))?;
+        refusing(&mut db_conn, add(true))?;
+        // Only warned about by default
+        run(&mut db_conn, add(false), false)?;
+
+        let state = State::new(db_conn.0)?;
+        assert_eq!(state.columns[1].tasks.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_commands_against_the_chosen_board() -> Result<(), Error> {
+        let mut db_conn = create_db_conn()?;
+        let add_board = Command::AddBoard {
+            name: String::from("Team"),
+        };
+        let board: serde_json::Value = serde_json::from_str(&run(&mut db_conn, add_board, true)?)?;
+        assert_eq!(board["id"], 2);
+        assert_eq!(board["name"], "Team");
+        let add_board = Command::AddBoard {
+            name: String::from("team"),
+        };
+        assert!(run(&mut db_conn, add_board, false).is_err());
+        assert_eq!(
+            run(&mut db_conn, Command::Boards, false)?,
+            "* Main\n  Team\n"
+        );
+
+        let add = Command::Add {
+            title: String::from("T1"),
+            description: String::new(),
+            column: None,
+            labels: Vec::new(),
+            priority: None,
+            due: None,
+            force: false,
+        };
+        let mut out = Vec::new();
+        run_command(
+            &mut db_conn,
+            Some("team"),
+            add,
+            false,
+            WipLimitMode::Warn,
+            &mut out,
+        )?;
+        let list = || Command::List {
+            column: Some(String::from("Todo")),
+        };
+        assert_eq!(run(&mut db_conn, list(), false)?, "");
+        let mut out = Vec::new();
+        run_command(
+            &mut db_conn,
+            Some("Team"),
+            list(),
+            false,
+            WipLimitMode::Warn,
+            &mut out,
+        )?;
+        assert_eq!(String::from_utf8(out)?, "#1 T1\n");
+        let mut out = Vec::new();
+        assert!(run_command(
+            &mut db_conn,
+            Some("Nope"),
+            list(),
+            false,
+            WipLimitMode::Warn,
+            &mut out
+        )
+        .is_err());
+
+        Ok(())
+    }
+}
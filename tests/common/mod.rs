@@ -0,0 +1,31 @@
+//! Helpers shared by the integration tests, each test file uses only
+//! some of them.
+#![allow(dead_code)]
+
+use anyhow::Error;
+use kanban_tui::{migrate, run_command, Command, DBConn, State, WipLimitMode};
+use rusqlite::Connection;
+
+/// An in-memory database with the schema in place.
+pub fn create_connection() -> Result<Connection, Error> {
+    let mut conn = Connection::open_in_memory()?;
+    migrate::run(&mut conn)?;
+    Ok(conn)
+}
+
+/// Runs `command` against the board the app opens, with the default
+/// WIP limit mode, and returns what it printed.
+pub fn run(db_conn: &mut DBConn, command: Command, json: bool) -> Result<String, Error> {
+    let mut out = Vec::new();
+    run_command(db_conn, None, command, json, WipLimitMode::Warn, &mut out)?;
+    Ok(String::from_utf8(out)?)
+}
+
+/// Titles of the tasks of the column at `col_idx`, in order.
+pub fn titles(state: &State<'_>, col_idx: usize) -> Vec<String> {
+    state.columns[col_idx]
+        .tasks
+        .iter()
+        .map(|t| t.title.clone())
+        .collect()
+}
@@ -0,0 +1,94 @@
+mod common;
+
+#[cfg(test)]
+mod due_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use chrono::NaiveDate;
+    use kanban_tui::{describe_due_date, parse_due_date, DueStatus, Priority, State};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn it_parses_absolute_and_relative_due_dates() -> Result<(), Error> {
+        // A Wednesday
+        let today = date(2024, 1, 31);
+        let parse = |input: &str| parse_due_date(input, today);
+
+        assert_eq!(parse("")?, None);
+        assert_eq!(parse("2024-05-01")?, Some(date(2024, 5, 1)));
+        assert_eq!(parse(" Today ")?, Some(today));
+        assert_eq!(parse("tomorrow")?, Some(date(2024, 2, 1)));
+        assert_eq!(parse("yesterday")?, Some(date(2024, 1, 30)));
+        assert_eq!(parse("+3d")?, Some(date(2024, 2, 3)));
+        assert_eq!(parse("2w")?, Some(date(2024, 2, 14)));
+        assert_eq!(parse("+1m")?, Some(date(2024, 2, 29)));
+        assert_eq!(parse("-1y")?, Some(date(2023, 1, 31)));
+        assert_eq!(parse("fri")?, Some(date(2024, 2, 2)));
+        // The same weekday means next week
+        assert_eq!(parse("Wednesday")?, Some(date(2024, 2, 7)));
+        for invalid in ["soon", "+3", "3x", "2024-13-01", "+d"] {
+            assert!(parse(invalid).is_err(), "{invalid} should not parse");
+        }
+
+        assert_eq!(DueStatus::of(date(2024, 1, 30), today), DueStatus::Overdue);
+        assert_eq!(DueStatus::of(today, today), DueStatus::Today);
+        assert_eq!(DueStatus::of(date(2024, 2, 1), today), DueStatus::Upcoming);
+        assert_eq!(
+            describe_due_date(date(2024, 1, 28), today),
+            "3 days overdue"
+        );
+        assert_eq!(describe_due_date(date(2024, 2, 3), today), "in 3 days");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_lists_every_task_by_due_date_in_the_agenda() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        for (title, due_date) in [
+            ("No date", None),
+            ("Later", Some(date(2024, 3, 1))),
+            ("Sooner", Some(date(2024, 2, 1))),
+        ] {
+            state.add_new_task(String::from(title), String::new())?;
+            state.set_selected_task_due_date(due_date)?;
+        }
+        state.select_column_right()?;
+        state.add_new_task(String::from("Urgent later"), String::new())?;
+        state.set_selected_task_due_date(Some(date(2024, 3, 1)))?;
+        state.set_selected_task_priority(Some(Priority::P0))?;
+
+        let agenda = |state: &State<'_>| -> Vec<String> {
+            state
+                .get_agenda()
+                .into_iter()
+                .map(|(c, t)| state.columns[c].tasks[t].title.clone())
+                .collect()
+        };
+        assert_eq!(
+            agenda(&state),
+            ["Sooner", "Urgent later", "Later", "No date"]
+        );
+
+        state.undo()?;
+        state.undo()?;
+        assert_eq!(state.get_selected_task().unwrap().due_date, None);
+        state.redo()?;
+
+        let mut state = State::new(state.db_conn.0)?;
+        assert_eq!(state.columns[0].tasks[2].due_date, Some(date(2024, 2, 1)));
+        assert_eq!(
+            agenda(&state),
+            ["Sooner", "Later", "Urgent later", "No date"]
+        );
+
+        state.open_agenda_entry(0)?;
+        assert_eq!(state.selected_column_idx, 0);
+        assert_eq!(state.get_selected_task().unwrap().title, "Sooner");
+
+        Ok(())
+    }
+}
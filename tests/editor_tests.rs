@@ -0,0 +1,78 @@
+mod common;
+
+#[cfg(test)]
+mod editor_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use kanban_tui::{
+        format_task_for_editor, parse_task_from_editor, run_editor, State, TaskState,
+    };
+
+    #[test]
+    fn it_formats_and_parses_tasks() -> Result<(), Error> {
+        let text = format_task_for_editor("Title", "Line 1\n\nLine 2");
+        assert_eq!(text, "Title\n\nLine 1\n\nLine 2\n");
+        assert_eq!(
+            parse_task_from_editor(&text)?,
+            (String::from("Title"), String::from("Line 1\n\nLine 2"))
+        );
+        assert_eq!(format_task_for_editor("Title", ""), "Title\n");
+        assert_eq!(
+            parse_task_from_editor("  Title  \nRight below\n\n\n")?,
+            (String::from("Title"), String::from("Right below"))
+        );
+        assert!(parse_task_from_editor("\n\nNo title").is_err());
+        assert!(parse_task_from_editor("").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_the_editor_on_a_temp_file() -> Result<(), Error> {
+        let edited = run_editor("sed -i s/Old/New/", "Old title\n")?;
+        assert_eq!(edited, "New title\n");
+
+        let error = run_editor("false", "Title\n").unwrap_err().to_string();
+        assert!(error.starts_with("false exited with"), "{error}");
+        let error = run_editor("kanban-tui-no-such-editor", "Title\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Couldn't start kanban-tui-no-such-editor"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_applies_the_edited_text() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        assert_eq!(state.get_editor_text().unwrap(), "T1\n\nD1\n");
+
+        // Closing the editor without changes isn't an edit
+        state.apply_editor_text("T1\n\nD1\n")?;
+        state.apply_editor_text("\nNo title")?;
+        assert!(state.status_message.is_some());
+        state.undo()?;
+        assert!(state.columns[0].tasks.is_empty());
+        state.redo()?;
+
+        state.apply_editor_text("Renamed\n\nMore\ndetails\n")?;
+        let task = state.get_selected_task().unwrap();
+        assert_eq!(
+            (task.title.as_str(), task.description.as_str()),
+            ("Renamed", "More\ndetails")
+        );
+        state.undo()?;
+        assert_eq!(state.get_selected_task().unwrap().title, "T1");
+
+        // With the form open only the form changes
+        state.task_edit_state = Some(TaskState::default());
+        assert_eq!(state.get_editor_text().unwrap(), "\n");
+        state.apply_editor_text("New task\n\nFrom the editor")?;
+        let form = state.task_edit_state.as_ref().unwrap();
+        assert_eq!(form.title.lines(), ["New task"]);
+        assert_eq!(form.description.lines(), ["From the editor"]);
+        assert_eq!(state.get_selected_task().unwrap().title, "T1");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+mod common;
+
+#[cfg(test)]
+mod export_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use kanban_tui::{
+        export_board, export_to_string, import_board, render_markdown, render_org, BoardExport,
+        ColumnRole, ExportFormat, ImportMode, State,
+    };
+
+    fn create_board() -> Result<State<'static>, Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.move_task_column_right()?;
+        state.move_task_column_right()?;
+        state.add_column(String::from("Review"))?;
+        state.add_new_task(String::from("T3"), String::from("D3"))?;
+        Ok(state)
+    }
+
+    #[test]
+    fn it_exports_and_imports_a_board_as_json() -> Result<(), Error> {
+        let mut state = create_board()?;
+        state.set_selected_task_labels(&[String::from("bug")])?;
+        state.cycle_label_color(0)?;
+        let json = export_to_string(&state.db_conn, 1, ExportFormat::Json)?;
+
+        let board: BoardExport = serde_json::from_str(&json)?;
+        let names: Vec<&str> = board.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Todo", "InProgress", "Done", "Ideas", "Review"]);
+        assert_eq!(board.columns[2].role, ColumnRole::Done);
+        assert_eq!(board.columns[4].role, ColumnRole::Active);
+        assert_eq!(&board.columns[2].tasks[0].title, "T2");
+        assert!(board.columns[2].tasks[0].completed_at.is_some());
+        assert_eq!(board.app_state["selected_column"], "4");
+
+        let conn = create_connection()?;
+        let db_conn = kanban_tui::DBConn::new(conn);
+        assert_eq!(
+            import_board(&db_conn, 1, &board, ImportMode::Replace)?,
+            (5, 3)
+        );
+
+        let imported = State::new(db_conn.0)?;
+        assert_eq!(imported.columns.len(), 5);
+        assert_eq!(imported.get_selected_column().name, "Review");
+        assert_eq!(imported.get_selected_task().unwrap().title, "T3");
+        assert_eq!(imported.get_selected_task().unwrap().labels, ["bug"]);
+        assert_eq!(imported.labels, state.labels[..]);
+        for (column, original) in imported.columns.iter().zip(&state.columns) {
+            assert_eq!(column.name, original.name);
+            assert_eq!(column.role, original.role);
+            let titles: Vec<&str> = column.tasks.iter().map(|t| t.title.as_str()).collect();
+            let original: Vec<&str> = original.tasks.iter().map(|t| t.title.as_str()).collect();
+            assert_eq!(titles, original);
+        }
+        let task = &imported.columns[2].tasks[0];
+        assert_eq!(task.created_at, state.columns[2].tasks[0].created_at);
+        assert_eq!(task.completed_at, state.columns[2].tasks[0].completed_at);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_merges_an_import_into_an_existing_board() -> Result<(), Error> {
+        let board = export_board(&create_board()?.db_conn, 1)?;
+
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T0"), String::from("D0"))?;
+        state.select_column_right()?;
+        assert_eq!(
+            import_board(&state.db_conn, 1, &board, ImportMode::Merge)?,
+            (1, 3)
+        );
+
+        // The app_state of the existing board is kept
+        let state = State::new(state.db_conn.0)?;
+        assert_eq!(state.get_selected_column().name, "InProgress");
+        let titles: Vec<&str> = state.columns[0]
+            .tasks
+            .iter()
+            .map(|t| t.title.as_str())
+            .collect();
+        assert_eq!(titles, ["T0", "T1"]);
+        assert_eq!(state.columns.len(), 5);
+        assert_eq!(&state.columns[4].tasks[0].title, "T3");
+
+        // Importing twice duplicates the tasks but not the columns
+        assert_eq!(
+            import_board(&state.db_conn, 1, &board, ImportMode::Merge)?,
+            (0, 3)
+        );
+        let state = State::new(state.db_conn.0)?;
+        assert_eq!(state.columns.len(), 5);
+        assert_eq!(state.columns[0].tasks.len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_renders_the_board_as_markdown_and_org() -> Result<(), Error> {
+        let mut state = create_board()?;
+        state.select_column_left()?;
+        state.select_column_left()?;
+        state.edit_task(String::from("T2"), String::from("Line 1\n\n* Line 2"))?;
+        let board = export_board(&state.db_conn, 1)?;
+
+        let markdown = render_markdown(&board);
+        assert!(markdown.starts_with("## Todo\n\n- [ ] T1\n  D1\n\n## InProgress\n\n_No tasks_\n"));
+        assert!(markdown.contains("## Done\n\n- [x] T2\n  Line 1\n\n  * Line 2\n"));
+        assert!(markdown.ends_with("## Review\n\n- [ ] T3\n  D3\n"));
+
+        let org = render_org(&board);
+        assert!(org.starts_with(
+            "#+TODO: TODO DOING SOMEDAY | DONE\n#+PRIORITIES: A D D\n\n* Todo\n** TODO T1\n   D1\n"
+        ));
+        assert!(org.contains("\n* Done\n** DONE T2\n   CLOSED: ["));
+        assert!(org.contains("]\n   Line 1\n\n   * Line 2\n"));
+        assert!(org.ends_with("* Review\n** DOING T3\n   D3\n"));
+
+        assert_eq!(export_to_string(&state.db_conn, 1, ExportFormat::Org)?, org);
+        assert_eq!(
+            state.get_export_path(ExportFormat::Markdown),
+            std::path::PathBuf::from("kanban.md")
+        );
+
+        Ok(())
+    }
+}
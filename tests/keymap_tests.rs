@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod keymap_tests {
+    use anyhow::Error;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use kanban_tui::{Action, Config, KeyBinding, KeyContext, Keymap};
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn it_parses_and_prints_keys() -> Result<(), Error> {
+        for text in [
+            "q", "G", "?", "C-r", "M-x", "Space", "Enter", "S-Tab", "Left", "F5",
+        ] {
+            assert_eq!(KeyBinding::parse(text)?.to_string(), text);
+        }
+        assert_eq!(KeyBinding::parse("S-a")?.to_string(), "A");
+        assert_eq!(KeyBinding::parse("esc")?.code, KeyCode::Esc);
+        assert_eq!(KeyBinding::parse("€")?.code, KeyCode::Char('€'));
+        for text in ["", "Foo", "C-", "F30", "X-y", "€x", "中文"] {
+            assert!(KeyBinding::parse(text).is_err(), "{text}");
+        }
+
+        // Terminals may or may not report Shift along with capitals
+        let binding = KeyBinding::parse("G")?;
+        assert!(binding.matches(key(KeyCode::Char('G'), KeyModifiers::SHIFT)));
+        assert!(binding.matches(key(KeyCode::Char('G'), KeyModifiers::NONE)));
+        assert!(!binding.matches(key(KeyCode::Char('G'), KeyModifiers::CONTROL)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_overrides_the_default_keys() -> Result<(), Error> {
+        let config = Config::from_toml(
+            r#"
+            [keys.main]
+            quit = "Q"
+            select-task-below = ["j", "C-n"]
+            undo = []
+
+            [keys.task-edit]
+            next-field = "C-j"
+            "#,
+        )?;
+        let keymap = Keymap::new(&config.keys)?;
+        let none = KeyModifiers::NONE;
+
+        assert_eq!(
+            keymap.action(KeyContext::Main, key(KeyCode::Char('Q'), none)),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.action(KeyContext::Main, key(KeyCode::Char('q'), none)),
+            None
+        );
+        assert_eq!(
+            keymap.action(
+                KeyContext::Main,
+                key(KeyCode::Char('n'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::SelectTaskBelow)
+        );
+        assert_eq!(
+            keymap.action(KeyContext::Main, key(KeyCode::Char('u'), none)),
+            None
+        );
+        assert_eq!(keymap.describe_keys(Action::SelectTaskBelow), "j, C-n");
+        assert_eq!(keymap.describe_keys(Action::PreviousField), "S-Tab");
+
+        // The same key can mean different things in different contexts
+        let n = key(KeyCode::Char('n'), none);
+        assert_eq!(keymap.action(KeyContext::Main, n), Some(Action::NewTask));
+        assert_eq!(
+            keymap.action(KeyContext::Search, n),
+            Some(Action::NextMatch)
+        );
+
+        let footer = keymap.footer(&[
+            ("quit", &[Action::Quit]),
+            (
+                "navigation",
+                &[
+                    Action::SelectColumnLeft,
+                    Action::SelectTaskBelow,
+                    Action::SelectTaskAbove,
+                    Action::SelectColumnRight,
+                ],
+            ),
+            ("undo/redo", &[Action::Undo, Action::Redo]),
+            (
+                "export md/org",
+                &[Action::ExportMarkdown, Action::ExportOrg],
+            ),
+            ("cycle edit fields", &[Action::NextField]),
+        ]);
+        assert_eq!(
+            footer,
+            "quit: Q | navigation: hjkl | undo/redo: C-r | export md/org: x/X | cycle edit fields: C-j"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_reports_every_problem_with_the_config() -> Result<(), Error> {
+        let config = Config::from_toml(
+            r#"
+            [keys.main]
+            quit = "j"
+            archive-task = "F99"
+            next-match = "n"
+
+            [keys.search]
+            clear-search = "q"
+            "#,
+        )?;
+        let message = Keymap::new(&config.keys).unwrap_err().to_string();
+        assert_eq!(
+            message.lines().collect::<Vec<_>>(),
+            [
+                "Unknown key \"F99\" for \"archive-task\"",
+                "Unknown action \"next-match\" in [keys.main]",
+                "\"j\" is bound to both \"quit\" and \"select-task-below\" in [keys.main]",
+            ]
+        );
+
+        assert!(Config::from_toml("[keys.board]\nquit = \"q\"").is_err());
+        assert!(Config::from_toml("[colors]").is_err());
+        assert!(Config::from_toml("")?.keys.main.is_empty());
+
+        Ok(())
+    }
+}
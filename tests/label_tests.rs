@@ -0,0 +1,117 @@
+mod common;
+
+#[cfg(test)]
+mod label_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use kanban_tui::{parse_labels, LabelColor, State};
+
+    fn labels(names: &[&str]) -> Vec<String> {
+        names.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn it_parses_the_labels_typed_in_the_form() {
+        assert_eq!(parse_labels(" bug, ui ,,Bug, "), labels(&["bug", "ui"]));
+        assert!(parse_labels("").is_empty());
+    }
+
+    #[test]
+    fn it_puts_labels_on_tasks() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.set_selected_task_labels(&labels(&["ui", "Bug"]))?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.set_selected_task_labels(&labels(&["bug"]))?;
+
+        // Labels are shared by name ignoring case and get their own color
+        assert_eq!(state.labels.len(), 2);
+        assert_eq!(state.labels[0].name, "Bug");
+        assert_eq!(state.labels[0].color, LabelColor::Green);
+        assert_eq!(state.labels[1].color, LabelColor::Red);
+        assert_eq!(state.columns[0].tasks[0].labels, ["Bug", "ui"]);
+        assert_eq!(state.columns[0].tasks[1].labels, ["Bug"]);
+
+        state.cycle_label_color(0)?;
+        let mut state = State::new(state.db_conn.0)?;
+        assert_eq!(state.labels[0].color, LabelColor::Yellow);
+        assert_eq!(state.columns[0].tasks[0].labels, ["Bug", "ui"]);
+
+        state.delete_label(0)?;
+        assert_eq!(state.columns[0].tasks[0].labels, ["ui"]);
+        let state = State::new(state.db_conn.0)?;
+        assert_eq!(state.labels.len(), 1);
+        assert_eq!(state.columns[0].tasks[0].labels, ["ui"]);
+        assert!(state.columns[0].tasks[1].labels.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_filters_the_board_by_label() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        for (title, task_labels) in [("T1", &["bug"][..]), ("T2", &[]), ("T3", &["ui"])] {
+            state.add_new_task(String::from(title), String::new())?;
+            state.set_selected_task_labels(&labels(task_labels))?;
+        }
+        state.select_column_right()?;
+        state.add_new_task(String::from("T4"), String::new())?;
+        state.set_selected_task_labels(&labels(&["ui"]))?;
+        state.select_column_left()?;
+        state.select_task_above()?;
+        assert!(!state.is_filtering());
+
+        // The selected task got hidden so the next match is selected
+        state.toggle_label_filter(1)?;
+        assert!(state.is_filtering());
+        assert_eq!(state.get_selected_task().unwrap().title, "T3");
+        state.toggle_label_filter(0)?;
+        let visible: Vec<&str> = state.columns[0]
+            .tasks
+            .iter()
+            .filter(|t| state.is_task_visible(t))
+            .map(|t| t.title.as_str())
+            .collect();
+        assert_eq!(visible, ["T1", "T3"]);
+        state.select_task_above()?;
+        assert_eq!(state.get_selected_task().unwrap().title, "T1");
+
+        state.toggle_label_filter(1)?;
+        state.select_column_right()?;
+        assert!(state
+            .get_selected_column()
+            .tasks
+            .iter()
+            .all(|t| !state.is_task_visible(t)));
+        state.clear_label_filter();
+        assert!(!state.is_filtering());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_undoes_label_changes() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::new())?;
+        state.set_selected_task_labels(&labels(&["bug"]))?;
+        // Setting the same labels again isn't a change
+        state.set_selected_task_labels(&labels(&["bug"]))?;
+        state.set_selected_task_labels(&labels(&["bug", "ui"]))?;
+
+        state.undo()?;
+        assert_eq!(state.get_selected_task().unwrap().labels, ["bug"]);
+        state.undo()?;
+        assert!(state.get_selected_task().unwrap().labels.is_empty());
+        state.redo()?;
+        state.redo()?;
+        assert_eq!(state.get_selected_task().unwrap().labels, ["bug", "ui"]);
+
+        // Deleted tasks come back with their labels
+        state.delete_task()?;
+        state.undo()?;
+        let state = State::new(state.db_conn.0)?;
+        assert_eq!(state.columns[0].tasks[0].labels, ["bug", "ui"]);
+
+        Ok(())
+    }
+}
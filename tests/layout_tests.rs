@@ -0,0 +1,91 @@
+mod common;
+
+#[cfg(test)]
+mod layout_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use kanban_tui::{draw_ui_from_state, BoardLayout, Config, State};
+    use tui::backend::TestBackend;
+    use tui::Terminal;
+
+    fn draw(state: &mut State<'_>, width: u16, height: u16) -> Result<(), Error> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+        terminal.draw(|f| draw_ui_from_state(f, state))?;
+        Ok(())
+    }
+
+    fn shown_columns(state: &State<'_>) -> Vec<usize> {
+        state
+            .click_areas
+            .columns
+            .iter()
+            .map(|c| c.col_idx)
+            .collect()
+    }
+
+    #[test]
+    fn it_scrolls_just_enough_to_show_the_selected_column() {
+        let layout = BoardLayout::default();
+        // 3 columns of 24 fit in 72
+        assert_eq!(layout.visible_columns(8, 0, 0, 72), 0..3);
+        assert_eq!(layout.visible_columns(8, 4, 0, 72), 2..5);
+        assert_eq!(layout.visible_columns(8, 3, 2, 72), 2..5);
+        assert_eq!(layout.visible_columns(8, 1, 2, 72), 1..4);
+        // Growing the terminal doesn't leave empty space on the right
+        assert_eq!(layout.visible_columns(8, 7, 5, 240), 0..8);
+        // A single column is always shown, however narrow
+        assert_eq!(layout.visible_columns(8, 6, 0, 10), 6..7);
+        assert_eq!(layout.visible_columns(0, 0, 0, 72), 0..0);
+    }
+
+    #[test]
+    fn it_reads_the_layout_from_the_config() -> Result<(), Error> {
+        let config = Config::from_toml(
+            "[layout]\nmin-column-width = 30\ncompact-width = 100\npanels = false\n",
+        )?;
+        let layout = BoardLayout::new(&config.layout)?;
+        assert_eq!(layout.min_column_width, 30);
+        assert_eq!(layout.compact_height, BoardLayout::default().compact_height);
+        assert!(layout.is_compact(90, 40));
+        assert!(!layout.is_compact(100, 40));
+        assert_eq!(config.layout.panels, Some(false));
+
+        let config = Config::from_toml("[layout]\nmin-column-width = 0\n")?;
+        assert!(BoardLayout::new(&config.layout).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_draws_the_columns_that_fit_and_collapses_the_panels() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        for name in ["Review", "QA", "Staging", "Released"] {
+            state.add_column(String::from(name))?;
+        }
+        let count = state.columns.len();
+
+        // 100 cells fit 4 columns, ending on the selected last one
+        draw(&mut state, 100, 40)?;
+        assert_eq!(
+            shown_columns(&state),
+            (count - 4..count).collect::<Vec<_>>()
+        );
+        assert_eq!(state.column_offset, count - 4);
+        state.select_column_left()?;
+        draw(&mut state, 100, 40)?;
+        assert_eq!(state.column_offset, count - 4);
+
+        let full_height = state.click_areas.columns[0].list.height;
+        state.show_panels = false;
+        draw(&mut state, 100, 40)?;
+        assert_eq!(state.click_areas.columns[0].list.height, full_height + 10);
+
+        // Compact mode hides the panels whatever the setting
+        state.show_panels = true;
+        draw(&mut state, 60, 20)?;
+        assert_eq!(shown_columns(&state).len(), 2);
+        assert_eq!(state.click_areas.columns[0].list.height, 20 - 1 - 2 - 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+mod common;
+
+#[cfg(test)]
+mod link_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use kanban_tui::{export_board, import_board, ImportMode, State};
+
+    /// A board with T1 blocking T2, both in the first column, T2
+    /// being selected.
+    fn create_board() -> Result<State<'static>, Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.toggle_selected_task_blocker(1)?;
+        Ok(state)
+    }
+
+    #[test]
+    fn it_links_tasks_and_refuses_cycles() -> Result<(), Error> {
+        let mut state = create_board()?;
+        assert_eq!(state.get_selected_task().unwrap().blocked_by, [1]);
+        assert_eq!(state.columns[0].tasks[0].blocks, [2]);
+        assert!(state.is_task_blocked(state.get_selected_task().unwrap()));
+        assert_eq!(state.get_link_candidates(), [(0, 0)]);
+
+        // T2 already waits on T1 so T1 can't wait on T2
+        state.select_task_above()?;
+        state.toggle_selected_task_blocker(2)?;
+        assert!(state.status_message.is_some());
+        assert!(state.get_selected_task().unwrap().blocked_by.is_empty());
+
+        // The links survive a reload and undoing removes them
+        let mut state = State::new(state.db_conn.0)?;
+        assert_eq!(state.columns[0].tasks[1].blocked_by, [1]);
+        state.select_task_below()?;
+        state.toggle_selected_task_blocker(1)?;
+        assert!(state.get_selected_task().unwrap().blocked_by.is_empty());
+        state.undo()?;
+        assert_eq!(state.get_selected_task().unwrap().blocked_by, [1]);
+
+        // Deleting the blocker drops the link until it's restored
+        state.select_task_above()?;
+        state.delete_task()?;
+        assert!(state.columns[0].tasks[0].blocked_by.is_empty());
+        state.undo()?;
+        assert_eq!(state.columns[0].tasks[1].blocked_by, [1]);
+        assert_eq!(state.columns[0].tasks[0].blocks, [2]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_asks_before_starting_a_blocked_task() -> Result<(), Error> {
+        let mut state = create_board()?;
+
+        // InProgress is an active column
+        state.move_task_column_right()?;
+        assert!(state.confirm.is_some());
+        assert_eq!(state.columns[0].tasks.len(), 2);
+        state.confirm_action()?;
+        assert!(state.confirm.is_none());
+        assert_eq!(state.columns[1].tasks[0].title, "T2");
+
+        // Once the blocker is done, the move doesn't ask anymore
+        state.move_task_column_left()?;
+        state.select_task_above()?;
+        state.move_task_column_right()?;
+        state.move_task_column_right()?;
+        assert!(state.confirm.is_none());
+        state.select_column_left()?;
+        state.select_column_left()?;
+        assert!(!state.is_task_blocked(state.get_selected_task().unwrap()));
+        state.move_task_column_right()?;
+        assert!(state.confirm.is_none());
+        assert_eq!(state.columns[1].tasks[0].title, "T2");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_finds_blockers_on_other_boards() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.add_board("Other")?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.toggle_selected_task_blocker(1)?;
+        assert!(state.is_task_blocked(state.get_selected_task().unwrap()));
+        state.move_task_column_right()?;
+        assert!(state.confirm.is_some());
+        state.confirm = None;
+
+        // Finishing T1 on its own board unblocks T2
+        state.switch_board(0)?;
+        state.move_task_column_right()?;
+        state.move_task_column_right()?;
+        state.switch_board(1)?;
+        assert!(!state.is_task_blocked(state.get_selected_task().unwrap()));
+        state.move_task_column_right()?;
+        assert!(state.confirm.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_remaps_links_on_import() -> Result<(), Error> {
+        let board = export_board(&create_board()?.db_conn, 1)?;
+
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T0"), String::from("D0"))?;
+        import_board(&state.db_conn, 1, &board, ImportMode::Merge)?;
+
+        let state = State::new(state.db_conn.0)?;
+        let tasks = &state.columns[0].tasks;
+        assert_eq!(tasks[2].title, "T2");
+        assert_eq!(tasks[2].blocked_by, [tasks[1].id]);
+        assert_eq!(tasks[1].blocks, [tasks[2].id]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_imports_tasks_without_ids() -> Result<(), Error> {
+        let json = r#"{"version": 1, "columns": [
+            {"name": "Todo", "role": "backlog", "tasks": [{"title": "T1"}, {"title": "T2"}]},
+            {"name": "Done", "role": "done", "tasks": [{"title": "T3", "id": 7}]}
+        ]}"#;
+        let state = State::new(create_connection()?)?;
+        import_board(
+            &state.db_conn,
+            1,
+            &serde_json::from_str(json)?,
+            ImportMode::Merge,
+        )?;
+
+        let state = State::new(state.db_conn.0)?;
+        let tasks = &state.columns[0].tasks;
+        assert_eq!(tasks.len(), 2);
+        assert_ne!(tasks[0].id, tasks[1].id);
+        assert!(tasks
+            .iter()
+            .all(|t| t.blocked_by.is_empty() && t.blocks.is_empty()));
+
+        // A link to an ID shared by several tasks can't be resolved
+        let json = r#"{"version": 1, "columns": [
+            {"name": "Todo", "role": "backlog", "tasks": [
+                {"title": "T4"}, {"title": "T5"}, {"title": "T6", "id": 9, "blocked_by": [0]}
+            ]}
+        ]}"#;
+        let result = import_board(
+            &state.db_conn,
+            1,
+            &serde_json::from_str(json)?,
+            ImportMode::Merge,
+        );
+        assert!(result.is_err());
+        let state = State::new(state.db_conn.0)?;
+        assert_eq!(state.columns[0].tasks.len(), 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod migrate_tests {
+    use anyhow::Error;
+    use kanban_tui::{migrate, State};
+    use rusqlite::Connection;
+
+    fn user_version(conn: &Connection) -> Result<usize, Error> {
+        Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+    }
+
+    #[test]
+    fn it_migrates_a_new_database_and_is_idempotent() -> Result<(), Error> {
+        let mut conn = Connection::open_in_memory()?;
+        migrate::run(&mut conn)?;
+        assert_eq!(user_version(&conn)?, migrate::latest_version());
+        migrate::run(&mut conn)?;
+        assert_eq!(user_version(&conn)?, migrate::latest_version());
+
+        let state = State::new(conn)?;
+        assert_eq!(state.columns.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_brings_a_database_from_before_versioning_forward() -> Result<(), Error> {
+        // This is what the app used to run when the file didn't exist
+        let mut conn = Connection::open_in_memory()?;
+        conn.execute_batch(include_str!("../sql/migrations/0001_initial.sql"))?;
+        conn.execute(
+            "insert into task(title, description, sort_order, column_id)
+             values ('T1', 'D1', 0, 3)",
+            (),
+        )?;
+        conn.execute(
+            "update app_state set value = '2' where key = 'selected_column'",
+            (),
+        )?;
+        assert_eq!(user_version(&conn)?, 0);
+
+        migrate::run(&mut conn)?;
+        assert_eq!(user_version(&conn)?, migrate::latest_version());
+
+        let state = State::new(conn)?;
+        let names: Vec<&str> = state.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Todo", "InProgress", "Done", "Ideas"]);
+        assert_eq!(&state.columns[2].tasks[0].title, "T1");
+        // Everything ends up in a single board
+        assert_eq!(state.boards.len(), 1);
+        assert_eq!(state.selected_column_idx, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_refuses_a_database_newer_than_the_binary() -> Result<(), Error> {
+        let mut conn = Connection::open_in_memory()?;
+        migrate::run(&mut conn)?;
+        conn.pragma_update(None, "user_version", migrate::latest_version() + 1)?;
+
+        assert!(migrate::run(&mut conn).is_err());
+        assert_eq!(user_version(&conn)?, migrate::latest_version() + 1);
+
+        Ok(())
+    }
+}
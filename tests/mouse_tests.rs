@@ -0,0 +1,172 @@
+mod common;
+
+#[cfg(test)]
+mod mouse_tests {
+    use super::common::{create_connection, titles};
+    use anyhow::Error;
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use kanban_tui::{draw_ui_from_state, handle_mouse, State, TaskEventKind, WipLimitMode};
+    use tui::backend::TestBackend;
+    use tui::Terminal;
+
+    /// Draws a frame so [`State::click_areas`] matches the board.
+    fn draw(state: &mut State<'_>) -> Result<(), Error> {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40))?;
+        terminal.draw(|f| draw_ui_from_state(f, state))?;
+        Ok(())
+    }
+
+    fn mouse(state: &mut State<'_>, kind: MouseEventKind, (x, y): (u16, u16)) -> Result<(), Error> {
+        let event = MouseEvent {
+            kind,
+            column: x,
+            row: y,
+            modifiers: KeyModifiers::NONE,
+        };
+        handle_mouse(state, event)
+    }
+
+    /// Where the task on `row` of column `col_idx` was drawn.
+    fn task_pos(state: &State<'_>, col_idx: usize, row: u16) -> (u16, u16) {
+        let list = state.click_areas.columns[col_idx].list;
+        (list.x + 1, list.y + row)
+    }
+
+    #[test]
+    fn it_selects_the_clicked_task_and_scrolls_through_columns() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::new())?;
+        state.add_new_task(String::from("T2"), String::new())?;
+        state.add_new_task(String::from("T3"), String::new())?;
+        draw(&mut state)?;
+        assert_eq!(state.click_areas.columns[0].rows, [0, 1, 2]);
+
+        let pos = task_pos(&state, 0, 0);
+        mouse(&mut state, MouseEventKind::Down(MouseButton::Left), pos)?;
+        mouse(&mut state, MouseEventKind::Up(MouseButton::Left), pos)?;
+        assert_eq!(state.get_selected_task().unwrap().title, "T1");
+        assert_eq!(titles(&state, 0), ["T1", "T2", "T3"]);
+
+        // Clicking an empty column selects it
+        let pos = task_pos(&state, 2, 3);
+        mouse(&mut state, MouseEventKind::Down(MouseButton::Left), pos)?;
+        assert_eq!(state.selected_column_idx, 2);
+        assert_eq!(state.dragged_task, None);
+
+        mouse(&mut state, MouseEventKind::ScrollUp, (0, 0))?;
+        assert_eq!(state.selected_column_idx, 1);
+        mouse(&mut state, MouseEventKind::ScrollDown, (0, 0))?;
+        assert_eq!(state.selected_column_idx, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_drags_tasks_between_and_within_columns() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::new())?;
+        state.add_new_task(String::from("T2"), String::new())?;
+        state.add_new_task(String::from("T3"), String::new())?;
+        draw(&mut state)?;
+
+        // Dropped on a task it takes that task's place
+        let from = task_pos(&state, 0, 2);
+        let to = task_pos(&state, 0, 0);
+        mouse(&mut state, MouseEventKind::Down(MouseButton::Left), from)?;
+        mouse(&mut state, MouseEventKind::Up(MouseButton::Left), to)?;
+        assert_eq!(titles(&state, 0), ["T3", "T1", "T2"]);
+        assert_eq!(state.get_selected_task().unwrap().title, "T3");
+
+        // Dropped below the tasks it goes last
+        draw(&mut state)?;
+        let from = task_pos(&state, 0, 1);
+        let to = task_pos(&state, 1, 5);
+        mouse(&mut state, MouseEventKind::Down(MouseButton::Left), from)?;
+        mouse(&mut state, MouseEventKind::Up(MouseButton::Left), to)?;
+        assert_eq!(titles(&state, 0), ["T3", "T2"]);
+        assert_eq!(titles(&state, 1), ["T1"]);
+        assert_eq!(state.selected_column_idx, 1);
+
+        // A single undo puts it back
+        state.undo()?;
+        assert_eq!(titles(&state, 0), ["T3", "T1", "T2"]);
+        assert!(titles(&state, 1).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_drops_tasks_straight_into_the_target_column() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.wip_limit_mode = WipLimitMode::Refuse;
+        state.add_new_task(String::from("T1"), String::new())?;
+        state.add_new_task(String::from("T2"), String::new())?;
+        state.toggle_selected_task_blocker(1)?;
+        // InProgress is full, which doesn't matter when going past it
+        state.select_column_right()?;
+        state.set_column_wip_limit(Some(0))?;
+        state.select_column_left()?;
+
+        state.move_task_to(2, 0)?;
+        assert!(state.confirm.is_none());
+        assert_eq!(titles(&state, 0), ["T1"]);
+        assert_eq!(titles(&state, 2), ["T2"]);
+        let moves = state
+            .get_selected_task_history()?
+            .iter()
+            .filter(|e| e.kind == TaskEventKind::Moved)
+            .count();
+        assert_eq!(moves, 1);
+
+        // Only the target column asks about the blocker
+        state.select_column_left()?;
+        state.set_column_wip_limit(None)?;
+        state.select_column_right()?;
+        state.move_task_to(1, 0)?;
+        assert!(state.confirm.is_some());
+        state.confirm_action()?;
+        assert_eq!(titles(&state, 1), ["T2"]);
+        assert!(titles(&state, 2).is_empty());
+
+        state.undo()?;
+        assert_eq!(titles(&state, 2), ["T2"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_answers_the_confirmation_with_a_click() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::new())?;
+        state.add_new_task(String::from("T2"), String::new())?;
+
+        state.ask_delete_task();
+        draw(&mut state)?;
+        // The board underneath doesn't take clicks
+        let pos = task_pos(&state, 0, 0);
+        mouse(&mut state, MouseEventKind::Down(MouseButton::Left), pos)?;
+        assert_eq!(state.get_selected_task().unwrap().title, "T2");
+
+        let (_, no) = state.click_areas.buttons[1];
+        mouse(
+            &mut state,
+            MouseEventKind::Down(MouseButton::Left),
+            (no.x, no.y),
+        )?;
+        assert!(state.confirm.is_none());
+        assert_eq!(titles(&state, 0), ["T1", "T2"]);
+
+        state.ask_delete_task();
+        draw(&mut state)?;
+        let (_, yes) = state.click_areas.buttons[0];
+        mouse(
+            &mut state,
+            MouseEventKind::Down(MouseButton::Left),
+            (yes.x, yes.y),
+        )?;
+        assert!(state.confirm.is_none());
+        assert_eq!(titles(&state, 0), ["T1"]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+mod common;
+
+#[cfg(test)]
+mod priority_tests {
+    use super::common::{create_connection, titles};
+    use anyhow::Error;
+    use kanban_tui::{Priority, State};
+
+    #[test]
+    fn it_sets_and_clears_priorities() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::new())?;
+
+        state.toggle_selected_task_priority(Priority::P1)?;
+        assert_eq!(
+            state.get_selected_task().unwrap().priority,
+            Some(Priority::P1)
+        );
+        state.toggle_selected_task_priority(Priority::P1)?;
+        assert_eq!(state.get_selected_task().unwrap().priority, None);
+        state.set_selected_task_priority(Some(Priority::P0))?;
+
+        let state = State::new(state.db_conn.0)?;
+        assert_eq!(state.columns[0].tasks[0].priority, Some(Priority::P0));
+
+        assert_eq!(Priority::cycle(None, true), Some(Priority::P0));
+        assert_eq!(Priority::cycle(Some(Priority::P3), true), None);
+        assert_eq!(Priority::cycle(None, false), Some(Priority::P3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_sorts_columns_by_priority_then_manual_order() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        for (title, priority) in [
+            ("A", None),
+            ("B", Some(Priority::P2)),
+            ("C", Some(Priority::P0)),
+            ("D", Some(Priority::P2)),
+        ] {
+            state.add_new_task(String::from(title), String::new())?;
+            state.set_selected_task_priority(priority)?;
+        }
+
+        state.toggle_priority_sort()?;
+        assert_eq!(titles(&state, 0), ["C", "B", "D", "A"]);
+        // The same task stays selected
+        assert_eq!(state.get_selected_task().unwrap().title, "D");
+
+        // Tasks with another priority can't be swapped
+        state.move_task_up()?;
+        assert_eq!(titles(&state, 0), ["C", "D", "B", "A"]);
+        state.move_task_up()?;
+        assert_eq!(titles(&state, 0), ["C", "D", "B", "A"]);
+        assert!(state.status_message.is_some());
+
+        // A new priority moves the task to its place right away
+        state.set_selected_task_priority(Some(Priority::P3))?;
+        assert_eq!(titles(&state, 0), ["C", "B", "D", "A"]);
+        assert_eq!(state.get_selected_task().unwrap().title, "D");
+        state.undo()?;
+        assert_eq!(titles(&state, 0), ["C", "D", "B", "A"]);
+
+        let mut state = State::new(state.db_conn.0)?;
+        assert!(state.columns[0].sort_by_priority);
+        assert_eq!(titles(&state, 0), ["C", "D", "B", "A"]);
+
+        // Going back to the manual order keeps the reordering
+        state.toggle_priority_sort()?;
+        assert_eq!(titles(&state, 0), ["A", "D", "C", "B"]);
+
+        Ok(())
+    }
+}
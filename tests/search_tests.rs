@@ -0,0 +1,106 @@
+mod common;
+
+#[cfg(test)]
+mod search_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use kanban_tui::State;
+    use std::collections::HashSet;
+
+    fn search(state: &mut State<'_>, query: &str) -> Result<(), Error> {
+        state.start_search();
+        state.search.as_mut().unwrap().query.insert_str(query);
+        state.update_search()
+    }
+
+    fn selected_title(state: &State<'_>) -> String {
+        state.get_selected_task().unwrap().title.clone()
+    }
+
+    #[test]
+    fn it_searches_titles_and_descriptions_by_prefix() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("Write docs"), String::from("For the CLI"))?;
+        state.add_new_task(
+            String::from("Fix bug"),
+            String::from("Crash in \"docs\" page"),
+        )?;
+        state.add_new_task(String::from("Release"), String::from("-"))?;
+        let ids: Vec<i64> = state.columns[0].tasks.iter().map(|t| t.id).collect();
+
+        let db = &state.db_conn;
+        assert_eq!(db.search_tasks("doc")?, HashSet::from([ids[0], ids[1]]));
+        assert_eq!(db.search_tasks("DOC cli")?, HashSet::from([ids[0]]));
+        assert_eq!(
+            db.search_tasks("\"docs\"")?,
+            HashSet::from([ids[0], ids[1]])
+        );
+        // FTS5 syntax is matched literally instead of failing
+        assert!(db.search_tasks("docs AND OR (")?.is_empty());
+        assert!(db.search_tasks("-")?.is_empty());
+        assert!(db.search_tasks("  ")?.is_empty());
+
+        // The index follows the changes made to the tasks
+        state.edit_task(String::from("Release docs"), String::from("-"))?;
+        state.select_first_task()?;
+        state.delete_task()?;
+        let db = &state.db_conn;
+        assert_eq!(db.search_tasks("doc")?, HashSet::from([ids[1], ids[2]]));
+        assert!(db.search_tasks("write")?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_filters_the_board_and_jumps_between_matches() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        for title in ["A match", "B", "C match", "D"] {
+            state.add_new_task(String::from(title), String::new())?;
+        }
+        state.select_column_right()?;
+        state.add_new_task(String::from("E"), String::new())?;
+        state.select_column_right()?;
+        state.add_new_task(String::from("F match"), String::new())?;
+        state.select_column_left()?;
+        state.select_column_left()?;
+        state.select_last_task()?;
+
+        search(&mut state, "mat")?;
+        // The selected task got hidden so the next match is selected
+        assert_eq!(selected_title(&state), "F match");
+        let visible = |state: &State<'_>, col: usize| {
+            state.columns[col]
+                .tasks
+                .iter()
+                .filter(|t| state.is_task_visible(t))
+                .count()
+        };
+        assert_eq!(visible(&state, 0), 2);
+        assert_eq!(visible(&state, 1), 0);
+
+        state.select_next_match(true)?;
+        assert_eq!(selected_title(&state), "A match");
+        state.select_next_match(true)?;
+        assert_eq!(selected_title(&state), "C match");
+        state.select_task_above()?;
+        assert_eq!(selected_title(&state), "A match");
+        state.select_task_below()?;
+        assert_eq!(selected_title(&state), "C match");
+        state.select_task_below()?;
+        assert_eq!(selected_title(&state), "C match");
+        state.select_next_match(false)?;
+        assert_eq!(selected_title(&state), "A match");
+        state.select_next_match(false)?;
+        assert_eq!(selected_title(&state), "F match");
+
+        // Typing more narrows the search down to nothing
+        search(&mut state, "zzz")?;
+        state.select_next_match(true)?;
+        assert_eq!(state.status_message.as_deref(), Some("No matches"));
+
+        state.clear_search();
+        assert_eq!(visible(&state, 0), 4);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+mod common;
+
+#[cfg(test)]
+mod stats_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use kanban_tui::{FlowMetrics, State, TaskEvent, TaskEventKind};
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn event(task_id: i64, kind: TaskEventKind, column_id: i64, created_at: i64) -> TaskEvent {
+        TaskEvent {
+            task_id,
+            kind,
+            column_id: Some(column_id),
+            column_name: None,
+            created_at,
+        }
+    }
+
+    #[test]
+    fn it_computes_cycle_time_lead_time_and_throughput() -> Result<(), Error> {
+        use TaskEventKind::{Created, Deleted, Moved};
+
+        let state = State::new(create_connection()?)?;
+        let (todo, in_progress, done) = (
+            state.columns[0].id,
+            state.columns[1].id,
+            state.columns[2].id,
+        );
+
+        let events = vec![
+            event(1, Created, todo, 0),
+            event(1, Moved, in_progress, DAY),
+            event(1, Moved, done, 3 * DAY),
+            event(2, Created, todo, 20 * DAY),
+            event(2, Moved, in_progress, 22 * DAY),
+            event(2, Moved, done, 29 * DAY),
+            event(2, Deleted, done, 29 * DAY + DAY / 2),
+            // Moved back out of Done so it doesn't count as completed
+            event(3, Created, todo, 25 * DAY),
+            event(3, Moved, in_progress, 26 * DAY),
+            event(3, Moved, done, 27 * DAY),
+            event(3, Moved, in_progress, 28 * DAY),
+        ];
+        let metrics = FlowMetrics::from_events(&events, &state.columns, &[done], 30 * DAY);
+
+        let todo = &metrics.cycle_times[0];
+        assert_eq!(todo.name, "Todo");
+        assert_eq!(todo.samples, 3);
+        assert_eq!(todo.average, Some(4 * DAY / 3));
+        assert_eq!(todo.median, Some(DAY));
+
+        let in_progress = &metrics.cycle_times[1];
+        assert_eq!(in_progress.samples, 3);
+        assert_eq!(in_progress.average, Some(10 * DAY / 3));
+        assert_eq!(in_progress.median, Some(2 * DAY));
+
+        let done = &metrics.cycle_times[2];
+        assert_eq!(done.samples, 2);
+        assert_eq!(done.average, Some(3 * DAY / 4));
+        assert_eq!(done.median, Some(3 * DAY / 4));
+
+        let ideas = &metrics.cycle_times[3];
+        assert_eq!(ideas.samples, 0);
+        assert_eq!(ideas.average, None);
+        assert_eq!(ideas.median, None);
+
+        assert_eq!(metrics.lead_time_average, Some(6 * DAY));
+        assert_eq!(metrics.lead_time_median, Some(6 * DAY));
+        assert_eq!(metrics.weekly_throughput, [1, 0, 0, 1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_computes_flow_metrics_from_the_board() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+
+        let metrics = state.get_flow_metrics()?;
+        assert_eq!(metrics.lead_time_average, None);
+        assert_eq!(metrics.weekly_throughput, [0, 0, 0, 0]);
+
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.move_task_column_right()?;
+        state.move_task_column_right()?;
+
+        let metrics = state.get_flow_metrics()?;
+        assert_eq!(metrics.cycle_times[0].samples, 1);
+        assert_eq!(metrics.cycle_times[1].samples, 1);
+        assert_eq!(metrics.cycle_times[2].samples, 0);
+        assert!(metrics.lead_time_average.is_some());
+        assert_eq!(metrics.weekly_throughput, [1, 0, 0, 0]);
+
+        state.toggle_flow_metrics()?;
+        assert!(state.flow_metrics.is_some());
+        state.toggle_flow_metrics()?;
+        assert!(state.flow_metrics.is_none());
+
+        Ok(())
+    }
+}
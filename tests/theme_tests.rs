@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod theme_tests {
+    use anyhow::Error;
+    use kanban_tui::{parse_color, Config, Theme};
+    use tui::style::{Color, Modifier, Style};
+
+    #[test]
+    fn it_parses_colors() -> Result<(), Error> {
+        assert_eq!(parse_color("red")?, Color::Red);
+        assert_eq!(parse_color("Light-Blue")?, Color::LightBlue);
+        assert_eq!(parse_color("#ff8800")?, Color::Rgb(255, 136, 0));
+        assert_eq!(parse_color("208")?, Color::Indexed(208));
+        for text in ["", "purple", "#ff88", "#gg8800", "256", "#éé88"] {
+            assert!(parse_color(text).is_err(), "{text}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn it_applies_overrides_on_top_of_a_builtin_theme() -> Result<(), Error> {
+        for name in Theme::BUILTIN_NAMES {
+            assert!(Theme::builtin(name).is_some(), "{name}");
+        }
+        assert_eq!(Theme::new(&Config::from_toml("")?.theme)?, Theme::default());
+
+        let config = Config::from_toml(
+            r##"
+            [theme]
+            name = "high-contrast"
+            emoji = false
+            overdue = { fg = "#ff0000", modifiers = ["bold", "underlined"] }
+            selected-column = {}
+            "##,
+        )?;
+        let theme = Theme::new(&config.theme)?;
+        let high_contrast = Theme::builtin("high-contrast").unwrap();
+        assert_eq!(theme.popup, high_contrast.popup);
+        assert_eq!(
+            theme.overdue,
+            Style::default()
+                .fg(Color::Rgb(255, 0, 0))
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+        );
+        assert_eq!(theme.selected_column, Style::default());
+        assert!(!theme.emoji);
+        assert_eq!(theme.selected_marker(), "");
+        assert_eq!(theme.blocked_marker(), "[blocked] ");
+        assert_eq!(Theme::default().selected_marker(), " 👈");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_reports_invalid_themes() -> Result<(), Error> {
+        let error = |toml: &str| -> Result<String, Error> {
+            let config = Config::from_toml(toml)?;
+            Ok(format!("{:#}", Theme::new(&config.theme).unwrap_err()))
+        };
+        assert_eq!(
+            error("[theme]\nname = \"neon\"")?,
+            "Unknown theme \"neon\", the built-in ones are default, high-contrast, light"
+        );
+        assert_eq!(
+            error("[theme]\nlabel = { bg = \"purple\" }")?,
+            "Invalid style for \"label\": Unknown color \"purple\""
+        );
+        assert_eq!(
+            error("[theme]\nborder = { modifiers = [\"shiny\"] }")?,
+            "Invalid style for \"border\": Unknown modifier \"shiny\""
+        );
+        assert!(Config::from_toml("[theme]\ncolumns = {}").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+mod common;
+
+#[cfg(test)]
+mod trash_tests {
+    use super::common::{create_connection, run, titles};
+    use anyhow::Error;
+    use kanban_tui::{Command, Config, OffBoardKind, State};
+
+    #[test]
+    fn it_asks_before_deleting_and_restores_from_the_trash() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+
+        state.ask_delete_task();
+        state.confirm = None;
+        assert_eq!(titles(&state, 0), ["T1", "T2"]);
+        state.ask_delete_task();
+        state.confirm_action()?;
+        assert_eq!(titles(&state, 0), ["T1"]);
+        assert!(state.db_conn.search_tasks("D2")?.is_empty());
+
+        // Deleted tasks survive a reload in the trash
+        let mut state = State::new(state.db_conn.0)?;
+        assert_eq!(titles(&state, 0), ["T1"]);
+        state.open_archive(OffBoardKind::Deleted)?;
+        assert_eq!(state.get_archive_entries().len(), 1);
+        state.archive.as_mut().unwrap().query.insert_str("D1");
+        state.update_archive_search()?;
+        assert!(state.get_archive_entries().is_empty());
+        state.archive.as_mut().unwrap().query = Default::default();
+        state.archive.as_mut().unwrap().query.insert_str("D2");
+        state.update_archive_search()?;
+        state.restore_off_board_task()?;
+        assert_eq!(titles(&state, 0), ["T1", "T2"]);
+        assert_eq!(state.get_selected_task().unwrap().title, "T2");
+
+        // Undoing the restore puts it back in the trash
+        state.undo()?;
+        assert_eq!(titles(&state, 0), ["T1"]);
+        assert_eq!(state.db_conn.get_trashed_tasks(1)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_trashes_the_tasks_of_a_deleted_column() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::new())?;
+        state.select_column_right()?;
+        state.add_new_task(String::from("T2"), String::new())?;
+        state.add_new_task(String::from("T3"), String::new())?;
+        state.delete_column(false)?;
+        assert_eq!(state.columns.len(), 3);
+
+        // The column is gone so the first one takes them back
+        let mut state = State::new(state.db_conn.0)?;
+        state.open_archive(OffBoardKind::Deleted)?;
+        assert_eq!(state.get_archive_entries().len(), 2);
+        state.restore_off_board_task()?;
+        assert_eq!(titles(&state, 0), ["T1", "T3"]);
+        assert_eq!(
+            state.status_message.as_deref(),
+            Some("Restored \"T3\" to Todo")
+        );
+        assert_eq!(state.db_conn.get_trashed_tasks(1)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_asks_before_deleting_a_label() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.set_selected_task_labels(&[String::from("bug")])?;
+
+        state.ask_delete_label(0);
+        assert_eq!(state.labels.len(), 1);
+        state.confirm_action()?;
+        assert!(state.labels.is_empty());
+        assert!(state.get_selected_task().unwrap().labels.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_purges_old_tasks_from_the_trash() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("Old"), String::new())?;
+        state.add_new_task(String::from("Recent"), String::new())?;
+        state.delete_task()?;
+        state.delete_task()?;
+        state.db_conn.execute(
+            "update task set deleted_at = deleted_at - 31 * 86400 where title = 'Old'",
+            (),
+        )?;
+
+        assert_eq!(state.db_conn.purge_trash(30)?, 1);
+        assert_eq!(Config::from_toml("trash-days = 7")?.trash_days, Some(7));
+        assert_eq!(Config::from_toml("")?.trash_days, None);
+        let mut db_conn = state.db_conn;
+        assert_eq!(run(&mut db_conn, Command::Trash, false)?, "#2 Recent\n");
+
+        let restore = |id| Command::Restore { id };
+        assert_eq!(run(&mut db_conn, restore(2), false)?, "#2 Recent\n");
+        assert!(run(&mut db_conn, restore(1), false).is_err());
+        run(&mut db_conn, Command::Rm { id: 2 }, false)?;
+        run(&mut db_conn, restore(2), false)?;
+        assert_eq!(titles(&State::new(db_conn.0)?, 0), ["Recent"]);
+
+        Ok(())
+    }
+}
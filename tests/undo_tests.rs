@@ -0,0 +1,156 @@
+mod common;
+
+#[cfg(test)]
+mod undo_tests {
+    use super::common::create_connection;
+    use anyhow::Error;
+    use kanban_tui::{Operation, State, TaskEventKind};
+
+    /// Titles of every column, so the whole board can be compared at once
+    fn board(state: &State<'_>) -> Vec<Vec<String>> {
+        state
+            .columns
+            .iter()
+            .map(|c| c.tasks.iter().map(|t| t.title.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn it_undoes_and_redoes_every_task_change() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.add_new_task(String::from("T3"), String::from("D3"))?;
+        let initial = board(&state);
+
+        state.move_task_up()?;
+        state.edit_task(String::from("T3 edited"), String::from("D3"))?;
+        state.move_task_column_right()?;
+        state.select_column_left()?;
+        state.select_first_task()?;
+        state.delete_task()?;
+        let changed = board(&state);
+        assert_eq!(changed, [vec!["T2"], vec!["T3 edited"], vec![], vec![]]);
+
+        for _ in 0..3 {
+            state.undo()?;
+        }
+        assert_eq!(
+            board(&state),
+            [vec!["T1", "T3", "T2"], vec![], vec![], vec![]]
+        );
+        state.undo()?;
+        assert_eq!(board(&state), initial);
+        assert_eq!(state.status_message.as_deref(), Some("Undid reorder task"));
+        state.undo()?;
+        state.undo()?;
+        state.undo()?;
+        assert!(board(&state).iter().all(Vec::is_empty));
+        state.undo()?;
+        assert_eq!(state.status_message.as_deref(), Some("Nothing to undo"));
+
+        for _ in 0..7 {
+            state.redo()?;
+        }
+        assert_eq!(board(&state), changed);
+        state.redo()?;
+        assert_eq!(state.status_message.as_deref(), Some("Nothing to redo"));
+
+        // The DB has to agree with what's in memory
+        let state = State::new(state.db_conn.0)?;
+        assert_eq!(board(&state), changed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rolls_back_a_batch_that_fails_half_way() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        let after = state.get_selected_task().unwrap().clone();
+        let mut before = after.clone();
+        before.title = String::from("T1 old");
+        let mut missing = after.clone();
+        missing.id = 99;
+        // Undone in reverse, so T1 gets reverted before the missing
+        // task stops the batch
+        state.history.record(Operation::Batch(vec![
+            Operation::EditTask {
+                before: missing.clone(),
+                after: missing,
+            },
+            Operation::EditTask { before, after },
+        ]));
+
+        state.undo()?;
+        assert!(!state.history.can_undo());
+        assert_eq!(state.get_selected_task().unwrap().title, "T1");
+        let state = State::new(state.db_conn.0)?;
+        assert_eq!(state.get_selected_task().unwrap().title, "T1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_only_logs_edits_that_change_the_text() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        let kinds = |state: &State<'_>| -> Result<Vec<TaskEventKind>, Error> {
+            let history = state.get_selected_task_history()?;
+            Ok(history.iter().map(|e| e.kind).collect())
+        };
+
+        // Like confirming the task form after changing only a label
+        state.history.start_batch();
+        state.edit_task(String::from("T1"), String::from("D1"))?;
+        state.set_selected_task_labels(&[String::from("bug")])?;
+        state.history.finish_batch();
+        assert_eq!(kinds(&state)?, [TaskEventKind::Created]);
+        state.undo()?;
+        assert!(state.get_selected_task().unwrap().labels.is_empty());
+
+        state.edit_task(String::from("T1 edited"), String::from("D1"))?;
+        let edited = [TaskEventKind::Created, TaskEventKind::Edited];
+        assert_eq!(kinds(&state)?, edited);
+        state.undo()?;
+        assert_eq!(state.get_selected_task().unwrap().title, "T1");
+        state.redo()?;
+        assert_eq!(state.get_selected_task().unwrap().title, "T1 edited");
+        assert_eq!(kinds(&state)?, edited);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_restores_a_deleted_task_as_it_was() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.add_new_task(String::from("T1"), String::from("D1"))?;
+        state.add_new_task(String::from("T2"), String::from("D2"))?;
+        state.add_new_task(String::from("T3"), String::from("D3"))?;
+        state.select_task_above()?;
+        let deleted = state.get_selected_task().unwrap().clone();
+
+        state.delete_task()?;
+        state.select_column_right()?;
+        state.undo()?;
+
+        assert_eq!(state.selected_column_idx, 0);
+        assert_eq!(state.get_selected_task(), Some(&deleted));
+        let history = state.get_selected_task_history()?;
+        assert_eq!(history.last().unwrap().kind, TaskEventKind::Restored);
+
+        let mut state = State::new(state.db_conn.0)?;
+        assert_eq!(board(&state)[0], ["T1", "T2", "T3"]);
+        assert_eq!(state.columns[0].tasks[1], deleted);
+
+        // A new change can't be mixed with the undone ones
+        state.add_new_task(String::from("T4"), String::from("D4"))?;
+        state.undo()?;
+        state.add_new_task(String::from("T5"), String::from("D5"))?;
+        state.redo()?;
+        assert_eq!(state.status_message.as_deref(), Some("Nothing to redo"));
+        assert_eq!(board(&state)[0], ["T1", "T2", "T3", "T5"]);
+
+        Ok(())
+    }
+}
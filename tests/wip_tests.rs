@@ -0,0 +1,84 @@
+mod common;
+
+#[cfg(test)]
+mod wip_tests {
+    use super::common::{create_connection, titles};
+    use anyhow::Error;
+    use kanban_tui::{export_board, import_board, Config, ImportMode, State, WipLimitMode};
+
+    #[test]
+    fn it_warns_when_going_over_a_wip_limit() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.set_column_wip_limit(Some(1))?;
+        state.add_new_task(String::from("T1"), String::new())?;
+        assert_eq!(state.status_message, None);
+        state.add_new_task(String::from("T2"), String::new())?;
+        assert_eq!(titles(&state, 0), ["T1", "T2"]);
+        assert_eq!(
+            state.status_message.as_deref(),
+            Some("Todo is over its WIP limit (2/1)")
+        );
+        assert!(state.columns[0].is_over_wip_limit());
+
+        // The limit survives a reload
+        let state = State::new(state.db_conn.0)?;
+        assert_eq!(state.columns[0].wip_limit, Some(1));
+        assert_eq!(state.columns[1].wip_limit, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_refuses_to_go_over_a_wip_limit() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.wip_limit_mode = WipLimitMode::Refuse;
+        state.add_new_task(String::from("T1"), String::new())?;
+        state.add_new_task(String::from("T2"), String::new())?;
+        state.select_column_right()?;
+        state.set_column_wip_limit(Some(1))?;
+        state.select_column_left()?;
+
+        state.move_task_column_right()?;
+        assert_eq!(state.status_message, None);
+        state.select_column_left()?;
+        state.move_task_column_right()?;
+        assert_eq!(titles(&state, 0), ["T1"]);
+        assert_eq!(titles(&state, 1), ["T2"]);
+        assert_eq!(
+            state.status_message.as_deref(),
+            Some("InProgress is at its WIP limit of 1, finish something first")
+        );
+
+        state.select_column_right()?;
+        state.add_new_task(String::from("T3"), String::new())?;
+        assert_eq!(titles(&state, 1), ["T2"]);
+
+        // Making room lets the task in
+        state.move_task_column_right()?;
+        state.select_column_left()?;
+        state.select_column_left()?;
+        state.move_task_column_right()?;
+        assert_eq!(titles(&state, 1), ["T1"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_wip_limits_in_exports_and_reads_the_mode_from_config() -> Result<(), Error> {
+        let mut state = State::new(create_connection()?)?;
+        state.set_column_wip_limit(Some(3))?;
+        let board = export_board(&state.db_conn, 1)?;
+        assert_eq!(board.columns[0].wip_limit, Some(3));
+
+        let db_conn = State::new(create_connection()?)?.db_conn;
+        import_board(&db_conn, 1, &board, ImportMode::Replace)?;
+        assert_eq!(State::new(db_conn.0)?.columns[0].wip_limit, Some(3));
+
+        assert_eq!(Config::from_toml("")?.wip_limit, WipLimitMode::Warn);
+        let config = Config::from_toml("wip-limit = \"refuse\"")?;
+        assert_eq!(config.wip_limit, WipLimitMode::Refuse);
+        assert!(Config::from_toml("wip-limit = \"never\"").is_err());
+
+        Ok(())
+    }
+}